@@ -0,0 +1,13 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use othello_gui::testing;
+
+// every byte chooses a move, so the fuzzer explores games by changing the bytes
+fuzz_target!(|data: &[u8]| {
+    let playout = testing::playout_from_bytes(data);
+
+    if let Err(err) = testing::check_playout(&playout) {
+        panic!("{err}, moves: {:?}", playout.moves);
+    }
+});
@@ -0,0 +1,953 @@
+use std::{
+    fmt,
+    future::Future,
+    io, mem, panic,
+    process::{self, ExitStatus, Stdio},
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc, Mutex, OnceLock, PoisonError,
+    },
+    thread,
+    time::*,
+};
+use tokio::{
+    io::{AsyncRead, AsyncReadExt, AsyncWriteExt},
+    process::{Child, Command},
+    runtime::{Handle, Runtime},
+    sync::oneshot::{self, error::TryRecvError},
+    task::{AbortHandle, JoinHandle},
+    time,
+};
+
+use crate::{engine_config::*, error::Error, rules::Rules, *};
+
+#[derive(Debug)]
+pub struct AI {
+    pub config: EngineConfig,
+    pub limit: Limit,
+    pub ai_run_handle: Option<AIRunHandle>,
+    // the logical CPU the AI is restricted to
+    pub pinned_core: Option<usize>,
+    // asked not to use randomness, with --deterministic
+    pub deterministic: bool,
+    // told the rules, with --rules
+    pub rules: Rules,
+}
+
+impl AI {
+    pub fn input(&self, pos: Pos) -> String {
+        self.config.option_lines() + &rules_input(pos, self.rules, self.limit, self.deterministic)
+    }
+
+    pub fn run(&mut self, pos: Pos) -> io::Result<()> {
+        self.run_input(self.input(pos))
+    }
+
+    /// Runs the AI with `input` as it is, e.g. to send it the input of a captured run again.
+    pub fn run_input(&mut self, input: String) -> io::Result<()> {
+        // spawning the process needs the runtime's reactor
+        let _guard = runtime().enter();
+
+        let mut command = run::command(&self.config);
+
+        if let Some(core) = self.pinned_core {
+            system::pin_to_core(&mut command, core);
+        }
+
+        system::own_process_group(&mut command);
+
+        let child = Command::from(command)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()?;
+
+        let deadline = Instant::now() + self.limit.time_limit();
+        let io = Arc::new(Mutex::new(EngineIo::default()));
+
+        let mut handle = AIRunHandle::spawn(run_child(child, input, deadline, Arc::clone(&io)));
+        handle.io = Some(io);
+
+        self.ai_run_handle = Some(handle);
+
+        Ok(())
+    }
+
+    pub fn new(config: EngineConfig, limit: Limit) -> Self {
+        Self {
+            config,
+            limit,
+            ai_run_handle: None,
+            pinned_core: None,
+            deterministic: false,
+            rules: Rules::Othello,
+        }
+    }
+
+    pub fn try_clone(&self) -> Result<Self, Error> {
+        match self.ai_run_handle {
+            None => Ok(Self {
+                config: self.config.clone(),
+                limit: self.limit,
+                ai_run_handle: None,
+                pinned_core: self.pinned_core,
+                deterministic: self.deterministic,
+                rules: self.rules,
+            }),
+            Some(_) => Err(Error::CloneRunningEngine(
+                self.config.path.display().to_string(),
+            )),
+        }
+    }
+}
+
+/// What the search of an AI is limited by.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Limit {
+    Time(Duration),
+    // for AIs honoring these, they make games deterministic and independent of the hardware
+    Depth(u32),
+    Nodes(u64),
+}
+
+// searching to a given depth or node count may take long, but hanging AIs are still stopped
+// eventually
+const SEARCH_TIME_LIMIT: Duration = Duration::from_secs(60);
+
+impl Limit {
+    /// The limit as it is given on the command line, e.g. `100ms` or `depth:6`.
+    pub fn arg(self) -> String {
+        match self {
+            Limit::Time(time_limit) => format!("{}ms", time_limit.as_millis()),
+            Limit::Depth(depth) => format!("depth:{depth}"),
+            Limit::Nodes(nodes) => format!("nodes:{nodes}"),
+        }
+    }
+
+    /// The time after which the AI is stopped.
+    pub fn time_limit(self) -> Duration {
+        match self {
+            Limit::Time(time_limit) => time_limit,
+            Limit::Depth(_) | Limit::Nodes(_) => SEARCH_TIME_LIMIT,
+        }
+    }
+}
+
+impl fmt::Display for Limit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Limit::Time(time_limit) => write!(f, "{} ms", time_limit.as_millis()),
+            Limit::Depth(depth) => write!(f, "depth {depth}"),
+            Limit::Nodes(nodes) => write!(f, "{nodes} nodes"),
+        }
+    }
+}
+
+/// The node count reported in the notes as `nodes=<n>`, with which node limits are checked.
+pub fn reported_nodes(notes: &str) -> Option<u64> {
+    notes
+        .split_whitespace()
+        .find_map(|token| token.strip_prefix("nodes="))
+        .and_then(|nodes| nodes.parse().ok())
+}
+
+/// The input of engines, which is the same regardless of how they are reached.
+pub fn engine_input(pos: Pos, limit: Limit, deterministic: bool) -> String {
+    rules_input(pos, Rules::Othello, limit, deterministic)
+}
+
+/// The input of AIs playing by `rules`, which are told the rules unless they are those of Othello.
+pub fn rules_input(pos: Pos, rules: Rules, limit: Limit, deterministic: bool) -> String {
+    let valid_moves = rules.valid_moves(&pos);
+
+    format!(
+        "{}{}{}\n{}\n{} {}\n{}",
+        // only sent for other rules, so AIs reading a fixed number of lines keep working
+        match rules {
+            Rules::Othello => String::new(),
+            rules => format!("rules {rules}\n"),
+        },
+        pos.board,
+        pos.next_player,
+        match limit {
+            Limit::Time(time_limit) => time_limit.as_millis().to_string(),
+            Limit::Depth(depth) => format!("depth {depth}"),
+            Limit::Nodes(nodes) => format!("nodes {nodes}"),
+        },
+        valid_moves.len(),
+        valid_moves
+            .iter()
+            .map(|mv| mv.move_string())
+            .collect::<Vec<_>>()
+            .join(" "),
+        // only sent when asked for, so AIs reading a fixed number of lines keep working
+        if deterministic { "deterministic\n" } else { "" }
+    )
+}
+
+const PREFLIGHT_TIME_LIMIT: Duration = Duration::from_millis(1000);
+const PREFLIGHT_NODE_LIMIT: u64 = 1000;
+
+/// How an AI did in its preflight run.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Preflight {
+    Passed,
+    // it exceeded the shortened time limit of the preflight, but may be fine with its own
+    Slow(String),
+    Failed(String),
+}
+
+/// Runs every AI once on the starting position with a short time limit, to find out whether
+/// they work at all before hours are spent playing games with them. At most `max_concurrency`
+/// AIs are run at once, so that they don't time out only because they slow each other down.
+pub fn preflight(ais: &mut [AI], max_concurrency: usize) -> Vec<Preflight> {
+    let pos = Pos::new();
+
+    let limits: Vec<Limit> = ais.iter().map(|ai| ai.limit).collect();
+    let mut results: Vec<Option<Preflight>> = vec![None; ais.len()];
+    let mut started = 0;
+
+    while results.iter().any(Option::is_none) {
+        let running = ais[..started]
+            .iter()
+            .filter(|ai| ai.ai_run_handle.is_some())
+            .count();
+
+        for (ai, result) in ais
+            .iter_mut()
+            .zip(&mut results)
+            .skip(started)
+            .take(max_concurrency.max(1).saturating_sub(running))
+        {
+            ai.limit = match ai.limit {
+                Limit::Time(time_limit) => Limit::Time(time_limit.min(PREFLIGHT_TIME_LIMIT)),
+                // only whether the AI works is checked, not how long deep searches take
+                Limit::Depth(_) => Limit::Depth(1),
+                Limit::Nodes(nodes) => Limit::Nodes(nodes.min(PREFLIGHT_NODE_LIMIT)),
+            };
+
+            if let Err(err) = ai.run(pos) {
+                *result = Some(Preflight::Failed(format!("unable to run AI: {err}")));
+            }
+
+            started += 1;
+        }
+
+        thread::sleep(Duration::from_millis(5));
+
+        for ((ai, result), limit) in ais[..started].iter_mut().zip(&mut results).zip(&limits) {
+            let Some(handle) = ai.ai_run_handle.as_mut() else {
+                continue;
+            };
+
+            *result = match handle.check() {
+                AIRunResult::Running => continue,
+                AIRunResult::Success(output) if pos.is_valid_move(output.mv) => {
+                    Some(Preflight::Passed)
+                }
+                AIRunResult::Success(output) => Some(Preflight::Failed(format!(
+                    "invalid move played: {}",
+                    output.mv.move_string()
+                ))),
+                AIRunResult::TimeOut { .. } => {
+                    let message = format!(
+                        "exceeded the time limit of {} ms",
+                        ai.limit.time_limit().as_millis()
+                    );
+
+                    if limit.time_limit() > ai.limit.time_limit() {
+                        Some(Preflight::Slow(message))
+                    } else {
+                        Some(Preflight::Failed(message))
+                    }
+                }
+                AIRunResult::RuntimeError { status, .. } => Some(Preflight::Failed(format!(
+                    "program exit code was non-zero: {status}"
+                ))),
+                AIRunResult::InvalidOuput(err) => Some(Preflight::Failed(err.to_string())),
+                AIRunResult::ConnectionError(err) => {
+                    Some(Preflight::Failed(format!("unable to connect: {err}")))
+                }
+            };
+
+            ai.ai_run_handle = None;
+        }
+    }
+
+    results.into_iter().map(|result| result.unwrap()).collect()
+}
+
+/// Finds the result of a position with perfect play, to end arena games early once there are
+/// few empty squares left.
+pub trait EndgameSolver: fmt::Debug {
+    /// Starts solving `pos` in the async runtime. The result is the winner with perfect play,
+    /// `Tile::Empty` for a draw.
+    fn start(&self, pos: Pos) -> SolveHandle;
+}
+
+/// A position being solved, the solver is stopped if the handle is dropped.
+#[derive(Debug)]
+pub struct SolveHandle {
+    result: oneshot::Receiver<Result<Tile, String>>,
+    task: AbortHandle,
+}
+
+impl SolveHandle {
+    fn spawn(solve: impl Future<Output = Result<Tile, String>> + Send + 'static) -> Self {
+        let (sender, receiver) = oneshot::channel();
+
+        let task = runtime().spawn(async move {
+            // the receiver is gone if the game ended in the meantime
+            let _ = sender.send(solve.await);
+        });
+
+        let handle = Self {
+            result: receiver,
+            task: task.abort_handle(),
+        };
+
+        register_task(task);
+
+        handle
+    }
+
+    /// The result, once the solver finished.
+    pub fn check(&mut self) -> Option<Result<Tile, String>> {
+        match self.result.try_recv() {
+            Ok(result) => Some(result),
+            Err(TryRecvError::Empty) => None,
+            Err(TryRecvError::Closed) => Some(Err("solver ended without a result".to_owned())),
+        }
+    }
+}
+
+impl Drop for SolveHandle {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+// the built-in solver gives up after searching this many positions, which takes a few seconds
+const BUILTIN_SOLVER_NODE_LIMIT: u64 = 20_000_000;
+
+/// Searches to the end of the game, which is only fast enough with few empty squares.
+#[derive(Debug, Default)]
+pub struct BuiltinSolver;
+
+impl EndgameSolver for BuiltinSolver {
+    fn start(&self, pos: Pos) -> SolveHandle {
+        // the search doesn't yield, so it runs on a thread of its own
+        let search = runtime().spawn_blocking(move || {
+            let mut nodes = 0;
+
+            match solve_value(pos, -1, 1, &mut nodes) {
+                Some(1) => Ok(Tile::X),
+                Some(-1) => Ok(Tile::O),
+                Some(_) => Ok(Tile::Empty),
+                None => Err(format!(
+                    "not solved within {BUILTIN_SOLVER_NODE_LIMIT} positions"
+                )),
+            }
+        });
+
+        SolveHandle::spawn(async move {
+            search
+                .await
+                .unwrap_or_else(|err| Err(format!("solver failed: {err}")))
+        })
+    }
+}
+
+// 1 if X wins with perfect play, -1 if O does and 0 for a draw, with alpha-beta pruning, `None`
+// once more than `BUILTIN_SOLVER_NODE_LIMIT` positions were searched
+fn solve_value(pos: Pos, mut alpha: i8, mut beta: i8, nodes: &mut u64) -> Option<i8> {
+    *nodes += 1;
+
+    if *nodes > BUILTIN_SOLVER_NODE_LIMIT {
+        return None;
+    }
+
+    if pos.is_game_over() {
+        return Some(match pos.winner() {
+            Tile::X => 1,
+            Tile::O => -1,
+            Tile::Empty => 0,
+        });
+    }
+
+    // players may have to pass, so who is to move is checked in every position
+    let maximizing = pos.next_player == Tile::X;
+    let mut best = if maximizing { -2 } else { 2 };
+
+    // moves leaving the opponent few replies are searched first, they cut off the search soonest
+    let mut children: Vec<Pos> = pos
+        .valid_moves()
+        .into_iter()
+        .map(|mv| pos.play_clone(mv))
+        .collect();
+    children.sort_by_cached_key(|child| child.valid_moves().len());
+
+    for child in children {
+        let value = solve_value(child, alpha, beta, nodes)?;
+
+        if maximizing {
+            best = best.max(value);
+            alpha = alpha.max(value);
+        } else {
+            best = best.min(value);
+            beta = beta.min(value);
+        }
+
+        if alpha >= beta {
+            break;
+        }
+    }
+
+    Some(best)
+}
+
+const SOLVER_TIME_LIMIT: Duration = Duration::from_secs(10);
+
+/// A program solving positions. It is given the same input as AIs, and has to output the winner
+/// with perfect play: `X`, `O` or `draw`.
+#[derive(Debug)]
+pub struct ExternalSolver {
+    pub config: EngineConfig,
+}
+
+impl EndgameSolver for ExternalSolver {
+    fn start(&self, pos: Pos) -> SolveHandle {
+        let command = run::command(&self.config);
+        let input = engine_input(pos, Limit::Time(SOLVER_TIME_LIMIT), false);
+
+        SolveHandle::spawn(async move {
+            let mut child = Command::from(command)
+                .stdin(Stdio::piped())
+                .stdout(Stdio::piped())
+                .stderr(Stdio::null())
+                .kill_on_drop(true)
+                .spawn()
+                .map_err(|err| format!("unable to run solver: {err}"))?;
+
+            let mut stdin = child.stdin.take().expect("Error getting stdin of solver");
+            let _ = stdin.write_all(input.as_bytes()).await;
+            drop(stdin);
+
+            // on timeout the child is dropped, which kills it
+            let output = time::timeout(SOLVER_TIME_LIMIT, child.wait_with_output())
+                .await
+                .map_err(|_| "solver exceeded time limit".to_owned())?
+                .map_err(|err| format!("error waiting for solver: {err}"))?;
+
+            if !output.status.success() {
+                return Err(format!("solver exit code was non-zero: {}", output.status));
+            }
+
+            match String::from_utf8_lossy(&output.stdout).trim() {
+                "X" => Ok(Tile::X),
+                "O" => Ok(Tile::O),
+                "draw" => Ok(Tile::Empty),
+                other => Err(format!("invalid solver output '{other}'")),
+            }
+        })
+    }
+}
+
+#[derive(Debug)]
+pub enum AIRunResult {
+    Running,
+    TimeOut { partial_output: String },
+    RuntimeError { status: ExitStatus, stderr: String },
+    InvalidOuput(Error),
+    ConnectionError(String),
+    Success(EngineOutput),
+}
+
+/// Everything an AI was sent and wrote in a run, as it was.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EngineIo {
+    pub input: String,
+    pub stdout: String,
+    pub stderr: String,
+}
+
+#[derive(Debug)]
+pub struct EngineOutput {
+    pub mv: Vec2,
+    pub notes: Option<String>,
+    // from the optional `version` line, so results can tell which build of the engine played
+    pub version: Option<String>,
+    // misbehavior which doesn't lose the game, e.g. not exiting after moving
+    pub warnings: Vec<String>,
+}
+
+pub(crate) fn runtime() -> &'static Runtime {
+    static RUNTIME: OnceLock<Runtime> = OnceLock::new();
+
+    RUNTIME.get_or_init(|| Runtime::new().expect("Error creating async runtime"))
+}
+
+// Every AI task is registered here, so that exit paths which skip destructors
+// (`process::exit`, panics) can still kill the AIs instead of leaving orphans behind.
+static TASKS: Mutex<Vec<JoinHandle<()>>> = Mutex::new(Vec::new());
+
+pub(crate) fn register_task(task: JoinHandle<()>) {
+    let mut tasks = TASKS.lock().unwrap_or_else(PoisonError::into_inner);
+
+    tasks.retain(|task| !task.is_finished());
+    tasks.push(task);
+}
+
+// Counts the AI runs which finished, so that the UI only looks at the games when an AI may have
+// moved, instead of checking every running AI every frame.
+static FINISHED_RUNS: AtomicUsize = AtomicUsize::new(0);
+
+/// The number of AI runs finished so far, changes whenever a result can be checked.
+pub fn finished_runs() -> usize {
+    FINISHED_RUNS.load(Ordering::Acquire)
+}
+
+// called from the async runtime whenever an AI run finished
+static ON_RUN_FINISHED: Mutex<Option<Box<dyn Fn() + Send>>> = Mutex::new(None);
+
+/// Sets what is called whenever an AI run finished, e.g. to wake up an event loop waiting for
+/// the result.
+pub fn on_run_finished(callback: impl Fn() + Send + 'static) {
+    *ON_RUN_FINISHED
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner) = Some(Box::new(callback));
+}
+
+pub fn kill_all_children() {
+    let tasks = mem::take(&mut *TASKS.lock().unwrap_or_else(PoisonError::into_inner));
+
+    if tasks.is_empty() {
+        return;
+    }
+
+    for task in &tasks {
+        task.abort();
+    }
+
+    // Aborted tasks drop their child, which kills it. Wait for that to happen, unless called
+    // from inside the runtime, where blocking isn't possible.
+    if Handle::try_current().is_err() {
+        runtime().block_on(async {
+            for task in tasks {
+                let _ = task.await;
+            }
+        });
+    }
+}
+
+/// Kills all running AIs before exiting, use instead of `process::exit` when AIs might be running.
+pub fn exit(code: i32) -> ! {
+    kill_all_children();
+    process::exit(code);
+}
+
+pub fn kill_children_on_panic() {
+    let default_hook = panic::take_hook();
+
+    panic::set_hook(Box::new(move |info| {
+        kill_all_children();
+        default_hook(info);
+    }));
+}
+
+// how long an AI may keep running after writing its move, before it is killed
+const EXIT_GRACE_PERIOD: Duration = Duration::from_millis(500);
+const EXIT_CHECK_INTERVAL: Duration = Duration::from_millis(10);
+// AIs writing more than this to stdout or stderr lose, so that runaway output can't fill the
+// memory, the console or log files
+pub(crate) const MAX_OUTPUT_SIZE: usize = 1024 * 1024;
+
+async fn run_child(
+    mut child: Child,
+    input: String,
+    deadline: Instant,
+    io: Arc<Mutex<EngineIo>>,
+) -> AIRunResult {
+    let mut stdout = PipeReader::new(
+        child
+            .stdout
+            .take()
+            .expect("Error getting stdout of program"),
+    );
+    let mut stderr = PipeReader::new(
+        child
+            .stderr
+            .take()
+            .expect("Error getting stderr of program"),
+    );
+
+    let result = run_piped(child, &input, deadline, &mut stdout, &mut stderr).await;
+
+    // the result is sent after this, so the io is complete by the time it is checked
+    *io.lock().unwrap_or_else(PoisonError::into_inner) = EngineIo {
+        input,
+        stdout: stdout.read_so_far(),
+        stderr: stderr.read_so_far(),
+    };
+
+    result
+}
+
+async fn run_piped(
+    mut child: Child,
+    input: &str,
+    deadline: Instant,
+    stdout: &mut PipeReader,
+    stderr: &mut PipeReader,
+) -> AIRunResult {
+    let pid = child.id();
+
+    // kept open until the AI finishes, like before
+    let mut stdin = child.stdin.take().expect("Error getting stdin of program");
+
+    // if the AI exits without reading its input, this fails, but the exit is reported below
+    let _ = stdin.write_all(input.as_bytes()).await;
+    let _ = stdin.flush().await;
+
+    // when the AI has written a complete move
+    let mut moved_at: Option<Instant> = None;
+
+    let status = loop {
+        let now = Instant::now();
+
+        if now >= deadline && moved_at.is_none() {
+            child.kill().await.unwrap_or_default();
+
+            if let Some(pid) = pid {
+                system::kill_leftovers(pid);
+            }
+
+            return AIRunResult::TimeOut {
+                partial_output: sanitize(&stdout.read_so_far()),
+            };
+        }
+
+        if let Some(result) = check_overflow(&mut child, pid, stdout, stderr).await {
+            return result;
+        }
+
+        // after the move, the deadline may have passed, the AI is waited for until the grace
+        // period ends instead
+        let wait_until = moved_at.map_or(deadline, |moved_at| moved_at + EXIT_GRACE_PERIOD);
+        let check_in = EXIT_CHECK_INTERVAL.min(wait_until.saturating_duration_since(now));
+
+        if let Ok(status) = time::timeout(check_in, child.wait()).await {
+            break status.expect("Error waiting for AI to finish");
+        }
+
+        let output = stdout.read_so_far();
+
+        if moved_at.is_none() && output.ends_with('\n') && parse_output(&output).is_ok() {
+            moved_at = Some(Instant::now());
+        }
+
+        if moved_at.is_some_and(|moved_at| moved_at.elapsed() >= EXIT_GRACE_PERIOD) {
+            child.kill().await.unwrap_or_default();
+
+            // more output may have been written since
+            let mut output = match parse_output(&stdout.read_so_far()) {
+                Ok(output) => output,
+                Err(err) => return AIRunResult::InvalidOuput(err),
+            };
+
+            output.warnings.push(format!(
+                "AI didn't exit within {} ms after writing its move, it was killed",
+                EXIT_GRACE_PERIOD.as_millis()
+            ));
+            output.warnings.extend(leftovers_warning(pid));
+
+            return AIRunResult::Success(output);
+        }
+    };
+
+    // processes started by the AI may hold on to its pipes, which would never close otherwise
+    let leftovers = leftovers_warning(pid);
+
+    let output = stdout.read_all().await;
+    let errors = stderr.read_all().await;
+
+    if let Some(result) = check_overflow(&mut child, pid, stdout, stderr).await {
+        return result;
+    }
+
+    if !status.success() {
+        return AIRunResult::RuntimeError {
+            status,
+            stderr: sanitize(&errors),
+        };
+    }
+
+    match parse_output(&output) {
+        Ok(mut output) => {
+            output.warnings.extend(leftovers);
+            AIRunResult::Success(output)
+        }
+        Err(err) => AIRunResult::InvalidOuput(err),
+    }
+}
+
+// kills the AI if it wrote too much
+async fn check_overflow(
+    child: &mut Child,
+    pid: Option<u32>,
+    stdout: &PipeReader,
+    stderr: &PipeReader,
+) -> Option<AIRunResult> {
+    let pipe = if stdout.overflowed() {
+        "stdout"
+    } else if stderr.overflowed() {
+        "stderr"
+    } else {
+        return None;
+    };
+
+    child.kill().await.unwrap_or_default();
+
+    if let Some(pid) = pid {
+        system::kill_leftovers(pid);
+    }
+
+    Some(AIRunResult::InvalidOuput(output_overflow(pipe)))
+}
+
+/// The error of an AI writing more than `MAX_OUTPUT_SIZE` bytes to `pipe`.
+pub(crate) fn output_overflow(pipe: &str) -> Error {
+    Error::InvalidOutput(format!(
+        "AI wrote more than {} KB to {pipe}, it was killed",
+        MAX_OUTPUT_SIZE / 1024
+    ))
+}
+
+/// Removes the control characters from the output of an AI except for newlines and tabs, so that
+/// escape sequences can't mess up the console.
+pub(crate) fn sanitize(output: &str) -> String {
+    output
+        .chars()
+        .filter(|&c| !c.is_control() || c == '\n' || c == '\t')
+        .collect()
+}
+
+fn leftovers_warning(pid: Option<u32>) -> Option<String> {
+    pid.filter(|&pid| system::kill_leftovers(pid))
+        .map(|_| "AI left processes running after it exited, they were killed".to_owned())
+}
+
+pub(crate) fn parse_output(output: &str) -> Result<EngineOutput, Error> {
+    let output = sanitize(output);
+    let mut output: Vec<_> = output.trim().split('\n').map(|ln| ln.trim()).collect();
+
+    let version = match output[0].strip_prefix("version ") {
+        Some(version) => {
+            let version = version.trim().to_owned();
+            output.remove(0);
+            Some(version)
+        }
+        None => None,
+    };
+
+    if !(1..=2).contains(&output.len()) {
+        return Err(Error::InvalidOutput(format!(
+            "Output contains {} lines, which is invalid. It must be between 1 and 2.",
+            output.len()
+        )));
+    }
+
+    let move_string = output[0];
+
+    if move_string.len() != 2 {
+        return Err(Error::InvalidOutput(format!(
+            "Output '{move_string}' has invalid length"
+        )));
+    }
+
+    let x_char = move_string.chars().next().unwrap();
+
+    if !('a'..='h').contains(&x_char) {
+        return Err(Error::InvalidOutput(format!(
+            "Move '{move_string}' has invalid x coordinate"
+        )));
+    }
+
+    let y_char = move_string.chars().nth(1).unwrap();
+
+    if !('1'..='8').contains(&y_char) {
+        return Err(Error::InvalidOutput(format!(
+            "Move '{move_string}' has invalid y coordinate"
+        )));
+    }
+
+    let x = x_char as u32 - 'a' as u32;
+    let y = y_char as u32 - '1' as u32;
+
+    let mv = Vec2::new(x as isize, y as isize);
+
+    Ok(EngineOutput {
+        mv,
+        notes: output.get(1).map(|notes| notes.to_string()),
+        version,
+        warnings: Vec::new(),
+    })
+}
+
+// Reads a pipe of the AI in a separate task, so verbose AIs can't block on a full pipe
+// buffer, and whatever was written so far can be shown even if the AI never finishes.
+// Reading stops after `MAX_OUTPUT_SIZE` bytes.
+#[derive(Debug)]
+struct PipeReader {
+    buffer: Arc<Mutex<Vec<u8>>>,
+    overflowed: Arc<AtomicBool>,
+    task: JoinHandle<()>,
+}
+
+impl PipeReader {
+    fn new(mut pipe: impl AsyncRead + Unpin + Send + 'static) -> Self {
+        let buffer = Arc::new(Mutex::new(Vec::new()));
+        let overflowed = Arc::new(AtomicBool::new(false));
+        let task_buffer = Arc::clone(&buffer);
+        let task_overflowed = Arc::clone(&overflowed);
+
+        let task = tokio::spawn(async move {
+            let mut chunk = [0; 4096];
+
+            loop {
+                match pipe.read(&mut chunk).await {
+                    Ok(0) => break,
+                    Ok(len) => {
+                        let mut buffer = task_buffer.lock().unwrap_or_else(PoisonError::into_inner);
+
+                        if buffer.len() + len > MAX_OUTPUT_SIZE {
+                            let room = MAX_OUTPUT_SIZE - buffer.len();
+                            buffer.extend_from_slice(&chunk[..room]);
+                            task_overflowed.store(true, Ordering::Relaxed);
+                            break;
+                        }
+
+                        buffer.extend_from_slice(&chunk[..len]);
+                    }
+                    Err(err) if err.kind() == io::ErrorKind::Interrupted => {}
+                    Err(_) => break,
+                }
+            }
+        });
+
+        Self {
+            buffer,
+            overflowed,
+            task,
+        }
+    }
+
+    fn read_so_far(&self) -> String {
+        let buffer = self.buffer.lock().unwrap_or_else(PoisonError::into_inner);
+
+        String::from_utf8_lossy(&buffer).into_owned()
+    }
+
+    fn overflowed(&self) -> bool {
+        self.overflowed.load(Ordering::Relaxed)
+    }
+
+    /// Waits until the pipe is closed, or reading stopped after too much output, then returns
+    /// everything read from it.
+    async fn read_all(&mut self) -> String {
+        (&mut self.task)
+            .await
+            .expect("Error reading pipe of program");
+
+        self.read_so_far()
+    }
+}
+
+/// A running AI. The AI runs in a task of the async runtime, which enforces the time limit
+/// and sends the result back once it's done.
+#[derive(Debug)]
+pub struct AIRunHandle {
+    result: oneshot::Receiver<AIRunResult>,
+    task: AbortHandle,
+    started: Instant,
+    // set when the run finished, which can be before the result is checked
+    finished_after: Arc<OnceLock<Duration>>,
+    // what the AI was sent and wrote, for AIs run as local programs
+    io: Option<Arc<Mutex<EngineIo>>>,
+}
+
+impl AIRunHandle {
+    /// Runs `run` in the async runtime, killing it if the handle is dropped.
+    pub(crate) fn spawn(run: impl Future<Output = AIRunResult> + Send + 'static) -> Self {
+        let (sender, receiver) = oneshot::channel();
+        let started = Instant::now();
+        let finished_after = Arc::new(OnceLock::new());
+
+        let finished = Arc::clone(&finished_after);
+        let task = runtime().spawn(async move {
+            let result = run.await;
+            let _ = finished.set(started.elapsed());
+
+            // the receiver is gone if the AI was stopped in the meantime
+            let _ = sender.send(result);
+            FINISHED_RUNS.fetch_add(1, Ordering::Release);
+
+            if let Some(callback) = &*ON_RUN_FINISHED
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+            {
+                callback();
+            }
+        });
+
+        let handle = Self {
+            result: receiver,
+            task: task.abort_handle(),
+            started,
+            finished_after,
+            io: None,
+        };
+
+        register_task(task);
+
+        handle
+    }
+
+    /// The time since the AI was started, or the time it took once it finished.
+    pub fn elapsed(&self) -> Duration {
+        self.finished_after
+            .get()
+            .copied()
+            .unwrap_or_else(|| self.started.elapsed())
+    }
+
+    /// What the AI was sent and wrote, once it finished, if it is a local program.
+    pub fn io(&self) -> Option<EngineIo> {
+        self.io
+            .as_ref()
+            .map(|io| io.lock().unwrap_or_else(PoisonError::into_inner).clone())
+    }
+
+    pub fn kill(&mut self) {
+        // dropping the child in the task kills it
+        self.task.abort();
+    }
+
+    pub fn check(&mut self) -> AIRunResult {
+        match self.result.try_recv() {
+            Ok(result) => result,
+            Err(TryRecvError::Empty) => AIRunResult::Running,
+            // the task panicked, which is reported as output of the AI, so the game goes on
+            Err(TryRecvError::Closed) => AIRunResult::InvalidOuput(Error::InvalidOutput(
+                "AI task ended without a result".to_owned(),
+            )),
+        }
+    }
+}
+
+impl Drop for AIRunHandle {
+    fn drop(&mut self) {
+        self.kill();
+    }
+}
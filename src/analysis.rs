@@ -0,0 +1,325 @@
+use std::{fmt, time::Duration};
+
+use crate::{error::Error, *};
+
+/// Moves losing at least this many discs compared to the best move are counted as blunders.
+pub const BLUNDER_THRESHOLD: f64 = 6.0;
+
+// positions with at least this many empty squares are in the opening, about the first 20 moves
+const OPENING_MIN_EMPTIES: usize = 40;
+// and positions with at most this many in the endgame, which engines often solve exactly
+const ENDGAME_MAX_EMPTIES: usize = 20;
+
+/// The part of the game a position is in, by its number of empty squares.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Phase {
+    Opening,
+    Midgame,
+    Endgame,
+}
+
+impl Phase {
+    pub const ALL: [Phase; 3] = [Phase::Opening, Phase::Midgame, Phase::Endgame];
+
+    pub fn of(pos: &Pos) -> Phase {
+        let empties = tile_count(pos, Tile::Empty);
+
+        if empties >= OPENING_MIN_EMPTIES {
+            Phase::Opening
+        } else if empties > ENDGAME_MAX_EMPTIES {
+            Phase::Midgame
+        } else {
+            Phase::Endgame
+        }
+    }
+}
+
+impl fmt::Display for Phase {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Phase::Opening => "opening",
+            Phase::Midgame => "midgame",
+            Phase::Endgame => "endgame",
+        };
+
+        write!(f, "{name}")
+    }
+}
+
+/// How well a player played its moves, according to the reference engine.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct MoveQuality {
+    pub moves: usize,
+    // the sum of the evals lost by the moves, in discs
+    pub total_loss: f64,
+    pub blunders: usize,
+    // the moves that were the same as the one of the reference engine
+    pub best_moves: usize,
+}
+
+impl MoveQuality {
+    pub fn add(&mut self, other: &MoveQuality) {
+        self.moves += other.moves;
+        self.total_loss += other.total_loss;
+        self.blunders += other.blunders;
+        self.best_moves += other.best_moves;
+    }
+
+    pub fn average_loss(&self) -> f64 {
+        self.total_loss / self.moves as f64
+    }
+
+    /// The percentage of moves the reference engine would have played too.
+    pub fn accuracy(&self) -> f64 {
+        self.best_moves as f64 / self.moves as f64 * 100.0
+    }
+
+    /// The percentage of moves which were blunders.
+    pub fn blunder_rate(&self) -> f64 {
+        self.blunders as f64 / self.moves as f64 * 100.0
+    }
+}
+
+/// The time an AI took for its moves, as measured by the GUI.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct TimeUsage {
+    pub moves: usize,
+    pub total: Duration,
+}
+
+impl TimeUsage {
+    pub fn add(&mut self, other: &TimeUsage) {
+        self.moves += other.moves;
+        self.total += other.total;
+    }
+
+    pub fn average(&self) -> Duration {
+        self.total / self.moves.max(1) as u32
+    }
+}
+
+/// The time `tile` took for its moves in every phase, indexed by the phase of the position the
+/// move was played in. Moves not computed by an AI, e.g. of people, aren't counted.
+pub fn time_by_phase(game: &Game, tile: Tile) -> [TimeUsage; 3] {
+    let mut usage = [TimeUsage::default(); 3];
+
+    for (&ply, &time) in &game.move_times {
+        let Some(&(before, _)) = game.history.get(ply - 1) else {
+            continue;
+        };
+
+        if before.next_player == tile {
+            let usage = &mut usage[Phase::of(&before) as usize];
+            usage.moves += 1;
+            usage.total += time;
+        }
+    }
+
+    usage
+}
+
+/// The eval reported as `eval=<n>` in the notes, from the view of the player to move.
+pub fn parse_eval(notes: &str) -> Option<f64> {
+    notes
+        .split_whitespace()
+        .find_map(|note| note.strip_prefix("eval="))
+        .and_then(|eval| eval.parse().ok())
+        .filter(|eval: &f64| eval.is_finite())
+}
+
+/// A move of a game, as judged by the reference engine.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AnalyzedMove {
+    // the index of the position after the move in the history of the game
+    pub ply: usize,
+    pub mover: Tile,
+    pub mv: Vec2,
+    // of the position the move was played in
+    pub phase: Phase,
+    // how much worse the move was than the best one, in discs
+    pub loss: f64,
+    pub best_move: Vec2,
+}
+
+impl AnalyzedMove {
+    pub fn is_blunder(&self) -> bool {
+        self.loss >= BLUNDER_THRESHOLD
+    }
+}
+
+/// Replays the games through the reference engine, running at most `max_concurrency` at once,
+/// and returns the move quality of both players of every game in every phase, indexed by their
+/// tile and by the phase.
+pub fn analyze(
+    reference: &AI,
+    games: &[&Game],
+    max_concurrency: usize,
+    console: &mut Console,
+) -> Result<Vec<[[MoveQuality; 3]; 2]>, Error> {
+    Ok(analyze_moves(reference, games, max_concurrency, console)?
+        .into_iter()
+        .map(|moves| {
+            let mut quality = [[MoveQuality::default(); 3]; 2];
+
+            for analyzed in moves {
+                let quality = &mut quality[analyzed.mover as usize][analyzed.phase as usize];
+
+                quality.moves += 1;
+                quality.total_loss += analyzed.loss;
+
+                if analyzed.is_blunder() {
+                    quality.blunders += 1;
+                }
+
+                if analyzed.mv == analyzed.best_move {
+                    quality.best_moves += 1;
+                }
+            }
+
+            quality
+        })
+        .collect())
+}
+
+/// Replays the games through the reference engine, running at most `max_concurrency` at once,
+/// and returns the analyzed moves of every game. Moves played in positions the reference engine
+/// gave no eval for are left out.
+pub fn analyze_moves(
+    reference: &AI,
+    games: &[&Game],
+    max_concurrency: usize,
+    console: &mut Console,
+) -> Result<Vec<Vec<AnalyzedMove>>, Error> {
+    // the positions to evaluate, and where they are in `positions` by game and history index
+    let mut positions = Vec::new();
+    let mut indices: Vec<Vec<Option<usize>>> = Vec::new();
+
+    for game in games {
+        indices.push(
+            game.history
+                .iter()
+                .map(|&(pos, _)| {
+                    (pos.next_player != Tile::Empty).then(|| {
+                        positions.push(pos);
+                        positions.len() - 1
+                    })
+                })
+                .collect(),
+        );
+    }
+
+    let mut evals: Vec<Option<(f64, Vec2)>> = vec![None; positions.len()];
+
+    eval::run_batch(
+        reference,
+        &positions,
+        max_concurrency,
+        console,
+        |idx, result, console| {
+            let result = result.and_then(|output| {
+                let eval = output
+                    .notes
+                    .as_deref()
+                    .and_then(parse_eval)
+                    .ok_or("no eval=<n> in its notes")?;
+
+                Ok((eval, output.mv))
+            });
+
+            match result {
+                Ok(eval) => evals[idx] = Some(eval),
+                Err(error) => console.warn(
+                    Category::Errors,
+                    &format!("The reference engine failed to analyze a position: {error}"),
+                ),
+            }
+
+            Ok(())
+        },
+    )?;
+
+    let eval_at = |game: usize, k: usize| indices[game][k].and_then(|idx| evals[idx]);
+
+    Ok(games
+        .iter()
+        .enumerate()
+        .map(|(i, game)| {
+            (0..game.history.len() - 1)
+                .filter_map(|k| {
+                    let (before, _) = game.history[k];
+                    let (after, Some(mv)) = game.history[k + 1] else {
+                        return None;
+                    };
+                    let mover = before.next_player;
+
+                    let (best, best_move) = eval_at(i, k)?;
+
+                    let played = if after.next_player == Tile::Empty {
+                        disc_difference(&after.board, mover)
+                    } else {
+                        let (eval, _) = eval_at(i, k + 1)?;
+
+                        if after.next_player == mover {
+                            eval
+                        } else {
+                            -eval
+                        }
+                    };
+
+                    Some(AnalyzedMove {
+                        ply: k + 1,
+                        mover,
+                        mv,
+                        phase: Phase::of(&before),
+                        loss: (best - played).max(0.0),
+                        best_move,
+                    })
+                })
+                .collect()
+        })
+        .collect())
+}
+
+// the discs of `tile` minus the discs of its opponent
+fn disc_difference(board: &Board, tile: Tile) -> f64 {
+    Vec2::board_iter()
+        .map(|coor| match board.get(coor) {
+            Tile::Empty => 0.0,
+            owner if owner == tile => 1.0,
+            _ => -1.0,
+        })
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn eval_in_notes() {
+        assert_eq!(parse_eval("eval=+4 nodes=51234"), Some(4.0));
+        assert_eq!(parse_eval("depth=8 eval=-2.5"), Some(-2.5));
+        assert_eq!(parse_eval("nodes=51234"), None);
+        assert_eq!(parse_eval("eval=winning"), None);
+        assert_eq!(parse_eval("eval=inf"), None);
+    }
+
+    #[test]
+    fn phases() {
+        let mut pos = Pos::new();
+        let mut phases = Vec::new();
+
+        while !pos.is_game_over() {
+            phases.push(Phase::of(&pos));
+
+            let mv = pos.valid_moves()[0];
+            pos.play(mv);
+        }
+
+        assert_eq!(phases[0], Phase::Opening);
+        assert_eq!(phases.last(), Some(&Phase::Endgame));
+        assert!(phases.contains(&Phase::Midgame));
+        // phases only follow each other
+        assert!(phases.windows(2).all(|pair| pair[0] <= pair[1]));
+    }
+}
@@ -0,0 +1,171 @@
+use serde::{Deserialize, Serialize};
+use std::{
+    fs, io,
+    path::PathBuf,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use crate::{error::Error, observer::GameObserver, settings::Settings, *};
+
+/// An unfinished visual game, saved after every move, so that it can be continued after a crash
+/// or after the window was closed by accident.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SavedGame {
+    // the command line the game was started with, which recreates its players
+    pub args: Vec<String>,
+    // the names of the players, indexed by their tile
+    pub players: [String; 2],
+    // the starting position as a diagram, and the moves from it like `d3c5`
+    pub start: String,
+    pub moves: String,
+    // Unix time in seconds
+    pub saved_at: u64,
+}
+
+impl SavedGame {
+    pub fn new(game: &Game, args: &[String]) -> Self {
+        let saved_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |time| time.as_secs());
+
+        Self {
+            args: args.to_vec(),
+            players: [game.players[0].name(), game.players[1].name()],
+            start: board_diagram(&game.history[0].0, None),
+            moves: game.moves().iter().map(Vec2::move_string).collect(),
+            saved_at,
+        }
+    }
+
+    /// The starting position and the moves played from it.
+    pub fn start_and_moves(&self) -> Result<(Pos, Vec<Vec2>), String> {
+        let start = diagram::parse_diagram(&self.start)?;
+        let moves = if self.moves.is_empty() {
+            Vec::new()
+        } else {
+            diagram::parse_move_string(&self.moves)
+                .ok_or_else(|| format!("Invalid moves '{}'", self.moves))?
+        };
+
+        Ok((start, moves))
+    }
+
+    pub fn move_count(&self) -> usize {
+        self.moves.len() / 2
+    }
+}
+
+/// `othello_gui/autosave.json` next to the settings, if the configuration directory is known.
+pub fn path() -> Option<PathBuf> {
+    Some(Settings::path()?.with_file_name("autosave.json"))
+}
+
+/// The game saved last, if it wasn't finished.
+pub fn load() -> Result<Option<SavedGame>, Error> {
+    let Some(path) = path() else {
+        return Ok(None);
+    };
+
+    let content = match fs::read_to_string(&path) {
+        Ok(content) => content,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(None),
+        Err(source) => {
+            return Err(Error::Read {
+                what: format!("autosave '{}'", path.display()),
+                source,
+            })
+        }
+    };
+
+    serde_json::from_str(&content).map(Some).map_err(|err| {
+        Error::InvalidValue(format!("Autosave '{}' is invalid: {err}", path.display()))
+    })
+}
+
+fn save(saved: &SavedGame) -> io::Result<()> {
+    let Some(path) = path() else {
+        return Ok(());
+    };
+
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+
+    let content = serde_json::to_string_pretty(saved).expect("Error serializing autosave");
+
+    // written next to it first, so that a crash while writing doesn't leave half a file
+    let temporary = path.with_extension("json.tmp");
+    fs::write(&temporary, content)?;
+    fs::rename(&temporary, &path)
+}
+
+fn remove() -> io::Result<()> {
+    match path().map(fs::remove_file) {
+        Some(Err(err)) if err.kind() != io::ErrorKind::NotFound => Err(err),
+        _ => Ok(()),
+    }
+}
+
+/// Saves the game after every move and undo, and removes the save once the game ends, as there is
+/// nothing left to continue.
+#[derive(Debug)]
+pub struct Autosaver {
+    args: Vec<String>,
+    // only the first failure is reported, the game goes on regardless
+    failed: bool,
+}
+
+impl Autosaver {
+    pub fn new(args: Vec<String>) -> Self {
+        Self {
+            args,
+            failed: false,
+        }
+    }
+
+    fn report(&mut self, result: io::Result<()>) {
+        if let (Err(err), false) = (result, self.failed) {
+            self.failed = true;
+            eprintln!("Unable to autosave the game: {err}");
+        }
+    }
+}
+
+impl GameObserver for Autosaver {
+    fn on_move(&mut self, game: &Game, _mv: Vec2, _notes: &str) {
+        if !game.pos.is_game_over() {
+            let result = save(&SavedGame::new(game, &self.args));
+            self.report(result);
+        }
+    }
+
+    fn on_undo(&mut self, game: &Game) {
+        let result = save(&SavedGame::new(game, &self.args));
+        self.report(result);
+    }
+
+    fn on_game_end(&mut self, _game: &Game) {
+        let result = remove();
+        self.report(result);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn start_and_moves() {
+        let mut game = Game::new(0, [Player::Human, Player::Human]);
+        let mv = game.pos.valid_moves()[0];
+        game.pos.play(mv);
+        game.history.push((game.pos, Some(mv)));
+
+        let saved = SavedGame::new(&game, &["othello_gui".to_owned()]);
+        let (start, moves) = saved.start_and_moves().unwrap();
+
+        assert!(same_pos(&start, &Pos::new()));
+        assert_eq!(moves, vec![mv]);
+        assert_eq!(saved.move_count(), 1);
+    }
+}
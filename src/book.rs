@@ -0,0 +1,262 @@
+use serde::Serialize;
+use std::{fs, path::Path};
+
+use crate::{error::Error, openings::Opening, *};
+
+/// A tree of the openings played in games, with how the players of every move fared.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct BookNode {
+    // `None` for the initial position
+    #[serde(rename = "move")]
+    pub mv: Option<String>,
+    pub games: usize,
+    // the score of the player making the move, with 0.5 for draws
+    pub score: f32,
+    pub win_rate: f32,
+    pub children: Vec<BookNode>,
+}
+
+impl BookNode {
+    fn new(mv: Option<String>) -> Self {
+        Self {
+            mv,
+            games: 0,
+            score: 0.0,
+            win_rate: 0.0,
+            children: Vec::new(),
+        }
+    }
+
+    fn add(&mut self, score: f32) {
+        self.games += 1;
+        self.score += score;
+        self.win_rate = self.score / self.games as f32;
+    }
+
+    fn child(&mut self, mv: String) -> &mut BookNode {
+        let idx = match self
+            .children
+            .iter()
+            .position(|child| child.mv.as_ref() == Some(&mv))
+        {
+            Some(idx) => idx,
+            None => {
+                self.children.push(BookNode::new(Some(mv)));
+                self.children.len() - 1
+            }
+        };
+
+        &mut self.children[idx]
+    }
+
+    // the most played moves first
+    fn sort(&mut self) {
+        self.children.sort_by(|child1, child2| {
+            child2
+                .games
+                .cmp(&child1.games)
+                .then_with(|| child1.mv.cmp(&child2.mv))
+        });
+
+        for child in &mut self.children {
+            child.sort();
+        }
+    }
+
+    /// The book in a line based format, which can be read back with `read_book`. Every move is
+    /// written on its own line as `<moves from the initial position> <games> <score>`.
+    pub fn to_lines(&self) -> String {
+        let mut lines = String::new();
+        self.write_lines("", &mut lines);
+        lines
+    }
+
+    fn write_lines(&self, prefix: &str, lines: &mut String) {
+        for child in &self.children {
+            let line = format!("{prefix}{}", child.mv.as_deref().unwrap_or_default());
+
+            lines.push_str(&format!("{line} {} {}\n", child.games, child.score));
+            child.write_lines(&line, lines);
+        }
+    }
+}
+
+/// Builds the tree from the moves of games from the initial position, up to `max_plies` deep.
+/// With `merge_symmetric`, openings which are rotations or mirror images of each other are merged
+/// into one, e.g. all first moves into d3.
+pub fn build(games: &[results::PlayedGame], max_plies: usize, merge_symmetric: bool) -> BookNode {
+    let mut root = BookNode::new(None);
+
+    for game in games {
+        let mut pos = Pos::new();
+        let mut node = &mut root;
+
+        node.add(0.5);
+
+        let mut moves: Vec<Vec2> = game.moves.iter().take(max_plies).copied().collect();
+
+        if merge_symmetric {
+            moves = symmetry::canonical_line(&moves);
+        }
+
+        for mv in moves {
+            let mover = pos.next_player;
+            pos.play(mv);
+
+            node = node.child(mv.move_string());
+            node.add(score(game.winner, mover));
+        }
+    }
+
+    root.sort();
+    root
+}
+
+fn score(winner: Tile, player: Tile) -> f32 {
+    if winner == Tile::Empty {
+        0.5
+    } else if winner == player {
+        1.0
+    } else {
+        0.0
+    }
+}
+
+/// A line of a book written by `BookNode::to_lines`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BookLine {
+    pub moves: Vec<Vec2>,
+    pub games: usize,
+    pub score: f32,
+}
+
+pub fn read_book(content: &str) -> Result<Vec<BookLine>, Error> {
+    content
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| {
+            let invalid = || {
+                Error::InvalidValue(format!(
+                    "Book line '{line}' is not in the form <moves> <games> <score>"
+                ))
+            };
+
+            let mut parts = line.split_whitespace();
+
+            let (Some(moves), Some(games), Some(score), None) =
+                (parts.next(), parts.next(), parts.next(), parts.next())
+            else {
+                return Err(invalid());
+            };
+
+            let moves = diagram::parse_move_string(moves).ok_or_else(invalid)?;
+
+            Ok(BookLine {
+                moves,
+                games: games.parse().map_err(|_| invalid())?,
+                score: score.parse().map_err(|_| invalid())?,
+            })
+        })
+        .collect()
+}
+
+pub fn load(path: &Path) -> Result<Vec<BookLine>, Error> {
+    let content = fs::read_to_string(path).map_err(|source| Error::Read {
+        what: format!("book '{}'", path.display()),
+        source,
+    })?;
+
+    read_book(&content)
+}
+
+/// The openings of the book with `depth` moves, played from the initial position.
+pub fn openings(book: &[BookLine], depth: usize) -> Result<Vec<Opening>, Error> {
+    let openings: Vec<Opening> = book
+        .iter()
+        .filter(|line| line.moves.len() == depth)
+        .map(|line| {
+            let mut pos = Pos::new();
+
+            for &mv in &line.moves {
+                if !pos.is_valid_move(mv) {
+                    return Err(Error::InvalidValue(format!(
+                        "Book line '{}' has an invalid move: {}",
+                        line.moves.iter().map(Vec2::move_string).collect::<String>(),
+                        mv.move_string()
+                    )));
+                }
+
+                pos.play(mv);
+            }
+
+            Ok(Opening {
+                pos,
+                moves: line.moves.clone(),
+            })
+        })
+        .collect::<Result<_, _>>()?;
+
+    if openings.is_empty() {
+        return Err(Error::InvalidValue(format!(
+            "The book has no openings of {depth} moves"
+        )));
+    }
+
+    Ok(openings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lines_round_trip() {
+        let mut root = BookNode::new(None);
+        root.child("d3".to_owned()).add(1.0);
+        root.child("d3".to_owned()).child("c5".to_owned()).add(0.0);
+        root.child("f5".to_owned()).add(0.5);
+
+        let lines = read_book(&root.to_lines()).unwrap();
+
+        assert_eq!(
+            lines,
+            vec![
+                BookLine {
+                    moves: vec![Vec2::new(3, 2)],
+                    games: 1,
+                    score: 1.0,
+                },
+                BookLine {
+                    moves: vec![Vec2::new(3, 2), Vec2::new(2, 4)],
+                    games: 1,
+                    score: 0.0,
+                },
+                BookLine {
+                    moves: vec![Vec2::new(5, 4)],
+                    games: 1,
+                    score: 0.5,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn invalid_lines() {
+        assert!(read_book("d3 1").is_err());
+        assert!(read_book("d3c 1 1").is_err());
+        assert!(read_book("d3 one 1").is_err());
+    }
+
+    #[test]
+    fn openings_of_depth() {
+        let book = read_book("d3 2 1\nd3c5 1 0\nf5 1 0.5\nd3d3 1 1").unwrap();
+
+        let first_moves = openings(&book[..3], 1).unwrap();
+        assert_eq!(first_moves.len(), 2);
+        assert_eq!(first_moves[1].moves, vec![Vec2::new(5, 4)]);
+        assert_eq!(first_moves[1].pos.next_player, Tile::O);
+
+        assert!(openings(&book, 2).is_err());
+        assert!(openings(&book[..3], 3).is_err());
+    }
+}
@@ -0,0 +1,255 @@
+use serde::{Deserialize, Serialize};
+use std::{io, process::Stdio, sync::Arc, time::*};
+use tokio::{
+    io::{AsyncBufReadExt, AsyncWriteExt, BufReader},
+    process::Command,
+    sync::{
+        mpsc::{self, UnboundedReceiver, UnboundedSender},
+        Mutex,
+    },
+    time,
+};
+
+use crate::{ai::*, engine_config::*, error::Error, net::parse_move, *};
+
+/// A player backed by a long running relay process, through which games can be played e.g. on
+/// online servers. Unlike AIs, the process is started once per game, and is talked to with JSON
+/// lines, as described in the protocol specification.
+#[derive(Debug)]
+pub struct BridgePlayer {
+    pub config: EngineConfig,
+    pub limit: Limit,
+    pub run_handle: Option<AIRunHandle>,
+    pub deterministic: bool,
+    // started when the first move is requested
+    process: Option<BridgeProcess>,
+}
+
+#[derive(Debug)]
+struct BridgeProcess {
+    requests: UnboundedSender<String>,
+    // shared by the runs of the moves, only one of which is running at a time
+    responses: Arc<Mutex<UnboundedReceiver<String>>>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum Request {
+    Move {
+        id: usize,
+        board: Vec<String>,
+        next_player: char,
+        valid_moves: Vec<String>,
+        moves: Vec<String>,
+        time_limit_ms: u128,
+        depth: Option<u32>,
+        nodes: Option<u64>,
+        deterministic: bool,
+    },
+    GameEnd {
+        // "X", "O" or "draw"
+        winner: String,
+    },
+}
+
+#[derive(Debug, Deserialize)]
+struct Response {
+    id: usize,
+    #[serde(rename = "move")]
+    mv: Option<String>,
+    notes: Option<String>,
+    version: Option<String>,
+    error: Option<String>,
+}
+
+impl BridgePlayer {
+    pub fn new(config: EngineConfig, limit: Limit) -> Self {
+        Self {
+            config,
+            limit,
+            run_handle: None,
+            deterministic: false,
+            process: None,
+        }
+    }
+
+    /// The line the relay is sent when the next move is requested.
+    pub fn request(&self, pos: Pos, moves: &[Vec2]) -> String {
+        let request = Request::Move {
+            id: moves.len(),
+            board: board_rows(&pos.board),
+            next_player: tile_char(pos.next_player),
+            valid_moves: pos
+                .valid_moves()
+                .iter()
+                .map(|mv| mv.move_string())
+                .collect(),
+            moves: moves.iter().map(|mv| mv.move_string()).collect(),
+            time_limit_ms: self.limit.time_limit().as_millis(),
+            depth: match self.limit {
+                Limit::Depth(depth) => Some(depth),
+                _ => None,
+            },
+            nodes: match self.limit {
+                Limit::Nodes(nodes) => Some(nodes),
+                _ => None,
+            },
+            deterministic: self.deterministic,
+        };
+
+        serde_json::to_string(&request).expect("Error serializing bridge request")
+    }
+
+    pub fn run(&mut self, pos: Pos, moves: &[Vec2]) -> io::Result<()> {
+        if self.process.is_none() {
+            self.process = Some(BridgeProcess::start(&self.config)?);
+        }
+
+        let process = self.process.as_ref().unwrap();
+
+        let id = moves.len();
+        let request = self.request(pos, moves);
+        let requests = process.requests.clone();
+        let responses = Arc::clone(&process.responses);
+        let deadline = Instant::now() + self.limit.time_limit();
+
+        self.run_handle = Some(AIRunHandle::spawn(async move {
+            // if the relay has exited, it is reported below
+            let _ = requests.send(request);
+
+            let mut responses = responses.lock().await;
+
+            let result = time::timeout_at(deadline.into(), async {
+                while let Some(line) = responses.recv().await {
+                    if let Some(result) = parse_response(&line, id) {
+                        return result;
+                    }
+                }
+
+                AIRunResult::ConnectionError("bridge process exited".to_owned())
+            })
+            .await;
+
+            result.unwrap_or(AIRunResult::TimeOut {
+                partial_output: String::new(),
+            })
+        }));
+
+        Ok(())
+    }
+
+    /// Tells the relay the result, and closes its stdin, so it can exit.
+    pub fn game_over(&mut self, winner: Tile) {
+        let Some(process) = self.process.take() else {
+            return;
+        };
+
+        let request = Request::GameEnd {
+            winner: winner_string(winner),
+        };
+
+        let _ = process
+            .requests
+            .send(serde_json::to_string(&request).expect("Error serializing bridge request"));
+    }
+
+    pub fn try_clone(&self) -> Result<Self, Error> {
+        match self.run_handle {
+            None => Ok(Self {
+                deterministic: self.deterministic,
+                ..Self::new(self.config.clone(), self.limit)
+            }),
+            Some(_) => Err(Error::CloneRunningEngine(format!(
+                "bridge:{}",
+                self.config.path.display()
+            ))),
+        }
+    }
+}
+
+impl BridgeProcess {
+    fn start(config: &EngineConfig) -> io::Result<Self> {
+        // spawning the process needs the runtime's reactor
+        let _guard = runtime().enter();
+
+        let mut child = Command::from(run::command(config))
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .kill_on_drop(true)
+            .spawn()?;
+
+        let mut stdin = child.stdin.take().expect("Error getting stdin of program");
+        let stdout = child
+            .stdout
+            .take()
+            .expect("Error getting stdout of program");
+
+        let (requests, mut unsent) = mpsc::unbounded_channel::<String>();
+        let (received, responses) = mpsc::unbounded_channel();
+
+        // stdin is closed once every request is written and the player is done with the relay
+        let writer = runtime().spawn(async move {
+            while let Some(request) = unsent.recv().await {
+                let line = request + "\n";
+
+                if stdin.write_all(line.as_bytes()).await.is_err() || stdin.flush().await.is_err() {
+                    break;
+                }
+            }
+        });
+
+        // owns the child, so the relay is killed when the task is aborted on exit
+        let reader = runtime().spawn(async move {
+            let mut lines = BufReader::new(stdout).lines();
+
+            while let Ok(Some(line)) = lines.next_line().await {
+                if received.send(line).is_err() {
+                    break;
+                }
+            }
+
+            let _ = child.wait().await;
+        });
+
+        register_task(writer);
+        register_task(reader);
+
+        Ok(Self {
+            requests,
+            responses: Arc::new(Mutex::new(responses)),
+        })
+    }
+}
+
+// `None` if the response is to an earlier request, which timed out or was undone
+fn parse_response(line: &str, id: usize) -> Option<AIRunResult> {
+    let invalid = |message: String| Some(AIRunResult::InvalidOuput(Error::InvalidOutput(message)));
+
+    let response: Response = match serde_json::from_str(line) {
+        Ok(response) => response,
+        Err(err) => return invalid(format!("Response '{line}' is invalid: {err}")),
+    };
+
+    if response.id != id {
+        return None;
+    }
+
+    if let Some(err) = response.error {
+        return invalid(format!("Bridge reported an error: {err}"));
+    }
+
+    let Some(move_string) = response.mv else {
+        return invalid(format!("Response '{line}' contains no move"));
+    };
+
+    match parse_move(&move_string) {
+        Some(mv) => Some(AIRunResult::Success(EngineOutput {
+            mv,
+            notes: response.notes.as_deref().map(sanitize),
+            version: response.version.as_deref().map(sanitize),
+            warnings: Vec::new(),
+        })),
+        None => invalid(format!("Move '{move_string}' is invalid")),
+    }
+}
@@ -0,0 +1,137 @@
+use std::{
+    fs, io,
+    path::Path,
+    process::Command,
+    time::{Duration, Instant, SystemTime},
+};
+
+use crate::engine_config::EngineConfig;
+
+/// What building an AI with the `build` AI option did.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BuildOutcome {
+    Built(Duration),
+    // the AI is newer than its sources
+    UpToDate,
+}
+
+// the end of the build output is shown if it fails, as compilers write the error last
+const SHOWN_OUTPUT_LINES: usize = 20;
+
+/// Runs the build command of the AI with the shell, unless it was built since its sources last
+/// changed. The error has the end of the output of the build.
+pub fn build(config: &EngineConfig) -> Result<BuildOutcome, String> {
+    let Some(build) = &config.build else {
+        return Ok(BuildOutcome::UpToDate);
+    };
+
+    if is_up_to_date(config) {
+        return Ok(BuildOutcome::UpToDate);
+    }
+
+    let start = Instant::now();
+
+    let output = shell(build)
+        .current_dir(config.build_dir())
+        .output()
+        .map_err(|err| format!("Unable to run '{build}': {err}"))?;
+
+    if !output.status.success() {
+        let text = format!(
+            "{}{}",
+            String::from_utf8_lossy(&output.stdout),
+            String::from_utf8_lossy(&output.stderr)
+        );
+        let lines: Vec<&str> = text.lines().collect();
+        let shown = &lines[lines.len().saturating_sub(SHOWN_OUTPUT_LINES)..];
+
+        let status = match output.status.code() {
+            Some(code) => format!("exit code {code}"),
+            None => "no exit code".to_owned(),
+        };
+
+        return Err(format!(
+            "'{build}' failed with {status}:\n{}",
+            shown.join("\n")
+        ));
+    }
+
+    if !config.path.is_file() {
+        return Err(format!(
+            "'{build}' succeeded, but didn't create '{}'",
+            config.path.display()
+        ));
+    }
+
+    Ok(BuildOutcome::Built(start.elapsed()))
+}
+
+/// Whether the AI was built after every file in its source directory was last modified. Hidden
+/// files and the directories the AI is in are skipped, as they are written by the build, e.g.
+/// `target` for Cargo.
+pub fn is_up_to_date(config: &EngineConfig) -> bool {
+    let Some(built) = modified(&config.path) else {
+        return false;
+    };
+
+    match newest_source(config.build_dir(), &config.path) {
+        Ok(Some(newest)) => newest <= built,
+        // nothing to compare to, so it is built every time
+        Ok(None) | Err(_) => false,
+    }
+}
+
+fn newest_source(dir: &Path, binary: &Path) -> io::Result<Option<SystemTime>> {
+    let binary = binary.canonicalize()?;
+    let mut newest = None;
+    let mut dirs = vec![dir.canonicalize()?];
+
+    while let Some(dir) = dirs.pop() {
+        for entry in fs::read_dir(dir)? {
+            let entry = entry?;
+            let path = entry.path();
+
+            if path
+                .file_name()
+                .is_some_and(|name| name.to_string_lossy().starts_with('.'))
+                || path == binary
+            {
+                continue;
+            }
+
+            // symbolic links to directories aren't followed, they could lead in a loop
+            if entry.file_type()?.is_dir() {
+                if !binary.starts_with(&path) {
+                    dirs.push(path);
+                }
+            } else if !path.is_dir() {
+                if let Some(modified) = modified(&path) {
+                    newest = newest.max(Some(modified));
+                }
+            }
+        }
+    }
+
+    Ok(newest)
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+}
+
+fn shell(command: &str) -> Command {
+    let mut shell = if cfg!(windows) {
+        let mut shell = Command::new("cmd");
+        shell.arg("/C");
+        shell
+    } else {
+        let mut shell = Command::new("sh");
+        shell.arg("-c");
+        shell
+    };
+
+    shell.arg(command);
+    shell
+}
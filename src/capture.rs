@@ -0,0 +1,93 @@
+use serde::{Deserialize, Serialize};
+use std::{
+    fs::{self, File, OpenOptions},
+    io::{self, Write},
+    path::Path,
+};
+
+use crate::{error::Error, observer::*, *};
+
+/// Appends a JSON line to a file for every run of a local AI, with the exact input it was sent
+/// and everything it wrote, so that failures can be reproduced with the same input later.
+#[derive(Debug)]
+pub struct CaptureWriter {
+    file: File,
+}
+
+/// A run of an AI in a capture file.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Capture {
+    pub game_id: usize,
+    // the number of moves played before the run
+    pub ply: usize,
+    // "X" or "O"
+    pub player: String,
+    // the <player> and <max time> arguments, with which the AI can be run again
+    pub engine: Option<String>,
+    pub limit: Option<String>,
+    pub input: String,
+    pub stdout: String,
+    pub stderr: String,
+}
+
+impl CaptureWriter {
+    pub fn create(path: &Path) -> io::Result<Self> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+
+        Ok(Self { file })
+    }
+}
+
+impl GameObserver for CaptureWriter {
+    fn on_engine_io(&mut self, game: &Game, io: &EngineIo) {
+        let player = game.next_player();
+
+        let capture = Capture {
+            game_id: game.id,
+            ply: game.history.len() - 1,
+            player: tile_char(game.pos.next_player).to_string(),
+            engine: player.and_then(Player::arg),
+            limit: player.and_then(Player::limit).map(Limit::arg),
+            input: io.input.clone(),
+            stdout: io.stdout.clone(),
+            stderr: io.stderr.clone(),
+        };
+
+        let line = serde_json::to_string(&capture).expect("Error serializing capture");
+
+        // losing a capture shouldn't stop the games, so failures are only reported
+        if let Err(err) = writeln!(self.file, "{line}") {
+            eprintln!("Unable to write capture of game #{}: {err}", game.id);
+        }
+    }
+}
+
+/// Reads the runs of `game_id` from a capture file.
+pub fn read_captures(path: &Path, game_id: usize) -> Result<Vec<Capture>, Error> {
+    let content = fs::read_to_string(path).map_err(|source| Error::Read {
+        what: format!("capture file '{}'", path.display()),
+        source,
+    })?;
+
+    let mut captures = Vec::new();
+
+    for (i, line) in content.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let capture: Capture = serde_json::from_str(line).map_err(|err| {
+            Error::InvalidValue(format!(
+                "Line {} of capture file '{}' is invalid: {err}",
+                i + 1,
+                path.display()
+            ))
+        })?;
+
+        if capture.game_id == game_id {
+            captures.push(capture);
+        }
+    }
+
+    Ok(captures)
+}
@@ -0,0 +1,74 @@
+use std::fmt;
+
+use othello_gui::{diagram, *};
+
+/// The system clipboard, opened when it is first used. It is kept open afterwards, as on Linux,
+/// copied text is only available while it is.
+#[derive(Default)]
+pub struct Clipboard(Option<arboard::Clipboard>);
+
+impl fmt::Debug for Clipboard {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Clipboard")
+    }
+}
+
+impl Clipboard {
+    fn open(&mut self) -> Result<&mut arboard::Clipboard, String> {
+        if self.0.is_none() {
+            let clipboard = arboard::Clipboard::new()
+                .map_err(|err| format!("Unable to open the clipboard: {err}"))?;
+            self.0 = Some(clipboard);
+        }
+
+        Ok(self.0.as_mut().expect("clipboard not opened"))
+    }
+
+    pub fn copy(&mut self, text: String) -> Result<(), String> {
+        self.open()?
+            .set_text(text)
+            .map_err(|err| format!("Unable to copy to the clipboard: {err}"))
+    }
+
+    pub fn paste(&mut self) -> Result<String, String> {
+        self.open()?
+            .get_text()
+            .map_err(|err| format!("Unable to paste from the clipboard: {err}"))
+    }
+}
+
+/// The position of the game as a diagram, after the moves leading to it if the game was started
+/// from the initial position, so that either can be pasted.
+pub fn game_text(game: &Game) -> String {
+    let diagram = board_diagram(&game.pos, game.history.last().and_then(|&(_, mv)| mv));
+    let moves = game.moves();
+
+    if same_pos(&game.history[0].0, &Pos::new()) && !moves.is_empty() {
+        let move_string: String = moves.iter().map(Vec2::move_string).collect();
+        format!("{move_string}\n{diagram}\n")
+    } else {
+        format!("{diagram}\n")
+    }
+}
+
+/// The starting position and the moves from it of pasted text: either moves from the initial
+/// position on the first line, or a diagram of a position.
+pub fn read_pasted(text: &str) -> Result<(Pos, Vec<Vec2>), String> {
+    let first_line = text
+        .lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty() && !line.starts_with('#'))
+        .ok_or("The clipboard is empty")?;
+
+    if let Some(moves) = diagram::parse_move_string(first_line) {
+        return Ok((Pos::new(), moves));
+    }
+
+    let pos = diagram::parse_diagram(text)?;
+
+    if pos.next_player == Tile::Empty {
+        return Err("Neither player can move in the pasted position".to_owned());
+    }
+
+    Ok((pos, Vec::new()))
+}
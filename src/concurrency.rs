@@ -0,0 +1,178 @@
+use std::time::{Duration, Instant};
+
+use crate::system;
+
+/// Finds how many games can run at once without the AIs slowing each other down, for
+/// `<max concurrency>` auto. More games are started while the CPUs aren't busy and moves don't
+/// take longer than at lower concurrency, and fewer once they are, or once a move times out.
+#[derive(Debug, Clone)]
+pub struct AutoConcurrency {
+    pub current: usize,
+    max: usize,
+    last_adjusted: Instant,
+    // the share of their time limit the moves since the last adjustment took
+    latencies: Vec<f32>,
+    timeouts: usize,
+    // the lowest average latency measured, to which later ones are compared
+    baseline: Option<f32>,
+    // `system::cpu_times()` at the last adjustment, the CPU usage is measured since
+    cpu_times: Option<(u64, u64)>,
+}
+
+/// A change of the number of games running at once.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Adjustment {
+    Increase,
+    Decrease,
+    Keep,
+}
+
+// how long the effects of an adjustment are measured before the next one
+const ADJUST_INTERVAL: Duration = Duration::from_secs(10);
+// fewer moves than this are too noisy to compare
+const MIN_SAMPLES: usize = 8;
+// moves taking this much longer than the baseline are slowed down by other games
+const LATENCY_SLACK: f32 = 1.25;
+// the share of the CPU time used above which the CPUs are oversubscribed, and below which there
+// is room for more games
+const OVERLOADED: f32 = 0.95;
+const UNDERLOADED: f32 = 0.85;
+
+impl AutoConcurrency {
+    /// Starts with a quarter of `max` games, which can grow to `max`.
+    pub fn new(max: usize) -> Self {
+        Self {
+            current: (max / 4).max(1),
+            max: max.max(1),
+            last_adjusted: Instant::now(),
+            latencies: Vec::new(),
+            timeouts: 0,
+            baseline: None,
+            cpu_times: system::cpu_times(),
+        }
+    }
+
+    pub fn record_move(&mut self, elapsed: Duration, limit: Duration) {
+        self.latencies
+            .push(elapsed.as_secs_f32() / limit.as_secs_f32().max(f32::EPSILON));
+    }
+
+    pub fn record_timeout(&mut self) {
+        self.timeouts += 1;
+    }
+
+    /// Changes the number of games, if enough was measured since the last change, returning
+    /// whether it changed. `cpu_times` are the current `system::cpu_times()`.
+    pub fn adjust(&mut self, cpu_times: Option<(u64, u64)>) -> bool {
+        if self.last_adjusted.elapsed() < ADJUST_INTERVAL {
+            return false;
+        }
+
+        let usage = match (self.cpu_times, cpu_times) {
+            (Some((busy_before, total_before)), Some((busy, total))) if total > total_before => {
+                Some((busy - busy_before) as f32 / (total - total_before) as f32)
+            }
+            _ => None,
+        };
+
+        let adjustment = self.decide(usage);
+
+        if adjustment == Adjustment::Keep && self.latencies.len() < MIN_SAMPLES {
+            // measured for longer instead
+            return false;
+        }
+
+        let previous = self.current;
+
+        self.current = match adjustment {
+            Adjustment::Increase => (self.current + 1).min(self.max),
+            Adjustment::Decrease => self.current.saturating_sub(1).max(1),
+            Adjustment::Keep => self.current,
+        };
+
+        self.last_adjusted = Instant::now();
+        self.cpu_times = cpu_times;
+        self.latencies.clear();
+        self.timeouts = 0;
+
+        self.current != previous
+    }
+
+    // `usage` is the share of the CPU time used since the last adjustment, if it is known
+    fn decide(&mut self, usage: Option<f32>) -> Adjustment {
+        if self.timeouts > 0 || usage.is_some_and(|usage| usage > OVERLOADED) {
+            return Adjustment::Decrease;
+        }
+
+        if self.latencies.len() < MIN_SAMPLES {
+            return Adjustment::Keep;
+        }
+
+        let latency = self.latencies.iter().sum::<f32>() / self.latencies.len() as f32;
+        let baseline = *self.baseline.get_or_insert(latency);
+
+        if latency > baseline * LATENCY_SLACK {
+            return Adjustment::Decrease;
+        }
+
+        self.baseline = Some(baseline.min(latency));
+
+        if self.current < self.max && !usage.is_some_and(|usage| usage >= UNDERLOADED) {
+            Adjustment::Increase
+        } else {
+            Adjustment::Keep
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn measured(concurrency: &mut AutoConcurrency, latency: f32) {
+        for _ in 0..MIN_SAMPLES {
+            concurrency.record_move(Duration::from_secs_f32(latency), Duration::from_secs(1));
+        }
+    }
+
+    #[test]
+    fn grows_while_idle() {
+        let mut concurrency = AutoConcurrency::new(8);
+        measured(&mut concurrency, 0.5);
+
+        assert_eq!(concurrency.decide(Some(0.3)), Adjustment::Increase);
+        assert_eq!(concurrency.decide(None), Adjustment::Increase);
+    }
+
+    #[test]
+    fn shrinks_when_overloaded() {
+        let mut concurrency = AutoConcurrency::new(8);
+        measured(&mut concurrency, 0.5);
+
+        assert_eq!(concurrency.decide(Some(0.99)), Adjustment::Decrease);
+
+        concurrency.record_timeout();
+        assert_eq!(concurrency.decide(Some(0.3)), Adjustment::Decrease);
+    }
+
+    #[test]
+    fn shrinks_when_moves_slow_down() {
+        let mut concurrency = AutoConcurrency::new(8);
+        measured(&mut concurrency, 0.2);
+        assert_eq!(concurrency.decide(None), Adjustment::Increase);
+
+        concurrency.latencies.clear();
+        measured(&mut concurrency, 0.4);
+        assert_eq!(concurrency.decide(None), Adjustment::Decrease);
+    }
+
+    #[test]
+    fn stays_within_bounds() {
+        let mut concurrency = AutoConcurrency::new(2);
+        assert_eq!(concurrency.current, 1);
+
+        concurrency.current = 2;
+        measured(&mut concurrency, 0.5);
+        assert_eq!(concurrency.decide(Some(0.1)), Adjustment::Keep);
+    }
+}
@@ -1,101 +1,282 @@
-use crossterm::{cursor, terminal, ExecutableCommand, QueueableCommand};
-use std::io::{stdout, Write};
+use crossterm::{
+    cursor,
+    style::{self, Stylize},
+    terminal, QueueableCommand,
+};
+use std::{
+    env,
+    io::{stderr, stdout, IsTerminal, Write},
+    str::FromStr,
+};
 
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
 pub struct Console {
-    pinned: Option<String>,
-    pub level: Level,
+    // lines kept below the other output, e.g. the progress, only shown in terminals
+    pinned: Vec<String>,
+    // the lowest level printed of every category
+    levels: [Level; Category::COUNT],
+    // keeps stdout free for machine-readable output
+    pub use_stderr: bool,
+    color: bool,
+    // for following games without the GUI
+    pub print_boards: bool,
 }
 
 impl Console {
     pub fn new(level: Level) -> Self {
         Self {
-            pinned: None,
-            level,
+            pinned: Vec::new(),
+            levels: [level; Category::COUNT],
+            use_stderr: false,
+            color: false,
+            print_boards: false,
         }
     }
 
-    pub fn print_with_level(&self, level: Level, message: &str) {
-        if level < self.level || (cfg!(debug_assert) && level == Level::Debug) {
-            return;
+    fn is_terminal(&self) -> bool {
+        if self.use_stderr {
+            stderr().is_terminal()
+        } else {
+            stdout().is_terminal()
         }
+    }
 
-        if let Some(pinned) = &self.pinned {
-            let message_line_count = message.lines().count();
-
-            print!("{}{}", "\n".repeat(message_line_count), pinned);
-            stdout()
-                .queue(cursor::MoveUp(message_line_count as u16))
-                .unwrap()
-                .queue(cursor::MoveToColumn(0))
-                .unwrap()
-                .queue(terminal::Clear(terminal::ClearType::CurrentLine))
-                .unwrap();
-            print!("{message}");
-            stdout()
-                .queue(cursor::MoveDown(message_line_count as u16))
-                .unwrap()
-                .queue(cursor::MoveToColumn(0))
-                .unwrap();
-            stdout().flush().unwrap();
+    fn output(&self) -> Box<dyn Write> {
+        if self.use_stderr {
+            Box::new(stderr())
         } else {
-            println!("{message}");
+            Box::new(stdout())
+        }
+    }
+
+    /// Decides whether to color the output, with `ColorMode::Auto` only if it goes to a
+    /// terminal and `NO_COLOR` isn't set. Call after choosing between stdout and stderr.
+    pub fn set_color(&mut self, mode: ColorMode) {
+        self.color = match mode {
+            ColorMode::Always => true,
+            ColorMode::Never => false,
+            ColorMode::Auto => self.is_terminal() && env::var_os("NO_COLOR").is_none(),
+        };
+    }
+
+    /// Sets the level of every category.
+    pub fn set_level(&mut self, level: Level) {
+        self.levels = [level; Category::COUNT];
+    }
+
+    pub fn set_category_level(&mut self, category: Category, level: Level) {
+        self.levels[category as usize] = level;
+    }
+
+    pub fn print_with_level(&self, category: Category, level: Level, message: &str) {
+        if level < self.levels[category as usize] || (cfg!(debug_assert) && level == Level::Debug) {
+            return;
         }
+
+        let mut output = self.output();
+
+        // the message is written in place of the pinned lines, which are drawn again below it
+        self.clear_pinned(&mut output);
+        self.write_message(&mut output, category, level, message);
+        writeln!(output).unwrap();
+        self.draw_pinned(&mut output);
+        output.flush().unwrap();
+    }
+
+    // crossterm falls back to the console API on Windows terminals without ANSI support
+    fn write_message(
+        &self,
+        output: &mut dyn Write,
+        category: Category,
+        level: Level,
+        message: &str,
+    ) {
+        let styled = match (self.color, category, level) {
+            (false, ..) => message.stylize(),
+            (true, Category::Results, _) => message.green(),
+            (true, Category::Errors, _) => message.red(),
+            // there is a line for every move
+            (true, Category::GameFlow, Level::Info) => message.dim(),
+            (true, ..) => message.stylize(),
+        };
+
+        output.queue(style::PrintStyledContent(styled)).unwrap();
     }
 
-    pub fn print(&self, message: &str) {
-        self.print_with_level(Level::Necessary, message);
+    pub fn print(&self, category: Category, message: &str) {
+        self.print_with_level(category, Level::Necessary, message);
     }
 
-    pub fn warn(&self, message: &str) {
-        self.print_with_level(Level::Warning, message);
+    pub fn warn(&self, category: Category, message: &str) {
+        self.print_with_level(category, Level::Warning, message);
     }
 
-    pub fn info(&self, message: &str) {
-        self.print_with_level(Level::Info, message);
+    pub fn info(&self, category: Category, message: &str) {
+        self.print_with_level(category, Level::Info, message);
     }
 
-    pub fn debug(&self, message: &str) {
-        self.print_with_level(Level::Debug, message);
+    pub fn debug(&self, category: Category, message: &str) {
+        self.print_with_level(category, Level::Debug, message);
     }
 
+    /// Keeps `pinned` below the other output until it is replaced or unpinned. It can have
+    /// multiple lines, e.g. the progress and the standings.
     pub fn pin(&mut self, pinned: String) {
-        if let Some(already_pinned) = &self.pinned {
-            if *already_pinned == pinned {
-                return;
-            }
-        }
+        let pinned: Vec<String> = pinned.lines().map(str::to_owned).collect();
 
-        self.clear_pinned();
+        if pinned == self.pinned {
+            return;
+        }
 
-        print!("{pinned}");
-        stdout().flush().unwrap();
-        self.pinned = Some(pinned);
+        let mut output = self.output();
+        self.clear_pinned(&mut output);
+        self.pinned = pinned;
+        self.draw_pinned(&mut output);
+        output.flush().unwrap();
     }
 
     pub fn unpin(&mut self) {
-        self.clear_pinned();
+        let mut output = self.output();
+        self.clear_pinned(&mut output);
+        output.flush().unwrap();
+
+        self.pinned = Vec::new();
+    }
 
-        self.pinned = None;
+    fn shows_pinned(&self) -> bool {
+        // escape sequences would only clutter files and pipes
+        !self.pinned.is_empty() && self.is_terminal()
     }
 
-    fn clear_pinned(&self) {
-        if self.pinned.is_some() {
-            stdout()
-                .execute(terminal::Clear(terminal::ClearType::CurrentLine))
+    // the cursor is kept at the start of the pinned lines, so everything from it is cleared
+    fn clear_pinned(&self, output: &mut dyn Write) {
+        if self.shows_pinned() {
+            output
+                .queue(cursor::MoveToColumn(0))
                 .unwrap()
-                .execute(cursor::MoveToColumn(0))
+                .queue(terminal::Clear(terminal::ClearType::FromCursorDown))
                 .unwrap();
         }
     }
+
+    fn draw_pinned(&self, output: &mut dyn Write) {
+        if !self.shows_pinned() {
+            return;
+        }
+
+        // the width is read every time, so that the lines fit after the terminal is resized
+        let width = terminal::size().map_or(80, |(columns, _)| columns as usize);
+
+        for (i, line) in self.pinned.iter().enumerate() {
+            if i > 0 {
+                writeln!(output).unwrap();
+            }
+
+            // a line filling the last column would wrap in some terminals
+            write!(output, "{}", fit_width(line, width.saturating_sub(1))).unwrap();
+        }
+
+        if self.pinned.len() > 1 {
+            output
+                .queue(cursor::MoveUp(self.pinned.len() as u16 - 1))
+                .unwrap();
+        }
+
+        output.queue(cursor::MoveToColumn(0)).unwrap();
+    }
+}
+
+// Cuts `line` to `width` columns, counting wide characters like CJK as two, as wrapped lines
+// would make moving back to the start of the pinned lines unreliable.
+fn fit_width(line: &str, width: usize) -> String {
+    let mut used = 0;
+    let mut fitted = String::new();
+
+    for c in line.chars() {
+        let char_width = textwrap::core::display_width(c.encode_utf8(&mut [0; 4]));
+
+        if used + char_width > width {
+            break;
+        }
+
+        used += char_width;
+        fitted.push(c);
+    }
+
+    fitted
 }
 
 #[repr(u8)]
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 pub enum Level {
+    // only used as the level of categories, to print nothing of them
+    Off = 4,
     Necessary = 3,
     Warning = 2,
     Info = 1,
     // debug is printed only and always in debug builds
     Debug = 0,
 }
+
+impl FromStr for Level {
+    type Err = String;
+
+    fn from_str(str: &str) -> Result<Self, Self::Err> {
+        match str.to_lowercase().as_str() {
+            "o" | "off" => Ok(Level::Off),
+            "i" | "info" => Ok(Level::Info),
+            "w" | "warn" | "warning" => Ok(Level::Warning),
+            "n" | "necessary" => Ok(Level::Necessary),
+            other => Err(format!("Unknown <level> '{other}'")),
+        }
+    }
+}
+
+/// What a message is about, so that e.g. AI errors can be shown without every move.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Category {
+    // what AIs were given and what they wrote
+    EngineIo,
+    // games starting, moves, undos, and progress of the arena
+    GameFlow,
+    Results,
+    Errors,
+}
+
+impl Category {
+    const COUNT: usize = 4;
+}
+
+impl FromStr for Category {
+    type Err = String;
+
+    fn from_str(str: &str) -> Result<Self, Self::Err> {
+        match str.to_lowercase().as_str() {
+            "engine-io" => Ok(Category::EngineIo),
+            "game-flow" => Ok(Category::GameFlow),
+            "results" => Ok(Category::Results),
+            "errors" => Ok(Category::Errors),
+            other => Err(format!("Unknown log category '{other}'")),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColorMode {
+    Auto,
+    Always,
+    Never,
+}
+
+impl FromStr for ColorMode {
+    type Err = String;
+
+    fn from_str(str: &str) -> Result<Self, Self::Err> {
+        match str.to_lowercase().as_str() {
+            "auto" => Ok(ColorMode::Auto),
+            "always" => Ok(ColorMode::Always),
+            "never" => Ok(ColorMode::Never),
+            other => Err(format!("Unknown color mode '{other}'")),
+        }
+    }
+}
@@ -0,0 +1,201 @@
+use crate::*;
+
+/// Reads a position drawn as 8 rows of 8 tiles, with `.`, `-` or `_` for empty tiles, and `x` or
+/// `o` in either case for disks, e.g. as pasted from a forum or the console. Spaces between the
+/// tiles, a line of column letters, row numbers in front of the rows, the brackets around the
+/// last move, empty lines and lines starting with `#` are ignored.
+///
+/// The rows can be followed by a line starting with the next player, like `O` or `O to move`,
+/// otherwise X moves next. If the next player has to pass, the opponent moves instead.
+pub fn parse_diagram(text: &str) -> Result<Pos, String> {
+    let mut pos = Pos::new();
+    let mut rows = 0;
+    let mut next_player = None;
+
+    for (i, line) in text.lines().enumerate() {
+        let line = line.trim();
+        let line_number = i + 1;
+
+        if line.is_empty() || line.starts_with('#') || is_column_header(line) {
+            continue;
+        }
+
+        if rows == 8 {
+            if next_player.is_some() {
+                return Err(format!(
+                    "Line {line_number}: unexpected text after the diagram"
+                ));
+            }
+
+            let player = line.split_whitespace().next().unwrap_or_default();
+
+            next_player = match player {
+                "x" | "X" => Some(Tile::X),
+                "o" | "O" => Some(Tile::O),
+                _ => {
+                    return Err(format!(
+                        "Line {line_number}: expected the next player, X or O, found '{player}'"
+                    ))
+                }
+            };
+
+            continue;
+        }
+
+        let tiles = line
+            .trim_start_matches(|c: char| c.is_ascii_digit())
+            .chars()
+            .filter(|c| !c.is_whitespace() && *c != '[' && *c != ']')
+            .map(|c| {
+                diagram_tile(c).ok_or_else(|| {
+                    format!("Line {line_number}: unknown tile '{c}', expected '.', 'x' or 'o'")
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        if tiles.len() != 8 {
+            return Err(format!(
+                "Line {line_number}: row {} has {} tiles instead of 8",
+                rows + 1,
+                tiles.len()
+            ));
+        }
+
+        for (x, tile) in tiles.into_iter().enumerate() {
+            pos.board.set(Vec2::new(x as isize, rows), tile);
+        }
+
+        rows += 1;
+    }
+
+    if rows < 8 {
+        return Err(format!("The diagram has {rows} rows instead of 8"));
+    }
+
+    pos.next_player = next_player.unwrap_or(Tile::X);
+
+    if pos.valid_moves().is_empty() {
+        pos.next_player = pos.next_player.opponent();
+
+        if pos.valid_moves().is_empty() {
+            pos.next_player = Tile::Empty;
+        }
+    }
+
+    Ok(pos)
+}
+
+/// Reads moves written one after the other from the initial position, like `f5d6c3`, ignoring
+/// whitespace. The moves aren't checked to be valid.
+pub fn parse_move_string(text: &str) -> Option<Vec<Vec2>> {
+    let chars: Vec<char> = text
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .map(|c| c.to_ascii_lowercase())
+        .collect();
+
+    if chars.is_empty() {
+        return None;
+    }
+
+    // a single character left at the end isn't parsed as a move either
+    chars
+        .chunks(2)
+        .map(|mv| net::parse_move(&mv.iter().collect::<String>()))
+        .collect()
+}
+
+fn diagram_tile(c: char) -> Option<Tile> {
+    match c {
+        '.' | '-' | '_' => Some(Tile::Empty),
+        'x' | 'X' => Some(Tile::X),
+        'o' | 'O' => Some(Tile::O),
+        _ => None,
+    }
+}
+
+// like `a b c d e f g h`
+fn is_column_header(line: &str) -> bool {
+    line.chars()
+        .filter(|c| !c.is_whitespace())
+        .eq("abcdefgh".chars())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn initial_position() {
+        let diagram = "\
+            ........\n\
+            ........\n\
+            ........\n\
+            ...ox...\n\
+            ...xo...\n\
+            ........\n\
+            ........\n\
+            ........\n";
+
+        assert!(same_pos(&parse_diagram(diagram).unwrap(), &Pos::new()));
+    }
+
+    #[test]
+    fn console_diagram() {
+        let mut pos = Pos::new();
+        let mv = pos.valid_moves()[0];
+        pos.play(mv);
+
+        let parsed = parse_diagram(&board_diagram(&pos, Some(mv))).unwrap();
+        assert!(same_pos(&parsed, &pos));
+    }
+
+    #[test]
+    fn passing_player() {
+        // neither player has a disk to flip
+        let diagram = "ooo_____\n".to_owned() + &"________\n".repeat(7) + "x\n";
+        let pos = parse_diagram(&diagram).unwrap();
+
+        assert_eq!(pos.next_player, Tile::Empty);
+    }
+
+    #[test]
+    fn move_string() {
+        let moves = parse_move_string("f5d6 C3\n").unwrap();
+
+        assert_eq!(
+            moves,
+            vec![Vec2::new(5, 4), Vec2::new(3, 5), Vec2::new(2, 2)]
+        );
+        assert_eq!(parse_move_string("f5d"), None);
+        assert_eq!(parse_move_string("f5i9"), None);
+        assert_eq!(parse_move_string("........"), None);
+    }
+
+    #[test]
+    fn errors() {
+        let short_row = "........\n".repeat(3) + ".......\n";
+        assert_eq!(
+            parse_diagram(&short_row).unwrap_err(),
+            "Line 4: row 4 has 7 tiles instead of 8"
+        );
+
+        let unknown_tile = "........\n".repeat(2) + "...q....\n";
+        assert_eq!(
+            parse_diagram(&unknown_tile).unwrap_err(),
+            "Line 3: unknown tile 'q', expected '.', 'x' or 'o'"
+        );
+
+        let missing_rows = "........\n".repeat(7);
+        assert_eq!(
+            parse_diagram(&missing_rows).unwrap_err(),
+            "The diagram has 7 rows instead of 8"
+        );
+
+        let bad_player = "........\n".repeat(8) + "z to move\n";
+        assert_eq!(
+            parse_diagram(&bad_player).unwrap_err(),
+            "Line 9: expected the next player, X or O, found 'z'"
+        );
+    }
+}
@@ -4,30 +4,26 @@ use std::{collections::HashMap, hash::Hash};
 // The whole implementation is generally ugly and inefficient.
 // However, it works and was easy to implement.
 
-pub struct Game<Player> {
-    pub players: [Player; 2],
+/// A game between two players, told apart by `Id`, e.g. `PlayerId`, with the score of the first.
+pub struct Game<Id> {
+    pub players: [Id; 2],
     pub score: f32,
 }
 
-struct HalfGame<Player> {
-    opponent: Player,
+struct HalfGame<Id> {
+    opponent: Id,
     outcome: Outcomes,
 }
 
-impl<Player> HalfGame<Player> {
-    fn new(opponent: Player, outcome: Outcomes) -> Self {
+impl<Id> HalfGame<Id> {
+    fn new(opponent: Id, outcome: Outcomes) -> Self {
         Self { opponent, outcome }
     }
 }
 
-fn new_elo<Player>(
-    player: &Player,
-    games: &[HalfGame<Player>],
-    elos: &HashMap<Player, f64>,
-    k: f64,
-) -> f64
+fn new_elo<Id>(player: &Id, games: &[HalfGame<Id>], elos: &HashMap<Id, f64>, k: f64) -> f64
 where
-    Player: Eq + Hash,
+    Id: Eq + Hash,
 {
     let rating = EloRating {
         rating: elos[player],
@@ -57,16 +53,12 @@ pub fn score_to_outcome(score: f32) -> Outcomes {
     }
 }
 
-pub fn from_single_tournament<Player>(
-    games: &[Game<Player>],
-    iterations: usize,
-    k: f64,
-) -> HashMap<Player, f64>
+pub fn from_single_tournament<Id>(games: &[Game<Id>], iterations: usize, k: f64) -> HashMap<Id, f64>
 where
-    Player: Clone + Eq + Hash,
+    Id: Clone + Eq + Hash,
 {
-    let mut games_by_player: HashMap<Player, Vec<HalfGame<Player>>> = HashMap::new();
-    let mut elos: HashMap<Player, f64> = HashMap::new();
+    let mut games_by_player: HashMap<Id, Vec<HalfGame<Id>>> = HashMap::new();
+    let mut elos: HashMap<Id, f64> = HashMap::new();
 
     for game in games {
         elos.entry(game.players[0].clone()).or_insert(1000.0);
@@ -103,10 +95,65 @@ where
     elos
 }
 
+/// The Elo difference of a player against a single opponent from its scores (0, 0.5 or 1),
+/// without a margin, so that it can be given even when one player has won every game, as if it
+/// had lost half a game more. `None` without scores.
+pub fn rating_difference(scores: &[f32]) -> Option<f64> {
+    if scores.is_empty() {
+        return None;
+    }
+
+    let games = scores.len() as f64;
+    let total = scores.iter().map(|&score| score as f64).sum::<f64>();
+    let mean = total.clamp(0.5, games - 0.5) / games;
+
+    Some(-400.0 * (1.0 / mean - 1.0).log10())
+}
+
+/// The Elo difference estimated from the scores (0, 0.5 or 1) of a player against a single
+/// opponent, with the margin of its 95% confidence interval. `None` while it can't be estimated,
+/// e.g. when one player has won every game so far.
+pub fn difference_estimate(scores: &[f32]) -> Option<(f64, f64)> {
+    if scores.len() < 2 {
+        return None;
+    }
+
+    let games = scores.len() as f64;
+
+    let mean = scores.iter().map(|&score| score as f64).sum::<f64>() / games;
+    let variance = scores
+        .iter()
+        .map(|&score| (score as f64 - mean).powi(2))
+        .sum::<f64>()
+        / games;
+
+    let margin = 1.96 * (variance / games).sqrt();
+    let (low, high) = (mean - margin, mean + margin);
+
+    if low <= 0.0 || high >= 1.0 {
+        return None;
+    }
+
+    let elo = |score: f64| -400.0 * (1.0 / score - 1.0).log10();
+
+    Some((elo(mean), (elo(high) - elo(low)) / 2.0))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn difference_of_one_sided_scores() {
+        assert_eq!(rating_difference(&[]), None);
+        assert_eq!(rating_difference(&[1.0, 0.0]), Some(0.0));
+
+        let all_wins = rating_difference(&[1.0; 4]).unwrap();
+        assert!(
+            all_wins.is_finite() && all_wins > rating_difference(&[1.0, 1.0, 0.5, 0.5]).unwrap()
+        );
+    }
+
     #[test]
     fn elo_1() {
         let games = vec![
@@ -158,4 +205,17 @@ mod tests {
 
         assert!((elos["a"] + elos["b"] + elos["c"] - 3000.0).abs() < 5.0);
     }
+
+    #[test]
+    fn difference() {
+        assert_eq!(difference_estimate(&[1.0, 1.0, 1.0]), None);
+
+        let (even, _) = difference_estimate(&[1.0, 0.0, 0.5, 0.5]).unwrap();
+        assert!(even.abs() < 1e-9);
+
+        // 75%
+        let (elo, margin) = difference_estimate(&[1.0, 1.0, 1.0, 0.0, 1.0, 1.0, 0.5, 0.5]).unwrap();
+        assert!((elo - 190.8).abs() < 0.1);
+        assert!(margin > 0.0);
+    }
 }
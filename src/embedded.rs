@@ -0,0 +1,254 @@
+use rand::{rngs::StdRng, seq::SliceRandom, SeedableRng};
+use std::{
+    fmt,
+    sync::{Arc, Mutex, PoisonError},
+    time::{Duration, Instant},
+};
+use tokio::{task, time};
+
+use crate::{error::Error, *};
+
+/// An engine written in Rust, played in-process without a program or protocol in between.
+///
+/// Engines are called on a thread of their own, one move at a time, and can't be stopped, so an
+/// engine exceeding its time limit loses the game, but finishes the move in the background.
+pub trait SyncEngine: Send {
+    /// Chooses a move of `pos.next_player`, which has to be one of `pos.valid_moves()`, within
+    /// `time`.
+    fn choose_move(&mut self, pos: Pos, time: Duration) -> Vec2;
+
+    /// Asks the engine not to use randomness, like `--deterministic` asks AIs.
+    fn set_deterministic(&mut self) {}
+}
+
+/// Creates an engine for every game the player plays, as games are played at the same time.
+pub type EngineFactory = Arc<dyn Fn() -> Box<dyn SyncEngine> + Send + Sync>;
+
+/// A `SyncEngine` playing as a player.
+pub struct EmbeddedPlayer {
+    pub name: String,
+    pub limit: Limit,
+    pub run_handle: Option<AIRunHandle>,
+    pub deterministic: bool,
+    new_engine: EngineFactory,
+    engine: Arc<Mutex<Box<dyn SyncEngine>>>,
+}
+
+impl EmbeddedPlayer {
+    pub fn new(name: String, limit: Limit, new_engine: EngineFactory) -> Self {
+        let engine = Arc::new(Mutex::new(new_engine()));
+
+        Self {
+            name,
+            limit,
+            run_handle: None,
+            deterministic: false,
+            new_engine,
+            engine,
+        }
+    }
+
+    /// The reference AIs built into the GUI, `builtin:<name>` on the command line.
+    pub fn builtin(name: &str, limit: Limit) -> Option<Self> {
+        let new_engine: EngineFactory = match name {
+            "random" => Arc::new(|| Box::<RandomEngine>::default()),
+            "greedy" => Arc::new(|| Box::new(GreedyEngine)),
+            _ => return None,
+        };
+
+        Some(Self::new(format!("builtin:{name}"), limit, new_engine))
+    }
+
+    pub fn set_deterministic(&mut self) {
+        self.deterministic = true;
+
+        self.engine
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .set_deterministic();
+    }
+
+    pub fn run(&mut self, pos: Pos) {
+        let time = self.limit.time_limit();
+
+        self.run_handle = Some(AIRunHandle::spawn(run_embedded(
+            Arc::clone(&self.engine),
+            pos,
+            time,
+        )));
+    }
+
+    pub fn try_clone(&self) -> Result<Self, Error> {
+        if self.run_handle.is_some() {
+            return Err(Error::CloneRunningEngine(self.name.clone()));
+        }
+
+        let mut clone = Self::new(self.name.clone(), self.limit, Arc::clone(&self.new_engine));
+
+        if self.deterministic {
+            clone.set_deterministic();
+        }
+
+        Ok(clone)
+    }
+}
+
+impl fmt::Debug for EmbeddedPlayer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EmbeddedPlayer")
+            .field("name", &self.name)
+            .field("limit", &self.limit)
+            .field("run_handle", &self.run_handle)
+            .field("deterministic", &self.deterministic)
+            .finish_non_exhaustive()
+    }
+}
+
+async fn run_embedded(
+    engine: Arc<Mutex<Box<dyn SyncEngine>>>,
+    pos: Pos,
+    time: Duration,
+) -> AIRunResult {
+    let deadline = Instant::now() + time;
+
+    let call = task::spawn_blocking(move || {
+        engine
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .choose_move(pos, time)
+    });
+
+    match time::timeout_at(deadline.into(), call).await {
+        Err(_) => AIRunResult::TimeOut {
+            partial_output: String::new(),
+        },
+        Ok(Err(err)) => {
+            AIRunResult::InvalidOuput(Error::InvalidOutput(format!("Engine panicked: {err}")))
+        }
+        Ok(Ok(mv)) => AIRunResult::Success(EngineOutput {
+            mv,
+            notes: None,
+            version: None,
+            warnings: Vec::new(),
+        }),
+    }
+}
+
+/// Plays a random valid move.
+#[derive(Debug)]
+pub struct RandomEngine {
+    rng: StdRng,
+}
+
+impl Default for RandomEngine {
+    fn default() -> Self {
+        Self {
+            rng: StdRng::from_entropy(),
+        }
+    }
+}
+
+impl SyncEngine for RandomEngine {
+    fn choose_move(&mut self, pos: Pos, _time: Duration) -> Vec2 {
+        *pos.valid_moves()
+            .choose(&mut self.rng)
+            .expect("Engine asked for a move without valid moves")
+    }
+
+    fn set_deterministic(&mut self) {
+        self.rng = StdRng::seed_from_u64(0);
+    }
+}
+
+/// Plays the move flipping the most disks, the first such one if there are several.
+#[derive(Debug, Default)]
+pub struct GreedyEngine;
+
+impl SyncEngine for GreedyEngine {
+    fn choose_move(&mut self, pos: Pos, _time: Duration) -> Vec2 {
+        let player = pos.next_player;
+
+        pos.valid_moves()
+            .into_iter()
+            .rev()
+            .max_by_key(|&mv| tile_count(&pos.play_clone(mv), player))
+            .expect("Engine asked for a move without valid moves")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builtin_engines_play_valid_moves() {
+        let mut pos = Pos::new();
+        let mut engines: [Box<dyn SyncEngine>; 2] =
+            [Box::<RandomEngine>::default(), Box::new(GreedyEngine)];
+
+        while !pos.is_game_over() {
+            let engine = &mut engines[usize::from(pos.next_player == Tile::O)];
+            let mv = engine.choose_move(pos, Duration::from_millis(100));
+
+            assert!(pos.is_valid_move(mv));
+            pos.play(mv);
+        }
+    }
+
+    struct PanickingEngine;
+
+    impl SyncEngine for PanickingEngine {
+        fn choose_move(&mut self, _pos: Pos, _time: Duration) -> Vec2 {
+            panic!("no move");
+        }
+    }
+
+    #[test]
+    fn panicking_engine_forfeits() {
+        let limit = Limit::Time(Duration::from_secs(5));
+        let panicking = EmbeddedPlayer::new(
+            "panicking".to_owned(),
+            limit,
+            Arc::new(|| Box::new(PanickingEngine)),
+        );
+        let greedy = EmbeddedPlayer::builtin("greedy", limit).unwrap();
+
+        let mut game = Game::new(0, [Player::Embedded(panicking), Player::Embedded(greedy)]);
+        let mut console = Console::new(Level::Info);
+
+        game.initialize(&mut console).unwrap();
+
+        // the console prints the position the engine failed in
+        while !game.is_game_over() {
+            game.update(&mut console).unwrap();
+            std::thread::sleep(Duration::from_millis(5));
+        }
+
+        assert_eq!(game.winner, Some(Tile::O));
+        assert!(game.failure.is_some());
+    }
+
+    #[test]
+    fn greedy_takes_most_disks() {
+        let mut pos = Pos::new();
+
+        for _ in 0..2 {
+            let mv = pos.valid_moves()[0];
+            pos.play(mv);
+        }
+
+        let disks = |mv| tile_count(&pos.play_clone(mv), pos.next_player);
+
+        let most = pos.valid_moves().into_iter().map(disks).max().unwrap();
+        let mv = GreedyEngine.choose_move(pos, Duration::ZERO);
+
+        assert_eq!(disks(mv), most);
+        assert_eq!(
+            mv,
+            pos.valid_moves()
+                .into_iter()
+                .find(|&mv| disks(mv) == most)
+                .unwrap()
+        );
+    }
+}
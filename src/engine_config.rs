@@ -0,0 +1,425 @@
+use crate::run;
+use std::{
+    fmt,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+/// How an AI is launched. Written as the path of the AI, optionally followed by options in the
+/// form `|<key>=<value>`, both on the command line and in AI lists.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EngineConfig {
+    pub path: PathBuf,
+    // program (with arguments) the path is passed to, e.g. `java -jar`
+    pub interpreter: Option<Vec<String>>,
+    // AIs often look for their data files (opening books, weights) next to themselves
+    pub working_dir: Option<PathBuf>,
+    pub env: Vec<(String, String)>,
+    // the engine the program is a build of, and which version, so that reports can tell apart
+    // versions of the same engine, whatever their paths are
+    pub name: Option<String>,
+    pub version: Option<String>,
+    // shell command building the AI before it is run, in `source_dir`, e.g. `cargo build --release`
+    pub build: Option<String>,
+    // where the sources are, for rebuilding the AI only if they changed since it was built
+    pub source_dir: Option<PathBuf>,
+    // settings of the engine, like `hash=256`, so that one program can play with several
+    pub options: Vec<(String, String)>,
+    pub pass_options: PassOptions,
+}
+
+/// How the options of an engine are given to it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PassOptions {
+    /// As command line arguments, like `--hash=256`.
+    #[default]
+    Args,
+    /// As lines before the board in the input of every move, like `option hash 256`.
+    Input,
+}
+
+impl FromStr for PassOptions {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "args" => Ok(PassOptions::Args),
+            "input" => Ok(PassOptions::Input),
+            other => Err(format!(
+                "Invalid way of passing options '{other}', expected args or input"
+            )),
+        }
+    }
+}
+
+impl EngineConfig {
+    pub fn new(path: PathBuf) -> Self {
+        Self {
+            path,
+            interpreter: None,
+            working_dir: None,
+            env: Vec::new(),
+            name: None,
+            version: None,
+            build: None,
+            source_dir: None,
+            options: Vec::new(),
+            pass_options: PassOptions::Args,
+        }
+    }
+
+    /// The engine the program is a build of, or its path if it isn't named.
+    pub fn engine(&self) -> String {
+        match &self.name {
+            Some(name) => name.clone(),
+            None => self.path.display().to_string(),
+        }
+    }
+
+    /// The engine with its version and options, like `myengine v1.2 [hash=256]`, as it is shown
+    /// in results.
+    pub fn label(&self) -> String {
+        let mut label = match &self.version {
+            Some(version) => format!("{} {version}", self.engine()),
+            None => self.engine(),
+        };
+
+        if !self.options.is_empty() {
+            let options: Vec<String> = self
+                .options
+                .iter()
+                .map(|(key, value)| format!("{key}={value}"))
+                .collect();
+
+            label += &format!(" [{}]", options.join(", "));
+        }
+
+        label
+    }
+
+    /// The command line arguments the options are given as, if they are passed as arguments.
+    pub fn option_args(&self) -> Vec<String> {
+        match self.pass_options {
+            PassOptions::Args => self
+                .options
+                .iter()
+                .map(|(key, value)| format!("--{key}={value}"))
+                .collect(),
+            PassOptions::Input => Vec::new(),
+        }
+    }
+
+    /// The lines the options are given as before the input of every move, if they are passed
+    /// that way.
+    pub fn option_lines(&self) -> String {
+        match self.pass_options {
+            PassOptions::Args => String::new(),
+            PassOptions::Input => self
+                .options
+                .iter()
+                .map(|(key, value)| format!("option {key} {value}\n"))
+                .collect(),
+        }
+    }
+
+    /// The directory the build command is run in: the source directory, the working directory or
+    /// the current directory, in this order.
+    pub fn build_dir(&self) -> &Path {
+        self.source_dir
+            .as_deref()
+            .or(self.working_dir.as_deref())
+            .unwrap_or(Path::new("."))
+    }
+
+    /// The same config with absolute paths, so that configs of the same program compare equal
+    /// however its path was given.
+    pub fn absolute(&self) -> Self {
+        Self {
+            path: run::absolute(&self.path),
+            working_dir: self.working_dir.as_deref().map(run::absolute),
+            source_dir: self.source_dir.as_deref().map(run::absolute),
+            ..self.clone()
+        }
+    }
+
+    /// Resolves relative paths against `base`, like in AI lists, where paths are relative to the
+    /// list.
+    pub fn relative_to(mut self, base: &Path) -> Self {
+        self.path = base.join(&self.path);
+        self.working_dir = self.working_dir.map(|dir| base.join(dir));
+        self.source_dir = self.source_dir.map(|dir| base.join(dir));
+
+        self
+    }
+}
+
+impl FromStr for EngineConfig {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.split('|').map(|part| part.trim());
+
+        let path = parts.next().unwrap_or_default();
+
+        if path.is_empty() {
+            return Err(format!("AI '{s}' has an empty path"));
+        }
+
+        let mut config = Self::new(path.into());
+
+        for option in parts {
+            let Some((key, value)) = option.split_once('=') else {
+                return Err(format!(
+                    "AI option '{option}' is not in the form <key>=<value>"
+                ));
+            };
+
+            match key.trim() {
+                "interpreter" => {
+                    let interpreter: Vec<String> =
+                        value.split_whitespace().map(|arg| arg.to_owned()).collect();
+
+                    if interpreter.is_empty() {
+                        return Err(format!("AI '{path}' has an empty interpreter"));
+                    }
+
+                    config.interpreter = Some(interpreter);
+                }
+                "cwd" => config.working_dir = Some(value.trim().into()),
+                "name" => config.name = Some(value.trim().to_owned()),
+                "version" => config.version = Some(value.trim().to_owned()),
+                "build" => {
+                    if value.trim().is_empty() {
+                        return Err(format!("AI '{path}' has an empty build command"));
+                    }
+
+                    config.build = Some(value.trim().to_owned());
+                }
+                "src" => config.source_dir = Some(value.trim().into()),
+                "env" => {
+                    let Some((name, value)) = value.split_once('=') else {
+                        return Err(format!(
+                            "Environment variable '{value}' is not in the form <name>=<value>"
+                        ));
+                    };
+
+                    config.env.push((name.trim().to_owned(), value.to_owned()));
+                }
+                "option" => {
+                    let Some((name, value)) = value.split_once('=') else {
+                        return Err(format!(
+                            "Engine option '{value}' is not in the form <key>=<value>"
+                        ));
+                    };
+
+                    let (name, value) = (name.trim(), value.trim());
+
+                    // the keys and values are sent as words of a line in the input
+                    if [name, value]
+                        .iter()
+                        .any(|word| word.is_empty() || word.contains(char::is_whitespace))
+                    {
+                        return Err(format!(
+                            "Engine option '{name}={value}' needs a key and a value without spaces"
+                        ));
+                    }
+
+                    config.options.push((name.to_owned(), value.to_owned()));
+                }
+                "pass-options" => config.pass_options = value.trim().parse()?,
+                other => return Err(format!("Unknown AI option '{other}'")),
+            }
+        }
+
+        Ok(config)
+    }
+}
+
+// the same format as it is parsed from
+impl fmt::Display for EngineConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.path.display())?;
+
+        if let Some(interpreter) = &self.interpreter {
+            write!(f, "|interpreter={}", interpreter.join(" "))?;
+        }
+
+        if let Some(working_dir) = &self.working_dir {
+            write!(f, "|cwd={}", working_dir.display())?;
+        }
+
+        for (name, value) in &self.env {
+            write!(f, "|env={name}={value}")?;
+        }
+
+        if let Some(name) = &self.name {
+            write!(f, "|name={name}")?;
+        }
+
+        if let Some(version) = &self.version {
+            write!(f, "|version={version}")?;
+        }
+
+        if let Some(build) = &self.build {
+            write!(f, "|build={build}")?;
+        }
+
+        if let Some(source_dir) = &self.source_dir {
+            write!(f, "|src={}", source_dir.display())?;
+        }
+
+        for (key, value) in &self.options {
+            write!(f, "|option={key}={value}")?;
+        }
+
+        if self.pass_options != PassOptions::Args {
+            write!(f, "|pass-options=input")?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn plain_path() {
+        assert_eq!(
+            "ais/bot.exe".parse(),
+            Ok(EngineConfig::new("ais/bot.exe".into()))
+        );
+    }
+
+    #[test]
+    fn interpreter() {
+        let config: EngineConfig = "bot.jar | interpreter=java -jar".parse().unwrap();
+
+        assert_eq!(config.path, PathBuf::from("bot.jar"));
+        assert_eq!(
+            config.interpreter,
+            Some(vec!["java".to_owned(), "-jar".to_owned()])
+        );
+    }
+
+    #[test]
+    fn working_dir_and_env() {
+        let config: EngineConfig = "bot/bot.exe|cwd=bot|env=THREADS=1|env=BOOK=a=b.txt"
+            .parse()
+            .unwrap();
+
+        assert_eq!(config.working_dir, Some(PathBuf::from("bot")));
+        assert_eq!(
+            config.env,
+            vec![
+                ("THREADS".to_owned(), "1".to_owned()),
+                ("BOOK".to_owned(), "a=b.txt".to_owned()),
+            ]
+        );
+
+        let config = config.relative_to(Path::new("ais"));
+
+        assert_eq!(config.path, Path::new("ais").join("bot/bot.exe"));
+        assert_eq!(config.working_dir, Some(Path::new("ais").join("bot")));
+    }
+
+    #[test]
+    fn invalid_options() {
+        assert!("bot|interpreter".parse::<EngineConfig>().is_err());
+        assert!("bot|interpreter=".parse::<EngineConfig>().is_err());
+        assert!("bot|color=red".parse::<EngineConfig>().is_err());
+        assert!("bot|env=THREADS".parse::<EngineConfig>().is_err());
+        assert!("|interpreter=python3".parse::<EngineConfig>().is_err());
+        assert!("bot|build= ".parse::<EngineConfig>().is_err());
+        assert!("bot|option=hash".parse::<EngineConfig>().is_err());
+        assert!("bot|option=hash size=256".parse::<EngineConfig>().is_err());
+        assert!("bot|option=style=very aggressive"
+            .parse::<EngineConfig>()
+            .is_err());
+        assert!("bot|pass-options=stdin".parse::<EngineConfig>().is_err());
+    }
+
+    #[test]
+    fn engine_options() {
+        let config: EngineConfig = "bot|option=hash=256|option=style=aggressive"
+            .parse()
+            .unwrap();
+
+        assert_eq!(config.label(), "bot [hash=256, style=aggressive]");
+        assert_eq!(
+            config.option_args(),
+            vec!["--hash=256", "--style=aggressive"]
+        );
+        assert_eq!(config.option_lines(), "");
+        assert_eq!(config.to_string().parse(), Ok(config.clone()));
+
+        let config: EngineConfig = "bot|option=hash=256|pass-options=input".parse().unwrap();
+
+        assert!(config.option_args().is_empty());
+        assert_eq!(config.option_lines(), "option hash 256\n");
+        assert_eq!(config.to_string().parse(), Ok(config));
+
+        // the same program with other options is another player
+        assert_ne!(
+            "bot|option=hash=256"
+                .parse::<EngineConfig>()
+                .unwrap()
+                .to_string(),
+            "bot|option=hash=512"
+                .parse::<EngineConfig>()
+                .unwrap()
+                .to_string()
+        );
+    }
+
+    #[test]
+    fn name_and_version() {
+        let config: EngineConfig = "builds/0412/bot|name=bot|version=v1.2".parse().unwrap();
+
+        assert_eq!(config.label(), "bot v1.2");
+        assert_eq!(config.engine(), "bot");
+        assert_eq!(EngineConfig::new("bot".into()).label(), "bot");
+    }
+
+    #[test]
+    fn build() {
+        let config: EngineConfig = "bot/target/release/bot|build=cargo build --release|src=bot"
+            .parse()
+            .unwrap();
+
+        assert_eq!(config.build.as_deref(), Some("cargo build --release"));
+        assert_eq!(config.build_dir(), Path::new("bot"));
+
+        let config: EngineConfig = "bot/bot|build=make|cwd=bot".parse().unwrap();
+
+        assert_eq!(config.build_dir(), Path::new("bot"));
+    }
+
+    #[test]
+    fn absolute_paths() {
+        use std::env;
+
+        let relative: EngineConfig = "ais/../ais/./bot|cwd=ais".parse().unwrap();
+        let absolute: EngineConfig = format!(
+            "{}|cwd={}",
+            env::current_dir().unwrap().join("ais/bot").display(),
+            env::current_dir().unwrap().join("ais").display()
+        )
+        .parse()
+        .unwrap();
+
+        assert_ne!(relative, absolute);
+        assert_eq!(relative.absolute(), absolute.absolute());
+    }
+
+    #[test]
+    fn display_round_trip() {
+        let config: EngineConfig =
+            "bot.jar|interpreter=java -jar|cwd=bot|env=THREADS=1|name=bot|version=v2|build=make|src=src"
+                .parse()
+                .unwrap();
+
+        assert_eq!(config.to_string().parse(), Ok(config));
+    }
+}
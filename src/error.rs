@@ -0,0 +1,57 @@
+use std::{io, path::PathBuf};
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum Error {
+    // arguments
+    #[error("Expected arguments")]
+    NoArguments,
+    #[error("Unknown mode '{0}'")]
+    UnknownMode(String),
+    #[error("Unrecognised option '{0}'")]
+    UnrecognisedOption(String),
+    #[error("Unexpected end of arguments, expected {0}")]
+    MissingArgument(String),
+    #[error("Error converting {what} to integer, which is '{value}'")]
+    InvalidInteger { what: String, value: String },
+    #[error("{0}")]
+    InvalidValue(String),
+    #[error("{0} must be positive")]
+    NotPositive(String),
+    #[error("Human player is not accepted")]
+    HumanNotAccepted,
+    #[error("{0}")]
+    InvalidEngineConfig(String),
+    #[error("{0}")]
+    InvalidAIList(String),
+    #[error("{0}")]
+    InvalidAIListEntry(String),
+
+    // IO
+    #[error("Unable to read {what}: {source}")]
+    Read { what: String, source: io::Error },
+    #[error("Unable to write {what}: {source}")]
+    Write { what: String, source: io::Error },
+    #[error("Unable to start spectator server on port {port}: {source}")]
+    Serve { port: u16, source: io::Error },
+
+    // engines
+    #[error("{0}")]
+    InvalidEngine(String),
+    #[error("Error encountered while trying to run AI '{}': {source}", path.display())]
+    RunEngine { path: PathBuf, source: io::Error },
+    #[error("{0}")]
+    Build(String),
+    #[error("{0}")]
+    Preflight(String),
+    #[error("Unable to clone AI '{0}' while it is running")]
+    CloneRunningEngine(String),
+
+    // protocol
+    #[error("{0}")]
+    InvalidOutput(String),
+
+    // network play
+    #[error("{0}")]
+    Network(String),
+}
@@ -0,0 +1,197 @@
+use serde::Serialize;
+use std::{io::Write, thread, time::Duration};
+
+use crate::{error::Error, *};
+
+/// A position of the positions file of eval mode, with the line it was read from.
+#[derive(Debug)]
+pub struct Position {
+    pub line: String,
+    pub pos: Pos,
+}
+
+/// One line of the output of eval mode. Either `move` or `error` is set.
+#[derive(Debug, Serialize)]
+struct Evaluation<'a> {
+    position: &'a str,
+    #[serde(rename = "move")]
+    mv: Option<String>,
+    notes: Option<String>,
+    error: Option<String>,
+}
+
+/// Reads positions given as 64 tile characters and the next player, e.g.
+/// `...........................OX......XO........................... X`. Empty lines and lines
+/// starting with `#` are skipped.
+pub fn read_positions(content: &str) -> Result<Vec<Position>, Error> {
+    content
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty() && !line.trim_start().starts_with('#'))
+        .map(|(i, line)| {
+            let line = line.trim();
+
+            let pos = line
+                .split_once(char::is_whitespace)
+                .and_then(|(board, next_player)| {
+                    let mut next_player = next_player.trim().chars();
+
+                    match (next_player.next(), next_player.next()) {
+                        (Some(next_player), None) => parse_pos(board, next_player),
+                        _ => None,
+                    }
+                })
+                .ok_or_else(|| {
+                    Error::InvalidValue(format!(
+                        "Line {} of <positions file> is not in the form <board> <next player>",
+                        i + 1
+                    ))
+                })?;
+
+            Ok(Position {
+                line: line.to_owned(),
+                pos,
+            })
+        })
+        .collect()
+}
+
+/// Asks the AI for a move in every position, running at most `max_concurrency` at once, and
+/// writes the results to `output` as JSON lines in the order of the positions.
+pub fn evaluate(
+    ai: &AI,
+    positions: &[Position],
+    max_concurrency: usize,
+    output: &mut dyn Write,
+    console: &mut Console,
+) -> Result<(), Error> {
+    let mut results: Vec<Option<Evaluation>> = positions.iter().map(|_| None).collect();
+    let mut written = 0;
+    let poss: Vec<Pos> = positions.iter().map(|position| position.pos).collect();
+
+    run_batch(
+        ai,
+        &poss,
+        max_concurrency,
+        console,
+        |idx, result, console| {
+            let position = &positions[idx];
+
+            let evaluation = match result {
+                Ok(output) => Evaluation {
+                    position: &position.line,
+                    mv: Some(output.mv.move_string()),
+                    notes: output.notes,
+                    error: None,
+                },
+                Err(error) => {
+                    console.warn(
+                        Category::Errors,
+                        &format!("Evaluating '{}' failed: {error}", position.line),
+                    );
+
+                    Evaluation {
+                        position: &position.line,
+                        mv: None,
+                        notes: None,
+                        error: Some(error),
+                    }
+                }
+            };
+
+            results[idx] = Some(evaluation);
+
+            while let Some(Some(evaluation)) = results.get(written) {
+                let line = serde_json::to_string(evaluation).expect("Error serializing evaluation");
+
+                writeln!(output, "{line}").map_err(|source| Error::Write {
+                    what: "<output file>".to_owned(),
+                    source,
+                })?;
+
+                written += 1;
+            }
+
+            Ok(())
+        },
+    )
+}
+
+/// Asks the AI for a move in every position, running at most `max_concurrency` at once. `done` is
+/// called with the index of each position as soon as its move, or the reason it has none, is
+/// known.
+pub fn run_batch(
+    ai: &AI,
+    positions: &[Pos],
+    max_concurrency: usize,
+    console: &mut Console,
+    mut done: impl FnMut(usize, Result<EngineOutput, String>, &mut Console) -> Result<(), Error>,
+) -> Result<(), Error> {
+    // the AIs running, with the index of their position
+    let mut running: Vec<(AI, usize)> = Vec::new();
+    let mut next = 0;
+    let mut finished = 0;
+
+    while finished < positions.len() {
+        while running.len() < max_concurrency && next < positions.len() {
+            let pos = positions[next];
+            let mut runner = ai.try_clone()?;
+
+            if pos.valid_moves().is_empty() {
+                done(next, Err("no valid moves".to_owned()), console)?;
+                finished += 1;
+            } else {
+                match runner.run(pos) {
+                    Ok(()) => running.push((runner, next)),
+                    Err(err) => {
+                        done(next, Err(format!("unable to run AI: {err}")), console)?;
+                        finished += 1;
+                    }
+                }
+            }
+
+            next += 1;
+        }
+
+        let mut results = Vec::new();
+
+        running.retain_mut(|(runner, idx)| {
+            let pos = positions[*idx];
+
+            let handle = runner
+                .ai_run_handle
+                .as_mut()
+                .expect("Expected an AI run handle for evaluation");
+
+            let result = match handle.check() {
+                AIRunResult::Running => return true,
+                AIRunResult::Success(output) if pos.is_valid_move(output.mv) => Ok(output),
+                AIRunResult::Success(output) => {
+                    Err(format!("invalid move: {}", output.mv.move_string()))
+                }
+                AIRunResult::TimeOut { .. } => Err("exceeded time limit".to_owned()),
+                AIRunResult::RuntimeError { status, .. } => {
+                    Err(format!("program exit code was non-zero: {status}"))
+                }
+                AIRunResult::InvalidOuput(err) => Err(err.to_string()),
+                AIRunResult::ConnectionError(err) => Err(format!("unable to reach AI: {err}")),
+            };
+
+            results.push((*idx, result));
+            false
+        });
+
+        for (idx, result) in results {
+            done(idx, result, console)?;
+            finished += 1;
+        }
+
+        console.pin(format!("Evaluated: {finished}/{}", positions.len()));
+
+        thread::sleep(Duration::from_millis(5));
+    }
+
+    console.unpin();
+
+    Ok(())
+}
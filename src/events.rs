@@ -0,0 +1,203 @@
+use serde::Serialize;
+use std::{
+    fs::File,
+    io::{self, stdout, BufWriter, Write},
+    path::Path,
+    time::{Instant, SystemTime, UNIX_EPOCH},
+};
+
+use crate::{observer::*, *};
+
+/// Writes every event of the games as a JSON line as soon as it happens: to stdout, so that
+/// scripts and dashboards can follow the games, in which case the console is moved to stderr, or
+/// to a log file with the time of every event, for finding out afterwards why a long run was slow
+/// or which AIs were flaky.
+#[derive(Debug)]
+pub struct JsonEvents {
+    output: Output,
+}
+
+#[derive(Debug)]
+enum Output {
+    Stdout,
+    Log {
+        file: BufWriter<File>,
+        created: Instant,
+    },
+}
+
+// an event of the log file, with when it happened both as a Unix timestamp and since the start
+#[derive(Debug, Serialize)]
+struct Timestamped<'a> {
+    time_ms: u128,
+    elapsed_ms: u128,
+    #[serde(flatten)]
+    event: Event<'a>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+enum Event<'a> {
+    GameStart {
+        game_id: usize,
+        players: [String; 2],
+    },
+    Move {
+        game_id: usize,
+        player: char,
+        #[serde(rename = "move")]
+        mv: String,
+        notes: &'a str,
+    },
+    Undo {
+        game_id: usize,
+    },
+    EngineError {
+        game_id: usize,
+        player: char,
+        error: String,
+    },
+    EngineWarning {
+        game_id: usize,
+        player: char,
+        warning: &'a str,
+    },
+    VerificationFailure {
+        game_id: usize,
+        error: &'a str,
+    },
+    GameEnd {
+        game_id: usize,
+        // "X", "O" or "draw"
+        winner: String,
+        // the X and O disks on the final board
+        disks: [usize; 2],
+    },
+    ConcurrencyChange {
+        from: usize,
+        to: usize,
+    },
+    StartsPaused {
+        load: f32,
+    },
+    StartsResumed,
+    Truncated {
+        dropped: usize,
+    },
+    Interrupted {
+        unfinished: usize,
+    },
+}
+
+impl JsonEvents {
+    pub fn stdout() -> Self {
+        Self {
+            output: Output::Stdout,
+        }
+    }
+
+    /// Creates the log file at `path`, replacing an earlier one.
+    pub fn log_file(path: &Path) -> io::Result<Self> {
+        Ok(Self {
+            output: Output::Log {
+                file: BufWriter::new(File::create(path)?),
+                created: Instant::now(),
+            },
+        })
+    }
+
+    fn emit(&mut self, event: Event) {
+        // if nobody reads the events anymore, or the log can't be written, the games can still go
+        // on
+        match &mut self.output {
+            Output::Stdout => {
+                let line = serde_json::to_string(&event).expect("Error serializing event");
+
+                let mut stdout = stdout().lock();
+                let _ = writeln!(stdout, "{line}");
+                let _ = stdout.flush();
+            }
+            Output::Log { file, created } => {
+                let time_ms = SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .map_or(0, |time| time.as_millis());
+
+                let line = serde_json::to_string(&Timestamped {
+                    time_ms,
+                    elapsed_ms: created.elapsed().as_millis(),
+                    event,
+                })
+                .expect("Error serializing event");
+
+                // flushed every time, so that the events before a crash are kept
+                let _ = writeln!(file, "{line}");
+                let _ = file.flush();
+            }
+        }
+    }
+}
+
+impl GameObserver for JsonEvents {
+    fn on_game_start(&mut self, game: &Game) {
+        self.emit(Event::GameStart {
+            game_id: game.id,
+            players: [game.players[0].name(), game.players[1].name()],
+        });
+    }
+
+    fn on_move(&mut self, game: &Game, mv: Vec2, notes: &str) {
+        let mover = game.history[game.history.len() - 2].0.next_player;
+
+        self.emit(Event::Move {
+            game_id: game.id,
+            player: tile_char(mover),
+            mv: mv.move_string(),
+            notes,
+        });
+    }
+
+    fn on_undo(&mut self, game: &Game) {
+        self.emit(Event::Undo { game_id: game.id });
+    }
+
+    fn on_game_end(&mut self, game: &Game) {
+        self.emit(Event::GameEnd {
+            game_id: game.id,
+            winner: winner_string(game.winner.expect("game ended without a winner")),
+            disks: game.disk_counts(),
+        });
+    }
+
+    fn on_engine_error(&mut self, game: &Game, error: &EngineError) {
+        self.emit(Event::EngineError {
+            game_id: game.id,
+            player: tile_char(game.pos.next_player),
+            error: error.to_string(),
+        });
+    }
+
+    fn on_engine_warning(&mut self, game: &Game, warning: &str) {
+        self.emit(Event::EngineWarning {
+            game_id: game.id,
+            player: tile_char(game.pos.next_player),
+            warning,
+        });
+    }
+
+    fn on_verification_failure(&mut self, game: &Game, error: &str) {
+        self.emit(Event::VerificationFailure {
+            game_id: game.id,
+            error,
+        });
+    }
+
+    fn on_arena_event(&mut self, event: &ArenaEvent) {
+        self.emit(match *event {
+            ArenaEvent::ConcurrencyChanged { from, to } => Event::ConcurrencyChange { from, to },
+            ArenaEvent::StartsPaused { load } => Event::StartsPaused { load },
+            ArenaEvent::StartsResumed => Event::StartsResumed,
+            ArenaEvent::Truncated { dropped } => Event::Truncated { dropped },
+            ArenaEvent::Interrupted { unfinished } => Event::Interrupted { unfinished },
+        });
+    }
+}
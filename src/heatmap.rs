@@ -0,0 +1,109 @@
+use std::collections::BTreeMap;
+
+use crate::{analysis::Phase, results::Transcript, *};
+
+/// How often an engine played on every square in the opening, to spot where its opening book
+/// ends or what it avoids.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Heatmap {
+    // indexed by y, then x
+    pub counts: [[usize; 8]; 8],
+    pub moves: usize,
+}
+
+impl Heatmap {
+    pub fn add(&mut self, mv: Vec2) {
+        self.counts[mv.y as usize][mv.x as usize] += 1;
+        self.moves += 1;
+    }
+
+    pub fn max(&self) -> usize {
+        self.counts.iter().flatten().copied().max().unwrap_or(0)
+    }
+
+    /// The share of the moves played on every square in percent, as a board with coordinates,
+    /// with `.` for squares never played on.
+    pub fn to_text(&self) -> String {
+        let mut text = "   ".to_owned();
+
+        for column in 'a'..='h' {
+            text += &format!("{column:>4}");
+        }
+
+        for (y, row) in self.counts.iter().enumerate() {
+            text += &format!("\n{:>3}", y + 1);
+
+            for &count in row {
+                if count == 0 {
+                    text += &format!("{:>4}", ".");
+                } else {
+                    let percent = count as f64 / self.moves as f64 * 100.0;
+                    text += &format!("{:>3.0}%", percent);
+                }
+            }
+        }
+
+        text
+    }
+}
+
+/// The heatmaps of the moves every player played in the opening, by their name.
+pub fn heatmaps(transcripts: &[Transcript]) -> BTreeMap<String, Heatmap> {
+    let mut heatmaps: BTreeMap<String, Heatmap> = BTreeMap::new();
+
+    for transcript in transcripts {
+        let mut pos = transcript.start;
+
+        for &mv in &transcript.moves {
+            if !pos.is_valid_move(mv) {
+                break;
+            }
+
+            if Phase::of(&pos) == Phase::Opening {
+                let name = &transcript.players[pos.next_player as usize];
+                heatmaps.entry(name.clone()).or_default().add(mv);
+            }
+
+            pos.play(mv);
+        }
+    }
+
+    heatmaps
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn opening_moves_by_player() {
+        let mut pos = Pos::new();
+        let mut moves = Vec::new();
+
+        while !pos.is_game_over() {
+            let mv = pos.valid_moves()[0];
+            moves.push(mv);
+            pos.play(mv);
+        }
+
+        let transcript = Transcript {
+            game_id: 0,
+            players: ["a".to_owned(), "b".to_owned()],
+            start: Pos::new(),
+            moves: moves.clone(),
+        };
+
+        let heatmaps = heatmaps(&[transcript]);
+
+        // the moves until 40 squares are empty are in the opening, every one filling a square
+        assert_eq!(heatmaps["a"].moves + heatmaps["b"].moves, 21);
+
+        let first = moves[0];
+        assert_eq!(heatmaps["a"].counts[first.y as usize][first.x as usize], 1);
+
+        let text = heatmaps["a"].to_text();
+        assert_eq!(text.lines().count(), 9);
+        let share = format!("{:>3.0}%", 100.0 / heatmaps["a"].moves as f64);
+        assert!(text.contains(&share));
+    }
+}
@@ -0,0 +1,190 @@
+use std::{str::FromStr, sync::OnceLock};
+
+/// The language of the window and of the game messages of the console, chosen with `--lang`. Other
+/// messages, errors and reports are always in English.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Lang {
+    #[default]
+    En,
+    Hu,
+}
+
+impl FromStr for Lang {
+    type Err = String;
+
+    fn from_str(str: &str) -> Result<Self, Self::Err> {
+        match str.to_lowercase().as_str() {
+            "en" => Ok(Lang::En),
+            "hu" => Ok(Lang::Hu),
+            other => Err(format!("Unknown language '{other}', expected en or hu")),
+        }
+    }
+}
+
+static LANG: OnceLock<Lang> = OnceLock::new();
+
+/// Sets the language for the rest of the run, only the first call has an effect.
+pub fn set_lang(lang: Lang) {
+    let _ = LANG.set(lang);
+}
+
+pub fn lang() -> Lang {
+    LANG.get().copied().unwrap_or_default()
+}
+
+/// The user-facing texts which are translated. Texts with values are completed by the caller, in
+/// the same order in every language.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Text {
+    // console
+    GameStarted,
+    GameEnded,
+    GameAdjudicated,
+    Winner,
+    UndidMove,
+    // window
+    Thinking,
+    Wins,
+    Draw,
+    SettingsTitle,
+    Hints,
+    Theme,
+    Undo,
+    On,
+    Off,
+    ThemeClassic,
+    ThemeDark,
+    ThemeAccessible,
+    UndoTurn,
+    UndoMove,
+    GamesDone,
+    Running,
+    Elapsed,
+    Standings,
+    Failed,
+    LastGames,
+    WaitingForGames,
+    Leader,
+    // help
+    CommandLineArguments,
+    ReferenceInEnglish,
+    CopiedPosition,
+    LoadedPosition,
+    VisualPlayHelp,
+}
+
+/// The text in the language of the run.
+pub fn tr(text: Text) -> &'static str {
+    match lang() {
+        Lang::En => en(text),
+        Lang::Hu => hu(text),
+    }
+}
+
+fn en(text: Text) -> &'static str {
+    match text {
+        Text::GameStarted => "Game Started",
+        Text::GameEnded => "Game ended",
+        Text::GameAdjudicated => "Game adjudicated",
+        Text::Winner => "winner",
+        Text::UndidMove => "Undid move",
+        Text::Thinking => "thinking…",
+        Text::Wins => "wins",
+        Text::Draw => "Draw",
+        Text::SettingsTitle => "Settings\n(press the number or click to change, Esc to close)",
+        Text::Hints => "Hints",
+        Text::Theme => "Theme",
+        Text::Undo => "Undo",
+        Text::On => "on",
+        Text::Off => "off",
+        Text::ThemeClassic => "classic",
+        Text::ThemeDark => "dark",
+        Text::ThemeAccessible => "accessible",
+        Text::UndoTurn => "turn",
+        Text::UndoMove => "move",
+        Text::GamesDone => "Games done",
+        Text::Running => "running",
+        Text::Elapsed => "Elapsed",
+        Text::Standings => "Standings",
+        Text::Failed => "failed",
+        Text::LastGames => "Last games",
+        Text::WaitingForGames => "Waiting for games…",
+        Text::Leader => "leader",
+        Text::CommandLineArguments => "COMMAND LINE ARGUMENTS:",
+        Text::ReferenceInEnglish => "",
+        Text::CopiedPosition => "Copied the position to the clipboard",
+        Text::LoadedPosition => "Loaded the position from the clipboard",
+        Text::VisualPlayHelp => concat!(
+            "VISUAL PLAY:\n",
+            "\n",
+            "left click or tap: place disk.\n",
+            "long press: show the valid moves while held.\n",
+            "two-finger tap: undo.\n",
+            "z: undo.\n",
+            "Ctrl+C: copy the position to the clipboard, as a diagram after the moves leading to ",
+            "it.\n",
+            "Ctrl+V: continue the game from the pasted position, given as a diagram like for ",
+            "--position, or as moves from the initial position, like f5d6c3.\n",
+            "a: in compare and tournament mode, turn switching to a running game when the shown ",
+            "one ends on or off.\n",
+            "Esc: open or close the settings menu: hints marking the valid moves, the theme, and ",
+            "whether undo goes back to the last turn of a human or a single move. The settings are ",
+            "kept in othello_gui/settings.json in the configuration directory of the user.\n",
+        ),
+    }
+}
+
+fn hu(text: Text) -> &'static str {
+    match text {
+        Text::GameStarted => "Játszma elkezdődött",
+        Text::GameEnded => "Játszma véget ért",
+        Text::GameAdjudicated => "Játszma eldöntve",
+        Text::Winner => "győztes",
+        Text::UndidMove => "Lépés visszavonva",
+        Text::Thinking => "gondolkodik…",
+        Text::Wins => "nyert",
+        Text::Draw => "Döntetlen",
+        Text::SettingsTitle => {
+            "Beállítások\n(módosítás a számmal vagy kattintással, bezárás Esc-vel)"
+        }
+        Text::Hints => "Súgás",
+        Text::Theme => "Téma",
+        Text::Undo => "Visszavonás",
+        Text::On => "be",
+        Text::Off => "ki",
+        Text::ThemeClassic => "klasszikus",
+        Text::ThemeDark => "sötét",
+        Text::ThemeAccessible => "akadálymentes",
+        Text::UndoTurn => "kör",
+        Text::UndoMove => "lépés",
+        Text::GamesDone => "Befejezett játszmák",
+        Text::Running => "folyamatban",
+        Text::Elapsed => "Eltelt idő",
+        Text::Standings => "Állás",
+        Text::Failed => "hiba",
+        Text::LastGames => "Utolsó játszmák",
+        Text::WaitingForGames => "Várakozás a játszmákra…",
+        Text::Leader => "vezet",
+        Text::CommandLineArguments => "PARANCSSORI ARGUMENTUMOK:",
+        Text::ReferenceInEnglish => "A módok és az opciók részletes leírása angolul:",
+        Text::CopiedPosition => "Állás a vágólapra másolva",
+        Text::LoadedPosition => "Állás betöltve a vágólapról",
+        Text::VisualPlayHelp => concat!(
+            "VIZUÁLIS JÁTÉK:\n",
+            "\n",
+            "bal kattintás vagy koppintás: korong lerakása.\n",
+            "hosszú nyomás: a lehetséges lépések mutatása, amíg nyomva van.\n",
+            "kétujjas koppintás: visszavonás.\n",
+            "z: visszavonás.\n",
+            "Ctrl+C: az állás vágólapra másolása, ábraként, az odavezető lépések után.\n",
+            "Ctrl+V: a játszma folytatása a beillesztett állásból, amely a --position-höz ",
+            "hasonló ábra, vagy a kezdőállásból tett lépések, pl. f5d6c3.\n",
+            "a: összehasonlító és verseny módban annak be- vagy kikapcsolása, hogy a mutatott ",
+            "játszma végén egy futó játszma jelenjen meg.\n",
+            "Esc: a beállítások megnyitása vagy bezárása: a lehetséges lépések jelölése, a téma, ",
+            "és hogy a visszavonás az ember utolsó köréig vagy csak egy lépést lép vissza. A ",
+            "beállítások a felhasználó konfigurációs mappájában, az ",
+            "othello_gui/settings.json fájlban vannak.\n",
+        ),
+    }
+}
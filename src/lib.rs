@@ -1,232 +1,291 @@
+use ai::*;
+use bridge::*;
 use console::*;
+use embedded::*;
+use engine_config::EngineConfig;
+use error::Error;
+use net::*;
+use observer::*;
+use plugin::*;
+use remote::*;
+use rules::Rules;
+use serde::Serialize;
+use settings::UndoGranularity;
 use std::{
-    collections::HashSet,
-    error::Error,
+    collections::{BTreeMap, HashSet},
+    fmt,
     hash::Hash,
-    io::{self, Read, Write},
-    path::PathBuf,
-    process::{self, Child, Command, ExitStatus, Stdio},
-    time::*,
+    path::Path,
+    str::FromStr,
+    time::Duration,
 };
+use wasm_player::*;
 
 pub use othello_core_lib::*;
-// use run::*;
 
+pub mod ai;
+pub mod analysis;
+pub mod autosave;
+pub mod book;
+pub mod bridge;
+pub mod build;
+pub mod capture;
+pub mod concurrency;
 pub mod console;
+pub mod diagram;
 pub mod elo;
+pub mod embedded;
+pub mod engine_config;
+pub mod error;
+pub mod eval;
+pub mod events;
+pub mod heatmap;
+pub mod i18n;
+pub mod match_file;
+pub mod net;
+pub mod notify;
+pub mod observer;
+pub mod openings;
+pub mod plugin;
+pub mod ratings;
+pub mod remote;
+pub mod report;
+pub mod results;
+pub mod rules;
+pub mod run;
+pub mod serve;
+pub mod settings;
+pub mod start;
+pub mod symmetry;
+pub mod system;
+pub mod testing;
+pub mod verify;
+pub mod wasm_player;
 
 #[derive(Debug)]
-pub struct AI {
-    pub path: PathBuf,
-    pub time_limit: Duration,
-    pub ai_run_handle: Option<AIRunHandle>,
+pub enum Player {
+    AI(AI),
+    Human,
+    Network(NetPlayer),
+    Remote(RemoteEngine),
+    Bridge(BridgePlayer),
+    Plugin(PluginPlayer),
+    Wasm(WasmPlayer),
+    Embedded(EmbeddedPlayer),
 }
 
-impl AI {
-    pub fn input(&self, pos: Pos) -> String {
-        let valid_moves = pos.valid_moves();
-
-        format!(
-            "{}{}\n{}\n{} {}\n",
-            pos.board,
-            pos.next_player,
-            self.time_limit.as_millis(),
-            valid_moves.len(),
-            valid_moves
-                .iter()
-                .map(|mv| mv.move_string())
-                .collect::<Vec<_>>()
-                .join(" ")
-        )
-    }
-
-    pub fn run(&mut self, pos: Pos) -> io::Result<()> {
-        let mut child = Command::new(self.path.clone())
-            .stdin(Stdio::piped())
-            .stdout(Stdio::piped())
-            .stderr(Stdio::piped())
-            .spawn()?;
-
-        let stdin = child.stdin.as_mut().unwrap();
-        stdin.write_all(self.input(pos).as_bytes())?;
-        stdin.flush().expect("Unable to flush stdin");
-
-        let start = Instant::now();
-
-        self.ai_run_handle = Some(AIRunHandle {
-            child,
-            start,
-            time_limit: self.time_limit,
-        });
+/// Identifies a player in results, so that the same program is scored as one player whether its
+/// path was given relative or absolute, while programs with the same name are told apart.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+#[serde(transparent)]
+pub struct PlayerId(String);
 
-        Ok(())
+impl fmt::Display for PlayerId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
     }
+}
 
-    pub fn new(path: PathBuf, time_limit: Duration) -> Self {
-        Self {
-            path,
-            time_limit,
-            ai_run_handle: None,
-        }
+impl Player {
+    pub fn id(&self) -> PlayerId {
+        let id = match self {
+            Player::AI(ai) => ai.config.absolute().to_string(),
+            Player::Bridge(bridge) => format!("bridge:{}", bridge.config.absolute()),
+            Player::Plugin(plugin) => {
+                format!("dylib:{}", run::absolute(&plugin.path).display())
+            }
+            Player::Wasm(wasm) => format!("wasm:{}", run::absolute(&wasm.path).display()),
+            // these are only known by their names
+            Player::Human | Player::Network(_) | Player::Remote(_) | Player::Embedded(_) => {
+                self.name()
+            }
+        };
+
+        PlayerId(id)
     }
 
-    pub fn try_clone(&self) -> Result<Self, Box<dyn Error>> {
-        match self.ai_run_handle {
-            None => Ok(Self {
-                path: self.path.clone(),
-                time_limit: self.time_limit,
-                ai_run_handle: None,
-            }),
-            Some(_) => Err("Unable to clone ran AI".into()),
+    pub fn try_clone(&self) -> Result<Self, Error> {
+        match self {
+            Player::AI(ai) => Ok(Player::AI(ai.try_clone()?)),
+            Player::Human => Ok(Player::Human),
+            Player::Network(_) => Err(Error::Network("Unable to clone network player".to_owned())),
+            Player::Remote(remote) => Ok(Player::Remote(remote.try_clone()?)),
+            Player::Bridge(bridge) => Ok(Player::Bridge(bridge.try_clone()?)),
+            Player::Plugin(plugin) => Ok(Player::Plugin(plugin.try_clone()?)),
+            Player::Wasm(wasm) => Ok(Player::Wasm(wasm.try_clone()?)),
+            Player::Embedded(embedded) => Ok(Player::Embedded(embedded.try_clone()?)),
         }
     }
-}
 
-pub enum AIRunResult {
-    Running,
-    TimeOut,
-    RuntimeError { status: ExitStatus, stderr: String },
-    InvalidOuput(String),
-    // move, { notes, if provided }
-    Success(Vec2, Option<String>),
-}
-
-#[derive(Debug)]
-pub struct AIRunHandle {
-    child: Child,
-    start: Instant,
-    time_limit: Duration,
-}
-
-impl AIRunHandle {
-    pub fn kill(&mut self) -> io::Result<()> {
-        self.child.kill()
-    }
-
-    pub fn check(&mut self) -> AIRunResult {
-        match self
-            .child
-            .try_wait()
-            .expect("Error waiting for AI to finish")
-        {
-            Some(status) => self.handle_finished_child(status),
-            None => {
-                if self.start.elapsed() > self.time_limit {
-                    self.child.kill().unwrap();
-                    AIRunResult::TimeOut
-                } else {
-                    AIRunResult::Running
-                }
-            }
+    pub fn name(&self) -> String {
+        match self {
+            Player::AI(ai) => ai.config.label(),
+            Player::Human => "human".to_owned(),
+            Player::Network(_) => "network".to_owned(),
+            Player::Remote(remote) => format!("tcp:{}", remote.address),
+            Player::Bridge(bridge) => format!("bridge:{}", bridge.config.label()),
+            Player::Plugin(plugin) => format!("dylib:{}", plugin.path.display()),
+            Player::Wasm(wasm) => format!("wasm:{}", wasm.path.display()),
+            Player::Embedded(embedded) => embedded.name.clone(),
         }
     }
 
-    fn handle_finished_child(&mut self, status: ExitStatus) -> AIRunResult {
-        if !status.success() {
-            let mut stderr = String::new();
-
-            self.child
-                .stderr
-                .as_mut()
-                .expect("Error getting stderr of program")
-                .read_to_string(&mut stderr)
-                .expect("Error reading stderr of program");
-
-            return AIRunResult::RuntimeError { status, stderr };
+    /// The player as it is given on the command line, without its limit. `None` for network
+    /// players, which can't be recreated from arguments alone.
+    pub fn arg(&self) -> Option<String> {
+        match self {
+            Player::AI(ai) => Some(ai.config.to_string()),
+            Player::Human => Some("human".to_owned()),
+            Player::Network(_) => None,
+            Player::Remote(remote) => Some(format!("tcp:{}", remote.address)),
+            Player::Bridge(bridge) => Some(format!("bridge:{}", bridge.config)),
+            Player::Plugin(plugin) => Some(format!("dylib:{}", plugin.path.display())),
+            Player::Wasm(wasm) => Some(format!("wasm:{}", wasm.path.display())),
+            Player::Embedded(embedded) => Some(embedded.name.clone()),
         }
+    }
 
-        let mut output = String::new();
-
-        self.child
-            .stdout
-            .as_mut()
-            .expect("Error getting stdout of program")
-            .read_to_string(&mut output)
-            .expect("Error reading stdout of program");
-
-        let output: Vec<_> = output.trim().split('\n').map(|ln| ln.trim()).collect();
-
-        if !(1..=2).contains(&output.len()) {
-            return AIRunResult::InvalidOuput(format!(
-                "Output contains {} lines, which is invalid. It must be between 1 and 2.",
-                output.len()
-            ));
+    pub fn limit(&self) -> Option<Limit> {
+        match self {
+            Player::AI(ai) => Some(ai.limit),
+            Player::Remote(remote) => Some(remote.limit),
+            Player::Bridge(bridge) => Some(bridge.limit),
+            Player::Plugin(plugin) => Some(plugin.limit),
+            Player::Wasm(wasm) => Some(wasm.limit),
+            Player::Embedded(embedded) => Some(embedded.limit),
+            Player::Human | Player::Network(_) => None,
         }
+    }
 
-        let move_string = output[0];
-
-        if move_string.len() != 2 {
-            return AIRunResult::InvalidOuput(format!("Output '{move_string}' has invalid length"));
+    /// Asks the player not to use randomness, if it is an AI.
+    pub fn set_deterministic(&mut self) {
+        match self {
+            Player::AI(ai) => ai.deterministic = true,
+            Player::Remote(remote) => remote.deterministic = true,
+            Player::Bridge(bridge) => bridge.deterministic = true,
+            Player::Plugin(plugin) => plugin.deterministic = true,
+            Player::Wasm(wasm) => wasm.deterministic = true,
+            Player::Embedded(embedded) => embedded.set_deterministic(),
+            Player::Human | Player::Network(_) => {}
         }
+    }
 
-        let x_char = move_string.chars().next().unwrap();
-
-        if !('a'..='h').contains(&x_char) {
-            return AIRunResult::InvalidOuput(format!(
-                "Move '{move_string}' has invalid x coordinate"
-            ));
+    /// How the program of the player is run, if it is run locally.
+    pub fn config(&self) -> Option<&EngineConfig> {
+        match self {
+            Player::AI(ai) => Some(&ai.config),
+            Player::Bridge(bridge) => Some(&bridge.config),
+            Player::Human
+            | Player::Network(_)
+            | Player::Remote(_)
+            | Player::Plugin(_)
+            | Player::Wasm(_)
+            | Player::Embedded(_) => None,
         }
+    }
 
-        let y_char = move_string.chars().nth(1).unwrap();
-
-        if !('1'..='8').contains(&y_char) {
-            return AIRunResult::InvalidOuput(format!(
-                "Move '{move_string}' has invalid y coordinate"
-            ));
+    /// The engine the player is a version of, and which version, so that results of versions of
+    /// the same engine can be grouped.
+    pub fn engine_and_version(&self) -> (String, Option<String>) {
+        match self.config() {
+            Some(config) => (config.engine(), config.version.clone()),
+            None => (self.name(), None),
         }
+    }
 
-        let x = x_char as u32 - 'a' as u32;
-        let y = y_char as u32 - '1' as u32;
-
-        let mv = Vec2::new(x as isize, y as isize);
+    /// The program of the player, if it is run locally.
+    pub fn engine_path(&self) -> Option<&Path> {
+        self.config().map(|config| config.path.as_path())
+    }
 
-        if output.len() == 2 {
-            AIRunResult::Success(mv, Some(output[1].to_owned()))
-        } else {
-            AIRunResult::Success(mv, None)
+    /// The handle of the move being computed, if there is one.
+    pub fn run_handle(&self) -> Option<&AIRunHandle> {
+        match self {
+            Player::AI(ai) => ai.ai_run_handle.as_ref(),
+            Player::Remote(remote) => remote.run_handle.as_ref(),
+            Player::Bridge(bridge) => bridge.run_handle.as_ref(),
+            Player::Plugin(plugin) => plugin.run_handle.as_ref(),
+            Player::Wasm(wasm) => wasm.run_handle.as_ref(),
+            Player::Embedded(embedded) => embedded.run_handle.as_ref(),
+            Player::Human | Player::Network(_) => None,
         }
     }
-}
 
-/*
-// bad temporary solution for checking...
-impl Drop for AIRunHandle {
-    fn drop(&mut self) {
-        debug_assert!(
-            matches!(
-                self.child
-                    .try_wait()
-                    .expect("Error waiting for AI to finish"),
-                Some(_)
-            ),
-            "attempted to drop running AIRunHandle",
-        )
+    // players whose moves are computed in the async runtime
+    fn run_handle_mut(&mut self) -> Option<&mut Option<AIRunHandle>> {
+        match self {
+            Player::AI(ai) => Some(&mut ai.ai_run_handle),
+            Player::Remote(remote) => Some(&mut remote.run_handle),
+            Player::Bridge(bridge) => Some(&mut bridge.run_handle),
+            Player::Plugin(plugin) => Some(&mut plugin.run_handle),
+            Player::Wasm(wasm) => Some(&mut wasm.run_handle),
+            Player::Embedded(embedded) => Some(&mut embedded.run_handle),
+            Player::Human | Player::Network(_) => None,
+        }
     }
 }
-*/
 
-#[derive(Debug)]
-pub enum Player {
-    AI(AI),
-    Human,
+/// How the result of a game is turned into a score.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Scoring {
+    // 1 for a win, 0.5 for a draw and 0 for a loss
+    WinDrawLoss,
+    // own discs minus those of the opponent at the end
+    DiskDifference,
+    // own discs at the end, with the empty squares awarded to the winner, or split on a draw
+    DiskSplit,
 }
 
-impl Player {
-    pub fn try_clone(&self) -> Result<Self, Box<dyn Error>> {
-        match self {
-            Player::AI(ai) => Ok(Player::AI(ai.try_clone()?)),
-            Player::Human => Ok(Player::Human),
+impl FromStr for Scoring {
+    type Err = String;
+
+    fn from_str(str: &str) -> Result<Self, Self::Err> {
+        match str.to_lowercase().as_str() {
+            "difference" => Ok(Scoring::DiskDifference),
+            "split" => Ok(Scoring::DiskSplit),
+            other => Err(format!("Unknown disk scoring '{other}'")),
         }
     }
 }
 
+/// In compare mode every opening is played twice with colors swapped, and the two games are
+/// scored together.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Pair {
+    pub id: usize,
+    // the tile the first AI of the compare plays with
+    pub first: Tile,
+    // which compare of a batch, comparing the first AI with several others, the game is of
+    pub compare: usize,
+}
+
 #[derive(Debug)]
 pub struct Game {
     pub id: usize,
     pub pos: Pos,
     pub history: Vec<(Pos, Option<Vec2>)>,
     pub players: [Player; 2],
+    pub rules: Rules,
     pub winner: Option<Tile>,
+    // the versions the players reported, indexed the same as `players`
+    pub versions: [Option<String>; 2],
+    // the seed the opening was chosen with, if it was chosen randomly
+    pub seed: Option<u64>,
+    pub pair: Option<Pair>,
+    // the moves from the start setup, the initial position unless `--start` is given, to the
+    // starting one, if it is a known opening
+    pub opening: Option<Vec<Vec2>>,
+    // set if the game was lost by the failure of the loser's AI
+    pub failure: Option<Failure>,
+    // ended early with the result of perfect play
+    pub adjudicated: bool,
+    pub started: bool,
+    // the players still to be run once on a throwaway position before the first move, with the
+    // result discarded, so that their first timed move isn't slowed down by a cold start
+    pub warm_up: Vec<Tile>,
+    // how long the AI took for each move it played, by the index in `history` of the position
+    // after the move
+    pub move_times: BTreeMap<usize, Duration>,
 }
 
 impl Game {
@@ -266,49 +325,179 @@ impl Game {
         }
     }
 
-    pub fn play(&mut self, mv: Vec2, notes: &str, console: &Console) {
-        console.info(&format!(
-            "{} {}: {} ({})",
-            self.formatted_id(),
-            self.pos.next_player,
-            mv.move_string(),
-            notes
-        ));
+    pub fn play(&mut self, mv: Vec2, notes: &str, observer: &mut dyn GameObserver) {
+        let before = self.pos;
 
-        self.pos.play(mv);
+        self.rules.play(&mut self.pos, mv);
         self.history.push((self.pos, Some(mv)));
+        // left from a move which was undone
+        self.move_times.remove(&(self.history.len() - 1));
+
+        observer.on_move(self, mv, notes);
+
+        // placing the first disks in Reversi isn't an Othello move
+        if verify::is_enabled() && !self.rules.is_placing(&before) {
+            if let Err(err) = verify::check_move(&before, mv, &self.pos) {
+                observer.on_verification_failure(self, &err);
+            }
+        }
 
-        if self.pos.is_game_over() {
-            self.winner = Some(self.pos.winner());
+        if self.rules.is_game_over(&self.pos) {
+            self.end(self.pos.winner(), observer);
         }
     }
 
-    pub fn initialize(&mut self, console: &Console) {
-        console.info(&format!("{} Game Started", self.formatted_id()));
+    fn end(&mut self, winner: Tile, observer: &mut dyn GameObserver) {
+        self.winner = Some(winner);
+
+        for player in &mut self.players {
+            if let Player::Bridge(bridge) = player {
+                bridge.game_over(winner);
+            }
+        }
+
+        observer.on_game_end(self);
+    }
+
+    /// Ends the game with the result of perfect play from the current position.
+    pub fn adjudicate(&mut self, winner: Tile, observer: &mut dyn GameObserver) {
+        if let Some(Some(run_handle)) = self.next_player_mut().and_then(Player::run_handle_mut) {
+            run_handle.kill();
+        }
+
+        self.adjudicated = true;
+        self.end(winner, observer);
+    }
+
+    /// How long the player to move has been computing its move, and its limit, if it is one
+    /// computing moves.
+    pub fn thinking(&self) -> Option<(Duration, Option<Limit>)> {
+        let player = self.next_player()?;
+
+        Some((player.run_handle()?.elapsed(), player.limit()))
+    }
 
-        self.initialize_next_player(console);
+    /// The number of X and O disks on the board, indexed by their tile.
+    pub fn disk_counts(&self) -> [usize; 2] {
+        [Tile::X, Tile::O].map(|tile| tile_count(&self.pos, tile))
     }
 
-    pub fn initialize_next_player(&mut self, console: &Console) {
+    pub fn empty_count(&self) -> usize {
+        tile_count(&self.pos, Tile::Empty)
+    }
+
+    /// The moves played so far, without passes.
+    pub fn moves(&self) -> Vec<Vec2> {
+        self.history.iter().filter_map(|&(_, mv)| mv).collect()
+    }
+
+    pub fn initialize(&mut self, observer: &mut dyn GameObserver) -> Result<(), Error> {
+        self.started = true;
+        observer.on_game_start(self);
+
+        self.initialize_next_player()
+    }
+
+    pub fn initialize_next_player(&mut self) -> Result<(), Error> {
+        while let Some(&player) = self.warm_up.first() {
+            if self.start_warm_up(player)? {
+                return Ok(());
+            }
+
+            self.warm_up.remove(0);
+        }
+
         let pos = self.pos;
+        let moves = self.moves();
 
         match self.next_player_mut() {
             Some(Player::AI(ai)) => {
-                ai.run(pos).unwrap_or_else(|err| {
-                    eprintln!("Error encountered while trying to run AI: {err}");
-                    process::exit(4);
-                });
+                ai.run(pos).map_err(|source| Error::RunEngine {
+                    path: ai.config.path.clone(),
+                    source,
+                })?;
             }
-            Some(Player::Human) => {}
-            None => {
-                self.winner = Some(self.pos.winner());
-                console.info(&format!(
-                    "{} Game ended, winner: {}",
-                    self.formatted_id(),
-                    self.pos.winner()
-                ));
+            Some(Player::Remote(remote)) => remote.run(pos),
+            Some(Player::Plugin(plugin)) => plugin.run(pos),
+            Some(Player::Wasm(wasm)) => wasm.run(pos),
+            Some(Player::Embedded(embedded)) => embedded.run(pos),
+            Some(Player::Bridge(bridge)) => {
+                bridge.run(pos, &moves).map_err(|source| Error::RunEngine {
+                    path: bridge.config.path.clone(),
+                    source,
+                })?;
             }
+            _ => {}
         }
+
+        Ok(())
+    }
+
+    // returns whether a warm-up run was started, players that don't run locally aren't warmed up
+    fn start_warm_up(&mut self, player: Tile) -> Result<bool, Error> {
+        // the starting position, so that a late reply of a bridge to the warm-up, which has the
+        // same id as the first move, is still a valid answer to it
+        let pos = self.pos;
+
+        match &mut self.players[player as usize] {
+            Player::AI(ai) => {
+                ai.run(pos).map_err(|source| Error::RunEngine {
+                    path: ai.config.path.clone(),
+                    source,
+                })?;
+            }
+            Player::Bridge(bridge) => {
+                bridge.run(pos, &[]).map_err(|source| Error::RunEngine {
+                    path: bridge.config.path.clone(),
+                    source,
+                })?;
+            }
+            _ => return Ok(false),
+        }
+
+        Ok(true)
+    }
+
+    fn update_warm_up(
+        &mut self,
+        player: Tile,
+        observer: &mut dyn GameObserver,
+    ) -> Result<(), Error> {
+        let run_handle = self.players[player as usize]
+            .run_handle_mut()
+            .expect("Expected a local AI to warm up");
+
+        let res = run_handle
+            .as_mut()
+            .expect("Expected an AI run handle for warm-up")
+            .check();
+
+        let failure = match res {
+            AIRunResult::Running => return Ok(()),
+            AIRunResult::Success(_) => None,
+            AIRunResult::TimeOut { .. } => Some("exceeded the time limit".to_owned()),
+            AIRunResult::RuntimeError { status, .. } => {
+                Some(format!("program exit code was non-zero: {status}"))
+            }
+            AIRunResult::InvalidOuput(err) => Some(err.to_string()),
+            AIRunResult::ConnectionError(err) => Some(format!("unable to connect: {err}")),
+        };
+
+        *run_handle = None;
+        self.warm_up.remove(0);
+
+        // the game decides whether the AI works, a warm-up only primes its caches
+        if let Some(failure) = failure {
+            observer.on_engine_warning(
+                self,
+                &format!(
+                    "warm-up of '{}' failed: {failure}",
+                    self.players[player as usize].name()
+                ),
+            );
+        }
+
+        self.initialize_next_player()
     }
 
     pub fn new(id: usize, players: [Player; 2]) -> Self {
@@ -321,112 +510,270 @@ impl Game {
             pos,
             history: vec![(pos, None)],
             players,
+            rules: Rules::Othello,
             winner: None,
+            versions: [None, None],
+            seed: None,
+            pair: None,
+            opening: None,
+            failure: None,
+            adjudicated: false,
+            started: false,
+            warm_up: Vec::new(),
+            move_times: BTreeMap::new(),
         }
     }
 
-    pub fn print_input_for_debug(&mut self, console: &Console) {
-        let pos = self.pos;
+    pub fn print_input_for_debug(&self, console: &Console) {
+        let Some(player) = self.next_player() else {
+            panic!("print_input_for_debug was called after the game ended");
+        };
 
-        let Some(Player::AI(ai)) = self.next_player_mut() else {
-            panic!("print_input_for_debug was not called with an ai as next player");
+        let input = match player {
+            Player::AI(ai) => Some(ai.input(self.pos)),
+            Player::Remote(remote) => Some(remote.input(self.pos)),
+            Player::Plugin(plugin) => Some(plugin.input(self.pos)),
+            Player::Wasm(wasm) => Some(wasm.input(self.pos)),
+            Player::Bridge(bridge) => Some(bridge.request(self.pos, &self.moves())),
+            // given the position as it is, there is no input besides it
+            Player::Embedded(_) => None,
+            Player::Human | Player::Network(_) => {
+                panic!("print_input_for_debug was not called with an ai as next player")
+            }
         };
 
-        console.warn(&format!(
-            "For '{}' the input was",
-            ai.path.to_string_lossy()
-        ));
-        console.warn(&ai.input(pos));
+        if let Some(input) = input {
+            console.warn(
+                Category::EngineIo,
+                &format!("For '{}' the input was", player.name()),
+            );
+            console.warn(Category::EngineIo, &input);
+        }
+
+        // the input is hard to read for people, and doesn't show the last move
+        let last_move = self.history.last().expect("history empty").1;
+        let valid_moves: Vec<String> = self
+            .rules
+            .valid_moves(&self.pos)
+            .iter()
+            .map(Vec2::move_string)
+            .collect();
+
+        console.warn(Category::EngineIo, "The position was");
+        console.warn(Category::EngineIo, &board_diagram(&self.pos, last_move));
+        console.warn(
+            Category::EngineIo,
+            &format!("Valid moves: {}", valid_moves.join(" ")),
+        );
     }
 
-    pub fn update(&mut self, console: &Console) {
-        let Some(Player::AI(ai)) = self.next_player_mut() else {
-            return;
+    pub fn update(&mut self, observer: &mut dyn GameObserver) -> Result<(), Error> {
+        self.update_network_players(observer)?;
+
+        if let Some(&player) = self.warm_up.first() {
+            return self.update_warm_up(player, observer);
+        }
+
+        let Some(run_handle) = self.next_player_mut().and_then(Player::run_handle_mut) else {
+            return Ok(());
         };
 
-        let res = ai
-            .ai_run_handle
+        let res = run_handle
             .as_mut()
             .expect("Expected an AI run handle for next player")
             .check();
 
+        let elapsed = run_handle.as_ref().map(AIRunHandle::elapsed);
+
+        if !matches!(res, AIRunResult::Running) {
+            if let Some(io) = run_handle.take().and_then(|run_handle| run_handle.io()) {
+                observer.on_engine_io(self, &io);
+            }
+        }
+
         match res {
             AIRunResult::Running => {}
             AIRunResult::InvalidOuput(err) => {
-                console.warn(&format!(
-                    "{} Error reading AI {} move: {}",
-                    self.formatted_id(),
-                    self.pos.next_player,
-                    err
-                ));
-                self.print_input_for_debug(console);
-                self.winner = Some(self.pos.next_player.opponent());
+                self.forfeit(EngineError::InvalidOutput(err), observer);
             }
             AIRunResult::RuntimeError { status, stderr } => {
-                console.warn(&format!(
-                    "{} AI {} program exit code was non-zero: {}",
-                    self.formatted_id(),
-                    self.pos.next_player,
-                    status.code().unwrap(),
-                ));
-                console.warn("stderr of AI program:");
-                console.warn(&stderr);
-                self.print_input_for_debug(console);
-                self.winner = Some(self.pos.next_player.opponent());
+                self.forfeit(EngineError::RuntimeError { status, stderr }, observer);
+            }
+            AIRunResult::TimeOut { partial_output } => {
+                self.forfeit(EngineError::TimeOut { partial_output }, observer);
             }
-            AIRunResult::TimeOut => {
-                console.warn(&format!(
-                    "{} AI {} program exceeded time limit",
-                    self.formatted_id(),
-                    self.pos.next_player
-                ));
-                self.print_input_for_debug(console);
-                self.winner = Some(self.pos.next_player.opponent());
+            AIRunResult::ConnectionError(err) => {
+                self.forfeit(EngineError::Connection(err), observer);
             }
-            AIRunResult::Success(mv, notes) => {
-                ai.ai_run_handle = None;
-                if self.pos.is_valid_move(mv) {
+            AIRunResult::Success(output) => {
+                if let Some(version) = output.version {
+                    self.versions[self.pos.next_player as usize] = Some(version);
+                }
+
+                for warning in &output.warnings {
+                    observer.on_engine_warning(self, warning);
+                }
+
+                let mv = output.mv;
+
+                let node_limit = match self.next_player().and_then(Player::limit) {
+                    Some(Limit::Nodes(limit)) => Some(limit),
+                    _ => None,
+                };
+                let nodes = output.notes.as_deref().and_then(reported_nodes);
+
+                if let (Some(limit), Some(nodes)) = (node_limit, nodes) {
+                    if nodes > limit {
+                        self.forfeit(EngineError::NodeLimitExceeded { nodes, limit }, observer);
+                        return Ok(());
+                    }
+                }
+
+                if self.rules.is_valid_move(&self.pos, mv) {
                     self.play(
                         mv,
-                        &notes.unwrap_or_else(|| "no notes provided".to_owned()),
-                        console,
+                        &output
+                            .notes
+                            .unwrap_or_else(|| "no notes provided".to_owned()),
+                        observer,
                     );
-                    self.initialize_next_player(console);
+
+                    if let Some(elapsed) = elapsed {
+                        self.move_times.insert(self.history.len() - 1, elapsed);
+                    }
+
+                    self.initialize_next_player()?;
                 } else {
-                    console.warn(&format!(
-                        "{} Invalid move played by AI {}: {}",
-                        self.formatted_id(),
-                        self.pos.next_player,
-                        mv.move_string()
-                    ));
-                    self.print_input_for_debug(console);
-                    self.winner = Some(self.pos.next_player.opponent());
+                    self.forfeit(EngineError::InvalidMove(mv), observer);
                 }
             }
         }
+
+        Ok(())
     }
 
-    pub fn undo(&mut self, console: &Console) {
-        if let Some(Player::AI(ai)) = self.next_player_mut() {
-            if let Some(run_handle) = &mut ai.ai_run_handle {
-                run_handle.kill().unwrap_or_default();
+    // Network players are updated even if they aren't next, as they have to be sent the moves
+    // of the local player, and may reconnect at any time.
+    fn update_network_players(&mut self, observer: &mut dyn GameObserver) -> Result<(), Error> {
+        let moves = self.moves();
+
+        for player in &mut self.players {
+            if let Player::Network(net) = player {
+                net.update(&moves)?;
             }
         }
 
+        let Some(Player::Network(net)) = self.next_player() else {
+            return Ok(());
+        };
+
+        let Some(mv) = net.next_move(moves.len()) else {
+            return Ok(());
+        };
+
+        if !self.rules.is_valid_move(&self.pos, mv) {
+            return Err(Error::Network(format!(
+                "Invalid move received: {}",
+                mv.move_string()
+            )));
+        }
+
+        self.play(mv, "network", observer);
+        self.initialize_next_player()
+    }
+
+    // the AI to move lost the game because of `error`
+    fn forfeit(&mut self, error: EngineError, observer: &mut dyn GameObserver) {
+        observer.on_engine_error(self, &error);
+
+        self.failure = Some(error.failure());
+        self.end(self.pos.next_player.opponent(), observer);
+    }
+
+    /// Ends the game as a loss for the next player if its program couldn't be started, so that
+    /// an arena can go on with its other games. Other errors are returned as they are.
+    pub fn forfeit_on_start_failure(
+        &mut self,
+        result: Result<(), Error>,
+        observer: &mut dyn GameObserver,
+    ) -> Result<(), Error> {
+        match result {
+            Err(Error::RunEngine { path, source }) => {
+                self.forfeit(EngineError::StartFailed { path, source }, observer);
+                Ok(())
+            }
+            result => result,
+        }
+    }
+
+    pub fn undo(
+        &mut self,
+        granularity: UndoGranularity,
+        observer: &mut dyn GameObserver,
+    ) -> Result<(), Error> {
+        if let Some(Some(run_handle)) = self.next_player_mut().and_then(Player::run_handle_mut) {
+            run_handle.kill();
+        }
+
         self.winner = None;
 
         while self.history.len() >= 2 {
             self.history.pop();
-            console.info(&format!("{} Undid move", self.formatted_id()));
 
             self.pos = self.history.last().expect("history empty").0;
+            observer.on_undo(self);
+
+            if granularity == UndoGranularity::Move {
+                break;
+            }
 
             if let Some(Player::Human) = self.next_player() {
                 break;
             }
         }
 
-        self.initialize_next_player(console);
+        self.initialize_next_player()
+    }
+
+    /// Replaces the game so far with `moves` played from `start`, e.g. with a position pasted
+    /// from the clipboard. The game is left as it was if a move is invalid.
+    pub fn load(
+        &mut self,
+        start: Pos,
+        moves: &[Vec2],
+        observer: &mut dyn GameObserver,
+    ) -> Result<(), Error> {
+        let start = self.rules.with_next_player(start);
+        let mut history = vec![(start, None)];
+        let mut pos = start;
+
+        for &mv in moves {
+            if !self.rules.is_valid_move(&pos, mv) {
+                return Err(Error::InvalidValue(format!(
+                    "Invalid move {} after {} moves",
+                    mv.move_string(),
+                    history.len() - 1
+                )));
+            }
+
+            self.rules.play(&mut pos, mv);
+            history.push((pos, Some(mv)));
+        }
+
+        if let Some(Some(run_handle)) = self.next_player_mut().and_then(Player::run_handle_mut) {
+            run_handle.kill();
+        }
+
+        self.winner = None;
+        self.pos = pos;
+        self.history = history;
+
+        if self.rules.is_game_over(&self.pos) {
+            self.end(self.pos.winner(), observer);
+            return Ok(());
+        }
+
+        self.initialize_next_player()
     }
 
     pub fn is_game_over(&self) -> bool {
@@ -441,21 +788,141 @@ impl Game {
         Some(&mut self.players[self.winner? as usize])
     }
 
-    pub fn score_for(&self, tile: Tile) -> f32 {
+    /// The score of `tile` in the finished game. With disk scoring, the empty squares of games
+    /// not played to the end, because an AI failed or they were adjudicated, are awarded to the
+    /// winner.
+    pub fn score_for(&self, tile: Tile, scoring: Scoring) -> f32 {
         let winner = self.winner.unwrap();
 
         debug_assert!(tile != Tile::Empty);
 
         let relation = winner.relation(tile);
 
-        match relation {
+        let share = match relation {
             Relation::Same => 1.0,
             Relation::Neutral => 0.5,
             Relation::Opponent => 0.0,
+        };
+
+        let disks = self.disk_counts();
+        let (own, opponent) = (
+            disks[tile as usize] as f32,
+            disks[tile.opponent() as usize] as f32,
+        );
+        let empties = 64.0 - own - opponent;
+
+        match scoring {
+            Scoring::WinDrawLoss => share,
+            Scoring::DiskDifference if self.pos.next_player == Tile::Empty => own - opponent,
+            Scoring::DiskDifference => own + empties * share - (opponent + empties * (1.0 - share)),
+            Scoring::DiskSplit => own + empties * share,
         }
     }
 }
 
+/// The tile as it is written in the protocol.
+pub fn tile_char(tile: Tile) -> char {
+    match tile {
+        Tile::X => 'X',
+        Tile::O => 'O',
+        Tile::Empty => '.',
+    }
+}
+
+/// The inverse of `tile_char`.
+pub fn parse_tile(c: char) -> Option<Tile> {
+    match c {
+        'X' => Some(Tile::X),
+        'O' => Some(Tile::O),
+        '.' => Some(Tile::Empty),
+        _ => None,
+    }
+}
+
+/// A position from its board as 64 tile characters, row by row like `board_rows`, and the tile
+/// character of the next player.
+pub fn parse_pos(board: &str, next_player: char) -> Option<Pos> {
+    if board.chars().count() != 64 {
+        return None;
+    }
+
+    let mut pos = Pos::new();
+    pos.next_player = parse_tile(next_player)?;
+
+    for (i, c) in board.chars().enumerate() {
+        pos.board
+            .set(Vec2::new(i as isize % 8, i as isize / 8), parse_tile(c)?);
+    }
+
+    Some(pos)
+}
+
+/// The winner as it is written in machine-readable output: `X`, `O` or `draw`.
+pub fn winner_string(winner: Tile) -> String {
+    match winner {
+        Tile::Empty => "draw".to_owned(),
+        tile => tile_char(tile).to_string(),
+    }
+}
+
+/// The rows of the board from top to bottom, as they are written in the protocol.
+pub fn board_rows(board: &Board) -> Vec<String> {
+    (0..8)
+        .map(|y| {
+            (0..8)
+                .map(|x| tile_char(board.get(Vec2::new(x, y))))
+                .collect()
+        })
+        .collect()
+}
+
+/// The number of squares of the board with `tile`, e.g. the number of empty squares.
+pub fn tile_count(pos: &Pos, tile: Tile) -> usize {
+    Vec2::board_iter()
+        .filter(|&coor| pos.board.get(coor) == tile)
+        .count()
+}
+
+/// Whether the positions have the same board and the same player to move.
+pub fn same_pos(pos1: &Pos, pos2: &Pos) -> bool {
+    pos1.next_player == pos2.next_player && board_rows(&pos1.board) == board_rows(&pos2.board)
+}
+
+/// The board with coordinates for the console, the last move is marked with brackets.
+pub fn board_diagram(pos: &Pos, last_move: Option<Vec2>) -> String {
+    let mut diagram = "   a b c d e f g h\n".to_owned();
+
+    for y in 0..8 {
+        diagram.push_str(&format!("{} ", y + 1));
+
+        for x in 0..8 {
+            let coor = Vec2::new(x, y);
+
+            // the brackets take the place of the spaces between the tiles
+            let before = if Some(coor) == last_move {
+                '['
+            } else if x > 0 && Some(Vec2::new(x - 1, y)) == last_move {
+                ']'
+            } else {
+                ' '
+            };
+
+            diagram.push(before);
+            diagram.push(tile_char(pos.board.get(coor)));
+        }
+
+        if Some(Vec2::new(7, y)) == last_move {
+            diagram.push(']');
+        }
+
+        diagram.push('\n');
+    }
+
+    diagram.push_str(&format!("{} to move", pos.next_player));
+
+    diagram
+}
+
 // https://stackoverflow.com/questions/46766560/how-to-check-if-there-are-duplicates-in-a-slice
 pub fn has_unique_elements<T>(iter: T) -> bool
 where
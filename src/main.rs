@@ -1,23 +1,122 @@
+use ai::*;
 use ambassador::{delegatable_trait, Delegate};
+use analysis::{MoveQuality, Phase, TimeUsage};
+use autosave::{Autosaver, SavedGame};
+use book::BookLine;
+use bridge::*;
+use build::BuildOutcome;
+use capture::CaptureWriter;
+use clipboard::Clipboard;
+use concurrency::AutoConcurrency;
 use console::*;
+use embedded::EmbeddedPlayer;
+use engine_config::*;
+use error::Error;
+use events::*;
+use i18n::*;
+use match_file::{MatchFile, MatchMode};
 use nannou::prelude::*;
+use net::*;
+use notify::*;
+use observer::*;
 use othello_gui::*;
-use rand::seq::IteratorRandom;
+use plugin::PluginPlayer;
+use rand::{rngs::StdRng, seq::IteratorRandom, SeedableRng};
+use ratings::{Rating, RatingSnapshot};
+use remote::*;
+use report::{Report, ReportFormat, ScoreHistory};
+use results::*;
+use rules::Rules;
+use serve::*;
+use settings::*;
+use start::Start;
+use theme::{palette, rgba8, Palette, SERIES_COLORS};
+use wasm_player::WasmPlayer;
 #[rustfmt::skip]
 use std::{
-    collections::HashMap,
+    collections::{BTreeMap, HashMap},
     env,
-    path::PathBuf,
+    io::{self, Write},
+    path::{Path, PathBuf},
     process,
     slice::Iter,
     str::FromStr,
-    time::Duration,
+    sync::OnceLock,
+    thread,
+    time::{Duration, Instant},
 };
 
+mod clipboard;
+mod render;
+mod theme;
+
 const VERSION: &str = "0.12.0";
 
 fn main() {
-    nannou::app(model).event(event).update(update).run();
+    kill_children_on_panic();
+
+    let args: Vec<String> = env::args().collect();
+
+    // the language is needed before anything is printed, even the help
+    if let Some(lang) = args
+        .iter()
+        .position(|arg| arg.eq_ignore_ascii_case("--lang"))
+        .and_then(|idx| args.get(idx + 1))
+    {
+        match lang.parse() {
+            Ok(lang) => i18n::set_lang(lang),
+            Err(err) => exit_with_error(Error::InvalidValue(err)),
+        }
+    }
+
+    // these modes run without a window, e.g. over SSH
+    match args.get(1).map(|mode| mode.to_lowercase()).as_deref() {
+        Some("console-play") => {
+            let (mode, _) = read_mode(&args).unwrap_or_else(|err| exit_with_error(err));
+
+            let Mode::Visual(visual) = mode else {
+                unreachable!("console-play should be read as a visual game");
+            };
+
+            play_in_terminal(visual).unwrap_or_else(|err| exit_with_error(err));
+            ai::exit(0);
+        }
+        Some("eval") => {
+            run_eval_mode(&mut args[2..].iter()).unwrap_or_else(|err| exit_with_error(err));
+            ai::exit(0);
+        }
+        Some("build-book") => {
+            run_book_mode(&mut args[2..].iter()).unwrap_or_else(|err| exit_with_error(err));
+            process::exit(0);
+        }
+        Some("reproduce") => {
+            run_reproduce_mode(&mut args[2..].iter()).unwrap_or_else(|err| exit_with_error(err));
+            ai::exit(0);
+        }
+        Some("render") => {
+            run_render_mode(&mut args[2..].iter()).unwrap_or_else(|err| exit_with_error(err));
+            process::exit(0);
+        }
+        Some("viewer") => {
+            run_viewer_mode(&mut args[2..].iter()).unwrap_or_else(|err| exit_with_error(err));
+            process::exit(0);
+        }
+        Some("heatmap") => {
+            run_heatmap_mode(&mut args[2..].iter()).unwrap_or_else(|err| exit_with_error(err));
+            process::exit(0);
+        }
+        Some("ratings") => {
+            run_ratings_mode(&mut args[2..].iter()).unwrap_or_else(|err| exit_with_error(err));
+            process::exit(0);
+        }
+        _ => {}
+    }
+
+    nannou::app(model)
+        .event(event)
+        .update(update)
+        .exit(exit)
+        .run();
 }
 
 // DATA
@@ -25,82 +124,620 @@ fn main() {
 #[delegatable_trait]
 pub trait Showable {
     fn showed_game(&self) -> &Game;
+
+    /// How long the AI to move in the showed game has been thinking, and its limit, if it is
+    /// shown.
+    fn thinking(&self) -> Option<(Duration, Option<Limit>)>;
 }
 
 #[derive(Debug)]
 struct Model {
     window_id: window::Id,
     mode: Mode,
+    // the title last set, which shows the disk counts of the shown game
+    title: String,
+    window_options: WindowOptions,
+    stats_window: Option<window::Id>,
+    settings: Settings,
+    menu_open: bool,
+    clipboard: Clipboard,
+    touches: Touches,
+    // when Q was pressed while games were unfinished, quitting needs a second press soon after
+    quit_requested: Option<Instant>,
+}
+
+/// The fingers on the screen, to tell taps, long presses and two-finger taps apart.
+#[derive(Debug, Default)]
+struct Touches {
+    // where and when every finger touched down
+    down: HashMap<u64, (nannou::prelude::Vec2, Instant)>,
+    // the most fingers down at once since the first one touched down
+    most: usize,
+    // a finger moved too far for a tap
+    moved: bool,
+    // when the last finger was lifted
+    last_touch: Option<Instant>,
+}
+
+impl Touches {
+    // a single finger held down without moving
+    fn is_long_press(&self) -> bool {
+        self.most == 1
+            && !self.moved
+            && self.down.values().any(|(_, at)| at.elapsed() >= LONG_PRESS)
+    }
+}
+
+/// Where the parts of the GUI are drawn, for the current size of the window.
+struct Layout {
+    board: Rect,
+    // indexed by x and y, with y = 0 at the top
+    tiles: [[Rect; 8]; 8],
+    // the width of the space around the board, where the coordinates and the HUD are
+    margin: f32,
 }
 
 impl Model {
-    fn get_rects(window: &Window) -> [[Rect; 8]; 8] {
-        const SIZE_MULTIPLIER: (f32, f32) = (0.95, 0.95);
+    fn palette(&self) -> Palette {
+        if self.window_options.accessible {
+            palette(Theme::Accessible)
+        } else {
+            palette(self.settings.theme)
+        }
+    }
+}
 
-        let scale = f32::min(
-            window.inner_size_points().0 / SIZE_MULTIPLIER.0,
-            window.inner_size_points().1 / SIZE_MULTIPLIER.1,
-        );
+impl Layout {
+    fn new(window: &Window, present: bool) -> Self {
+        let (width, height) = window.inner_size_points();
+
+        // the board is kept square, in the middle of wide or tall windows
+        let (size, board, margin) = if present {
+            // below the names and the score, with no room for the coordinates
+            let band = height * SCORE_BAND_FRACTION;
+            let size = f32::min(width, height - band) * WINDOW_FRACTION;
 
-        let size = (scale * SIZE_MULTIPLIER.0, scale * SIZE_MULTIPLIER.1);
+            (size, Rect::from_x_y_w_h(0.0, -band / 2.0, size, size), 0.0)
+        } else {
+            let side = f32::min(width, height) * WINDOW_FRACTION;
+            let size = side * BOARD_FRACTION;
+            let margin = side * (1.0 - BOARD_FRACTION) / 2.0;
 
-        let used = Rect::from_w_h(size.0, size.1);
+            (size, Rect::from_w_h(size, size), margin)
+        };
 
-        let mut rects = [[Rect::from_w_h(0.0, 0.0); 8]; 8];
+        let mut tiles = [[Rect::from_w_h(0.0, 0.0); 8]; 8];
 
         #[allow(clippy::needless_range_loop)]
         for x in 0..8 {
             for y in 0..8 {
-                rects[x][7 - y] = Rect::from_wh(used.wh() / 8.0)
-                    .bottom_left_of(used)
-                    .shift_x(size.0 / 8.0 * x as f32)
-                    .shift_y(size.1 / 8.0 * y as f32);
+                tiles[x][7 - y] = Rect::from_wh(board.wh() / 8.0)
+                    .bottom_left_of(board)
+                    .shift_x(size / 8.0 * x as f32)
+                    .shift_y(size / 8.0 * y as f32);
             }
         }
 
-        rects
+        Self {
+            board,
+            tiles,
+            margin,
+        }
+    }
+
+    // the label of column `x` above the board
+    fn column_label(&self, x: usize) -> Rect {
+        let tile = self.tiles[x][0];
+
+        Rect::from_x_y_w_h(
+            tile.x(),
+            self.board.top() + self.margin / 2.0,
+            tile.w(),
+            self.margin,
+        )
+    }
+
+    // the label of row `y` left of the board
+    fn row_label(&self, y: usize) -> Rect {
+        let tile = self.tiles[0][y];
+
+        Rect::from_x_y_w_h(
+            self.board.left() - self.margin / 2.0,
+            tile.y(),
+            self.margin,
+            tile.h(),
+        )
+    }
+
+    // the top of the window in presentation mode, above the board
+    fn score_band(window: &Window) -> Rect {
+        let area = window.rect();
+        let band = area.h() * SCORE_BAND_FRACTION;
+
+        Rect::from_x_y_w_h(0.0, area.top() - band / 2.0, area.w(), band)
+    }
+
+    // below the board
+    fn hud(&self) -> Rect {
+        Rect::from_x_y_w_h(
+            self.board.x(),
+            self.board.bottom() - self.margin / 2.0,
+            self.board.w(),
+            self.margin,
+        )
     }
 }
 
+/// Settings of the window, which are given as options, but aren't part of the mode.
+#[derive(Debug, Clone, Copy, Default)]
+struct WindowOptions {
+    // keep the window square when it is resized
+    square: bool,
+    // open a second window with the standings of the arena
+    stats: bool,
+    // use the accessible theme, whichever is chosen in the settings
+    accessible: bool,
+    // fullscreen, with the names of the players and a big score instead of the coordinates and
+    // the HUD, and only the results printed
+    present: bool,
+}
+
+// there is only ever one Mode, so the size difference doesn't matter
+#[allow(clippy::large_enum_variant)]
 #[derive(Debug, Delegate)]
 #[delegate(Showable)]
 enum Mode {
     Visual(Visual),
     AIArena(AIArena),
+    Spectate(Spectate),
+}
+
+impl Mode {
+    fn console(&self) -> &Console {
+        match self {
+            Mode::Visual(visual) => &visual.console,
+            Mode::AIArena(arena) => &arena.console,
+            Mode::Spectate(spectate) => &spectate.console,
+        }
+    }
+
+    // the names of the players of the showed game, as they are shown to an audience
+    fn player_names(&self) -> [String; 2] {
+        match self {
+            Mode::Spectate(spectate) => spectate.player_names(),
+            mode => {
+                let players = &mode.showed_game().players;
+
+                [presented_name(&players[0]), presented_name(&players[1])]
+            }
+        }
+    }
 }
 
 #[derive(Debug)]
 struct Visual {
     game: Game,
     console: Console,
+    observers: Vec<Box<dyn GameObserver>>,
+    // how long a human move can be taken back by clicking its square again
+    undo_grace: Option<Duration>,
+    // the last human move and when it was played, while it can be taken back
+    grace_move: Option<(othello_gui::Vec2, Instant)>,
+    // the least time between showing a move and the next AI move, so that AI games can be followed
+    min_move_delay: Option<Duration>,
+    // the length of the history, and when it last changed
+    shown_moves: (usize, Instant),
+    // an unfinished game of an earlier run, which can be continued by pressing R
+    restorable: Option<SavedGame>,
 }
 
 impl Showable for Visual {
     fn showed_game(&self) ->  &Game {
         &self.game
     }
+
+    fn thinking(&self) -> Option<(Duration, Option<Limit>)> {
+        self.game.thinking()
+    }
 }
 
 #[derive(Debug)]
 struct AIArena {
     games: Vec<Game>,
     showed_game_idx: usize,
-    first_unstarted: usize,
     max_concurrency: usize,
+    // with `<max concurrency>` auto, the number of games running at once is adjusted up to
+    // `max_concurrency`
+    auto_concurrency: Option<AutoConcurrency>,
+    // no AI plays more than one game at once
+    exclusive_engines: bool,
+    // the AIs of every running game are pinned to a different one of these
+    pinned_cores: Option<Vec<usize>>,
+    // no games are started while the load average is higher
+    max_load: Option<f32>,
+    // whether games aren't started because of `max_load`
+    starts_paused: bool,
+    finish_hooks: FinishHooks,
+    // in compare mode, the running result is printed every this many finished pairs of games
+    report_every: Option<usize>,
+    reported_pairs: usize,
+    // in tournaments, the number of rounds whose standings were printed
+    reported_rounds: usize,
+    // the number of finished games when the rounds were last checked
+    rounds_checked_at: usize,
     console: Console,
+    observers: Vec<Box<dyn GameObserver>>,
     submode: Submode,
+    // AIs of the AI list identical to an earlier one, with the path of that one
+    identical_engines: Vec<(EngineConfig, PathBuf)>,
+    adjudication: Option<Adjudication>,
+    // how often the games are updated, if not every frame
+    poll_interval: Option<Duration>,
+    // `ai::finished_runs()` when the games were last updated
+    seen_runs: usize,
+    started_at: Instant,
+    // no new games are started after this much time
+    max_duration: Option<Duration>,
+    // the number of games planned, if some were dropped because of `max_duration`
+    truncated_from: Option<usize>,
+    // switch to a running game when the shown one ends
+    auto_advance: bool,
+    // how long the result of the shown game is shown before switching to a running one
+    hold_result: Option<Duration>,
+    // the index of the shown game and when it was first shown finished
+    showed_ended: Option<(usize, Instant)>,
+    // the games which are moved on to and chosen from with the arrow keys
+    view_filter: ViewFilter,
+    // the engine the moves of finished games are measured against
+    reference: Option<AI>,
+    groups: Option<Groups>,
+    // reported next to the win/draw/loss scores
+    disk_scoring: Option<Scoring>,
+    // ids of the finished games, in the order they ended
+    ended: Vec<usize>,
+    score_history: ScoreHistory,
+    // where the report is written when all games are done
+    report_path: Option<PathBuf>,
+    report_format: ReportFormat,
+    // where the Elo ratings are appended to at the end of tournaments
+    ratings_path: Option<PathBuf>,
+    // in challenge mode, the AI the second one challenges, which is the first one
+    challenge: Option<Challenge>,
+}
+
+/// A compare of a new AI against the top-rated one of a ratings file, after which the new AI is
+/// added to the file.
+#[derive(Debug)]
+struct Challenge {
+    ratings_path: PathBuf,
+    champion: Rating,
+}
+
+/// Which games of an arena are watched, chosen with --watch-engine or by pressing F.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum ViewFilter {
+    All,
+    // games one of whose players has this name, with or without the directories
+    Engine(String),
+    // games lost by the failure of an AI
+    Failures,
+}
+
+impl ViewFilter {
+    fn matches(&self, game: &Game) -> bool {
+        match self {
+            ViewFilter::All => true,
+            ViewFilter::Engine(name) => game
+                .players
+                .iter()
+                .any(|player| player.name() == *name || presented_name(player) == *name),
+            ViewFilter::Failures => game.failure.is_some(),
+        }
+    }
+
+    fn description(&self) -> String {
+        match self {
+            ViewFilter::All => "all games".to_owned(),
+            ViewFilter::Engine(name) => format!("the games of {name}"),
+            ViewFilter::Failures => "the games lost by a failure".to_owned(),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Adjudication {
+    solver: Box<dyn EndgameSolver>,
+    // games are solved once at most this many squares are empty
+    max_empties: usize,
+    // ids of the games the solver failed on, which are played to the end instead
+    unsolved: Vec<usize>,
+    // the games being solved, by id, with the number of moves played when the solver started
+    solving: BTreeMap<usize, (usize, SolveHandle)>,
+}
+
+/// The group stage of a tournament, after which the best of every group play a final.
+#[derive(Debug)]
+struct Groups {
+    // the AIs in every group, in the order of <ai list>
+    members: Vec<Vec<PlayerId>>,
+    // how many AIs of every group play in the final
+    advance: usize,
+    schedule: Schedule,
+    // the games of the group stage, once the final started
+    group_games: Option<Vec<Game>>,
 }
 
 impl Showable for AIArena {
     fn showed_game(&self) ->  &Game {
         &self.games[self.showed_game_idx]
     }
+
+    // games change too often in an arena for it to be useful
+    fn thinking(&self) -> Option<(Duration, Option<Limit>)> {
+        None
+    }
+}
+
+impl AIArena {
+    // an arena playing `games`, with every option at its default, which the modes override
+    fn new(
+        games: Vec<Game>,
+        submode: Submode,
+        max_concurrency: usize,
+        auto_concurrency: Option<AutoConcurrency>,
+    ) -> Self {
+        Self {
+            games,
+            showed_game_idx: 0,
+            max_concurrency,
+            auto_concurrency,
+            exclusive_engines: false,
+            pinned_cores: None,
+            max_load: None,
+            starts_paused: false,
+            finish_hooks: FinishHooks::default(),
+            report_every: None,
+            reported_pairs: 0,
+            reported_rounds: 0,
+            rounds_checked_at: 0,
+            console: Console::new(Level::Info),
+            observers: Vec::new(),
+            submode,
+            identical_engines: Vec::new(),
+            adjudication: None,
+            poll_interval: None,
+            seen_runs: 0,
+            started_at: Instant::now(),
+            max_duration: None,
+            truncated_from: None,
+            auto_advance: true,
+            hold_result: None,
+            showed_ended: None,
+            view_filter: ViewFilter::All,
+            reference: None,
+            groups: None,
+            disk_scoring: None,
+            ended: Vec::new(),
+            score_history: ScoreHistory::default(),
+            report_path: None,
+            report_format: ReportFormat::Json,
+            ratings_path: None,
+            challenge: None,
+        }
+    }
+
+    // the game to move on to: the last started one still going, or with failures watched, the last
+    // one which ended with a failure, as games only fail once they end
+    fn next_watched_game(&self) -> Option<usize> {
+        match self.view_filter {
+            ViewFilter::Failures => self.ended.iter().rev().find_map(|&id| {
+                self.games
+                    .iter()
+                    .position(|game| game.id == id && self.view_filter.matches(game))
+            }),
+            _ => self.games.iter().rposition(|game| {
+                game.started && !game.is_game_over() && self.view_filter.matches(game)
+            }),
+        }
+    }
+
+    // shows the started game `offset` games after the shown one, of those the filter matches
+    fn step(&mut self, offset: isize) {
+        let watched: Vec<usize> = (0..self.games.len())
+            .filter(|&idx| self.games[idx].started && self.view_filter.matches(&self.games[idx]))
+            .collect();
+
+        if watched.is_empty() {
+            return;
+        }
+
+        let at = watched.partition_point(|&idx| idx < self.showed_game_idx) as isize;
+
+        // from a game the filter doesn't match, the next one is the one right after it
+        let target = if offset > 0 && watched.binary_search(&self.showed_game_idx).is_err() {
+            at + offset - 1
+        } else {
+            at + offset
+        };
+
+        self.showed_game_idx = watched[target.clamp(0, watched.len() as isize - 1) as usize];
+    }
+
+    // all games, then those with failures, then those of every AI in turn
+    fn next_view_filter(&self) -> ViewFilter {
+        let mut engines: Vec<String> = Vec::new();
+
+        for player in self.games.iter().flat_map(|game| &game.players) {
+            let name = presented_name(player);
+
+            if !engines.contains(&name) {
+                engines.push(name);
+            }
+        }
+
+        let engine_after = |skipped: usize| {
+            engines
+                .get(skipped)
+                .map_or(ViewFilter::All, |name| ViewFilter::Engine(name.clone()))
+        };
+
+        match &self.view_filter {
+            ViewFilter::All => ViewFilter::Failures,
+            ViewFilter::Failures => engine_after(0),
+            ViewFilter::Engine(name) => engine_after(
+                engines
+                    .iter()
+                    .position(|other| other == name)
+                    .map_or(0, |idx| idx + 1),
+            ),
+        }
+    }
+}
+
+/// The games of an arena running elsewhere, e.g. headless on a server, watched through its
+/// spectator server.
+#[derive(Debug)]
+struct Spectate {
+    client: SpectatorClient,
+    // every game received, including the finished ones
+    games: BTreeMap<usize, SpectatedGame>,
+    // the id of the showed game
+    showed: Option<usize>,
+    // move on to the next game with any activity once the showed one ended, like the arena
+    auto_advance: bool,
+    console: Console,
+    // showed until the first game is received
+    waiting: Game,
+}
+
+#[derive(Debug)]
+struct SpectatedGame {
+    // the players are only known by their names, which are kept separately
+    game: Game,
+    names: [String; 2],
+}
+
+impl Showable for Spectate {
+    fn showed_game(&self) -> &Game {
+        self.showed
+            .and_then(|id| self.games.get(&id))
+            .map_or(&self.waiting, |spectated| &spectated.game)
+    }
+
+    // the AIs are run by the arena, and snapshots only come with moves
+    fn thinking(&self) -> Option<(Duration, Option<Limit>)> {
+        None
+    }
+}
+
+impl Spectate {
+    fn player_names(&self) -> [String; 2] {
+        self.showed
+            .and_then(|id| self.games.get(&id))
+            .map_or([String::new(), String::new()], |spectated| {
+                spectated.names.clone()
+            })
+    }
+
+    // which game is showed, and its players
+    fn hud_prefix(&self) -> String {
+        let Some(id) = self.showed else {
+            return tr(Text::WaitingForGames).to_owned();
+        };
+
+        let idx = self
+            .games
+            .keys()
+            .position(|&other| other == id)
+            .unwrap_or(0);
+        let [x_name, o_name] = self.player_names();
+
+        format!(
+            "#{id} ({}/{})   {x_name} vs {o_name}",
+            idx + 1,
+            self.games.len()
+        )
+    }
+
+    // shows the game `offset` games after the showed one, in the order of ids
+    fn step(&mut self, offset: isize) {
+        let ids: Vec<usize> = self.games.keys().copied().collect();
+
+        let Some(idx) = self
+            .showed
+            .and_then(|id| ids.iter().position(|&other| other == id))
+        else {
+            return;
+        };
+
+        let idx = (idx as isize + offset).clamp(0, ids.len() as isize - 1);
+        self.showed = Some(ids[idx as usize]);
+    }
+}
+
+/// The order in which the games of an arena are started.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+enum Schedule {
+    // all games of a pairing after each other
+    InOrder,
+    // a game of every pairing in turn, so that results stopped early are balanced
+    Interleaved,
+}
+
+impl FromStr for Schedule {
+    type Err = String;
+
+    fn from_str(str: &str) -> Result<Self, Self::Err> {
+        match str.to_lowercase().as_str() {
+            "in-order" => Ok(Schedule::InOrder),
+            "interleaved" => Ok(Schedule::Interleaved),
+            other => Err(format!("Unknown schedule '{other}'")),
+        }
+    }
+}
+
+impl Schedule {
+    fn arrange(self, games: &mut Vec<Game>) {
+        if self == Schedule::InOrder {
+            return;
+        }
+
+        // the games of every pairing, in the order the pairings first appear
+        let mut pairings: Vec<(Vec<String>, Vec<Game>)> = Vec::new();
+
+        for game in games.drain(..) {
+            let mut names: Vec<String> = game.players.iter().map(Player::name).collect();
+            names.sort();
+
+            match pairings.iter_mut().find(|(pairing, _)| *pairing == names) {
+                Some((_, pairing_games)) => pairing_games.push(game),
+                None => pairings.push((names, vec![game])),
+            }
+        }
+
+        let mut pairings: Vec<_> = pairings
+            .into_iter()
+            .map(|(_, pairing_games)| pairing_games.into_iter())
+            .collect();
+
+        loop {
+            let round: Vec<Game> = pairings.iter_mut().filter_map(Iterator::next).collect();
+
+            if round.is_empty() {
+                break;
+            }
+
+            games.extend(round);
+        }
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 enum Submode {
     Compare,
     Tournament,
+    // failed games of an earlier run, played again
+    Rerun,
 }
 
 // INITALIZATION
@@ -112,19 +749,124 @@ fn model(app: &App) -> Model {
         .new_window()
         .view(view)
         .title(format!("Othello GUI - v{VERSION}"))
+        .min_size(MIN_WINDOW_SIZE, MIN_WINDOW_SIZE)
         .build()
         .unwrap();
 
+    // Esc opens the settings menu
+    app.set_exit_on_escape(false);
+
     let args: Vec<String> = env::args().collect();
 
+    let (mut mode, window_options) = read_mode(&args).unwrap_or_else(|err| exit_with_error(err));
+
+    if let Mode::Visual(visual) = &mut mode {
+        offer_restore(visual);
+        enable_autosave(visual, &args);
+    }
+
+    if window_options.present {
+        app.window(window_id)
+            .expect("Error finding window.")
+            .set_fullscreen(true);
+    }
+
+    // the GUI works without them, so they don't stop it from starting
+    let settings = Settings::load().unwrap_or_else(|err| {
+        mode.console().warn(
+            Category::Errors,
+            &format!("Using the default settings: {err}"),
+        );
+        Settings::default()
+    });
+
+    let stats_window = window_options.stats.then(|| {
+        app.new_window()
+            .view(view_stats)
+            .title(format!("Othello GUI - v{VERSION} - standings"))
+            .size(STATS_WINDOW_SIZE.0, STATS_WINDOW_SIZE.1)
+            .min_size(MIN_WINDOW_SIZE, MIN_WINDOW_SIZE)
+            .build()
+            .unwrap()
+    });
+
+    // Instead of every frame, the arena is updated as soon as an AI finished, and otherwise
+    // every interval, for timers like --max-duration. AI time limits are enforced in the async
+    // runtime regardless.
+    if let Mode::AIArena(AIArena {
+        poll_interval: Some(interval),
+        ..
+    }) = &mode
+    {
+        app.set_loop_mode(LoopMode::Wait);
+
+        let proxy = app.create_proxy();
+        ai::on_run_finished(move || {
+            let _ = proxy.wakeup();
+        });
+
+        let proxy = app.create_proxy();
+        let interval = *interval;
+        thread::spawn(move || {
+            // the event loop is closed once the app quits
+            while proxy.wakeup().is_ok() {
+                thread::sleep(interval);
+            }
+        });
+    }
+
+    Model {
+        window_id,
+        mode,
+        title: String::new(),
+        window_options,
+        stats_window,
+        settings,
+        menu_open: false,
+        clipboard: Clipboard::default(),
+        touches: Touches::default(),
+        quit_requested: None,
+    }
+}
+
+fn read_mode(args: &[String]) -> Result<(Mode, WindowOptions), Error> {
     let mut arg_iter = args.iter();
     let program_name = arg_iter.next().unwrap(); // program name
 
-    let mode = arg_iter.next().unwrap_or_else(|| {
-        println!("expected arguments");
-        print_help(program_name);
-        process::exit(5);
-    });
+    let mode = arg_iter.next().ok_or(Error::NoArguments)?;
+    let console_play = mode.eq_ignore_ascii_case("console-play");
+
+    // the openings of compare mode are chosen before the other options are read
+    let deterministic_seed = args
+        .iter()
+        .position(|arg| arg == "--deterministic")
+        .map(|idx| match args.get(idx + 1) {
+            Some(seed) => handled_parse::<u64>(seed, "<seed>"),
+            None => Err(Error::MissingArgument("<seed>".to_owned())),
+        })
+        .transpose()?;
+
+    // and the start setup they are played from
+    let start = args
+        .iter()
+        .position(|arg| arg == "--start")
+        .map(|idx| match args.get(idx + 1) {
+            Some(start) => start.parse::<Start>().map_err(Error::InvalidValue),
+            None => Err(Error::MissingArgument("<start>".to_owned())),
+        })
+        .transpose()?;
+
+    // and the book their openings are taken from
+    let book = args
+        .iter()
+        .position(|arg| arg == "--book")
+        .map(|idx| match args.get(idx + 1) {
+            Some(path) => book::load(Path::new(path)),
+            None => Err(Error::MissingArgument("<book file>".to_owned())),
+        })
+        .transpose()?;
+
+    let mut match_options = Vec::new();
 
     let mut mode = match mode.to_lowercase().as_str() {
         "h" | "help" => {
@@ -135,112 +877,997 @@ fn model(app: &App) -> Model {
             print_version_info();
             process::exit(0);
         }
-        "v" | "visual" => {
-            let game = Game::new(0, [read_player(&mut arg_iter), read_player(&mut arg_iter)]);
+        "v" | "visual" | "console-play" => {
+            let game = Game::new(
+                0,
+                [read_player(&mut arg_iter)?, read_player(&mut arg_iter)?],
+            );
 
             Mode::Visual(Visual {
                 game,
                 console: Console::new(Level::Info),
+                observers: Vec::new(),
+                undo_grace: None,
+                grace_move: None,
+                min_move_delay: None,
+                shown_moves: (1, Instant::now()),
+                restorable: None,
             })
         }
-        "c" | "compare" => handle_compare_mode(&mut arg_iter),
-        "t" | "tournament" => handle_tournament_mode(&mut arg_iter),
-        other => {
-            eprintln!("Unknown mode '{other}'");
-            print_help(program_name);
-            process::exit(6);
+        "host" => {
+            let port = read_int(&mut arg_iter, "<port>")?;
+            let opponent = Player::Network(NetPlayer::host(port)?);
+
+            Mode::Visual(Visual {
+                game: Game::new(0, [Player::Human, opponent]),
+                console: Console::new(Level::Info),
+                observers: Vec::new(),
+                undo_grace: None,
+                grace_move: None,
+                min_move_delay: None,
+                shown_moves: (1, Instant::now()),
+                restorable: None,
+            })
+        }
+        "join" => {
+            let address = read_string(&mut arg_iter, "<address>")?;
+            let opponent = Player::Network(NetPlayer::join(address));
+
+            Mode::Visual(Visual {
+                game: Game::new(0, [opponent, Player::Human]),
+                console: Console::new(Level::Info),
+                observers: Vec::new(),
+                undo_grace: None,
+                grace_move: None,
+                min_move_delay: None,
+                shown_moves: (1, Instant::now()),
+                restorable: None,
+            })
         }
+        "spectate" => return handle_spectate_mode(&mut arg_iter),
+        "c" | "compare" => {
+            handle_compare_mode(&mut arg_iter, deterministic_seed, start, book.as_deref())?
+        }
+        "t" | "tournament" => handle_tournament_mode(&mut arg_iter)?,
+        "rerun-failures" => handle_rerun_mode(&mut arg_iter)?,
+        "challenge" => handle_challenge_mode(&mut arg_iter, deterministic_seed)?,
+        "run" => {
+            let path = read_string(&mut arg_iter, "<match file>")?;
+            let match_file = MatchFile::read(Path::new(&path))?;
+
+            match_options = match_file.options();
+            handle_match_mode(&match_file, deterministic_seed, start, book.as_deref())?
+        }
+        other => return Err(Error::UnknownMode(other.to_owned())),
     };
 
+    // the options of a match file come first, so that those on the command line override them
+    let options: Vec<String> = match_options.into_iter().chain(arg_iter.cloned()).collect();
+    let mut arg_iter = options.iter();
+
     let mut level = Level::Info;
+    let mut category_levels = Vec::new();
+    let mut color = ColorMode::Auto;
+    let mut finish_hooks = FinishHooks::default();
+    let mut report_every = None;
+    let mut skip_preflight = false;
+    let mut dedupe_engines = false;
+    let mut exclusive_engines = false;
+    let mut pin_cores = false;
+    let mut max_load = None;
+    let mut serve_port = None;
+    let mut results_path = None;
+    let mut capture_path = None;
+    let mut warm_up = false;
+    let mut report_path: Option<PathBuf> = None;
+    let mut report_format = None;
+    let mut ratings_path: Option<PathBuf> = None;
+    let mut json_events = false;
+    let mut event_log = None;
+    let mut print_boards = false;
+    let mut window_options = WindowOptions::default();
+    let mut adjudicate = None;
+    let mut solver = None;
+    let mut poll_interval = None;
+    let mut max_duration = None;
+    let mut schedule = Schedule::InOrder;
+    let mut reference = None;
+    let mut groups = None;
+    let mut position = None;
+    let mut rules = None;
+    let mut undo_grace = None;
+    let mut min_move_delay = None;
+    let mut hold_result = None;
+    let mut watch_engine = None;
+    let mut disk_scoring = None;
+    let mut advance = None;
 
     while let Some(option) = arg_iter.next() {
         match option.to_lowercase().as_str() {
+            "--skip-preflight" => skip_preflight = true,
+            "--dedupe-engines" => dedupe_engines = true,
+            "--exclusive-engines" => exclusive_engines = true,
+            "--pin-cores" => pin_cores = true,
+            "--max-load" => {
+                let load = read_string(&mut arg_iter, "<load>")?;
+                max_load = Some(load.parse::<f32>().map_err(|_| {
+                    Error::InvalidValue(format!("<load> must be a number, but it is '{load}'"))
+                })?);
+            }
+            "--schedule" => {
+                schedule = read_string(&mut arg_iter, "<schedule>")?
+                    .parse()
+                    .map_err(Error::InvalidValue)?;
+            }
+            "--disk-scoring" => {
+                disk_scoring = Some(
+                    read_string(&mut arg_iter, "<scoring>")?
+                        .parse()
+                        .map_err(Error::InvalidValue)?,
+                );
+            }
+            "--max-duration" => {
+                let minutes: u64 = read_int(&mut arg_iter, "<minutes>")?;
+
+                if minutes == 0 {
+                    return Err(Error::NotPositive("<minutes>".to_owned()));
+                }
+
+                max_duration = Some(Duration::from_secs(minutes * 60));
+            }
+            "--poll-interval" => {
+                let interval = read_int(&mut arg_iter, "<interval>")?;
+
+                if interval == 0 {
+                    return Err(Error::NotPositive("<interval>".to_owned()));
+                }
+
+                poll_interval = Some(Duration::from_millis(interval));
+            }
+            "--serve" => serve_port = Some(read_int(&mut arg_iter, "<port>")?),
+            "--json-events" => json_events = true,
+            "--event-log" => event_log = Some(PathBuf::from(read_string(&mut arg_iter, "<file>")?)),
+            "--print-boards" => print_boards = true,
+            "--square-window" => window_options.square = true,
+            "--accessible" => window_options.accessible = true,
+            "--present" => window_options.present = true,
+            // already set in `main`
+            "--lang" => {
+                read_string(&mut arg_iter, "<language>")?;
+            }
+            "--stats-window" => window_options.stats = true,
+            "--adjudicate" => adjudicate = Some(read_int(&mut arg_iter, "<empties>")?),
+            "--solver" => {
+                let config = read_engine_config(&read_string(&mut arg_iter, "<engine>")?)?;
+                run::validate(&config)?;
+                solver = Some(config);
+            }
+            "--analyze" => {
+                let Player::AI(ai) = read_ai_player(&mut arg_iter)? else {
+                    return Err(Error::InvalidValue(
+                        "Only local AIs can be used with --analyze".to_owned(),
+                    ));
+                };
+                reference = Some(ai);
+            }
+            "--undo-grace" => {
+                undo_grace = Some(Duration::from_millis(read_int(&mut arg_iter, "<ms>")?));
+            }
+            "--watch-engine" => watch_engine = Some(read_string(&mut arg_iter, "<name>")?),
+            "--hold-result" => {
+                hold_result = Some(Duration::from_millis(read_int(&mut arg_iter, "<ms>")?));
+            }
+            "--min-move-delay" => {
+                min_move_delay = Some(Duration::from_millis(read_int(&mut arg_iter, "<ms>")?));
+            }
+            "--position" => position = Some(read_string(&mut arg_iter, "<file>")?),
+            "--rules" => {
+                rules = Some(
+                    read_string(&mut arg_iter, "<rules>")?
+                        .parse::<Rules>()
+                        .map_err(Error::InvalidValue)?,
+                );
+            }
+            "--groups" => groups = Some(read_int(&mut arg_iter, "<groups>")?),
+            "--advance" => advance = Some(read_int(&mut arg_iter, "<advance>")?),
+            "--results" => results_path = Some(read_string(&mut arg_iter, "<file>")?),
+            "--report" => report_path = Some(read_string(&mut arg_iter, "<file>")?.into()),
+            "--report-format" => {
+                report_format = Some(
+                    read_string(&mut arg_iter, "<format>")?
+                        .parse()
+                        .map_err(Error::InvalidValue)?,
+                );
+            }
+            "--ratings" => ratings_path = Some(read_string(&mut arg_iter, "<file>")?.into()),
+            // already read before the mode
+            "--deterministic" => {
+                read_string(&mut arg_iter, "<seed>")?;
+            }
+            "--start" => {
+                read_string(&mut arg_iter, "<start>")?;
+            }
+            "--book" => {
+                read_string(&mut arg_iter, "<book file>")?;
+            }
+            "--capture" => capture_path = Some(read_string(&mut arg_iter, "<file>")?),
+            "--warm-up" => warm_up = true,
+            "--verify-moves" => verify::enable(),
             "-l" | "--level" => {
-                level = match read_string(&mut arg_iter, "<level>")
-                    .to_lowercase()
-                    .as_str()
-                {
-                    "i" | "info" => Level::Info,
-                    "w" | "warn" | "warning" => Level::Warning,
-                    "n" | "necessary" => Level::Necessary,
-                    other => {
-                        eprintln!("Unknown <level> '{other}'");
-                        process::exit(19);
-                    }
+                level = read_string(&mut arg_iter, "<level>")?
+                    .parse()
+                    .map_err(Error::InvalidValue)?;
+            }
+            "--color" => {
+                color = read_string(&mut arg_iter, "<when>")?
+                    .parse()
+                    .map_err(Error::InvalidValue)?;
+            }
+            "--notify" => finish_hooks.notify = true,
+            "--on-finish" => {
+                finish_hooks.on_finish = Some(read_string(&mut arg_iter, "<command>")?);
+            }
+            "--report-every" => {
+                let pairs = read_int(&mut arg_iter, "<pairs>")?;
+
+                if pairs == 0 {
+                    return Err(Error::NotPositive("<pairs>".to_owned()));
                 }
+
+                report_every = Some(pairs);
             }
-            other => {
-                eprintln!("Unrecognised option '{other}'");
-                print_help(program_name);
-                process::exit(18);
+            "--log" => {
+                let filters = read_string(&mut arg_iter, "<log filters>")?;
+                category_levels.extend(read_log_filters(&filters)?);
             }
+            other => return Err(Error::UnrecognisedOption(other.to_owned())),
         }
     }
 
-    match &mut mode {
-        Mode::Visual(visual) => visual.console.level = level,
-        Mode::AIArena(arena) => arena.console.level = level,
-    }
+    if json_events {
+        let (console, observers) = match &mut mode {
+            Mode::Visual(visual) => (&mut visual.console, &mut visual.observers),
+            Mode::AIArena(arena) => (&mut arena.console, &mut arena.observers),
+            Mode::Spectate(_) => unreachable!("spectate mode has its own options"),
+        };
 
-    Model {
-        window_id,
-        mode,
+        console.use_stderr = true;
+        observers.push(Box::new(JsonEvents::stdout()));
     }
-}
 
-fn print_help(program_name: &str) {
-    print_version_info();
+    if let Some(path) = event_log {
+        let Mode::AIArena(arena) = &mut mode else {
+            return Err(Error::InvalidValue(
+                "--event-log can only be used in compare and tournament mode".to_owned(),
+            ));
+        };
 
-    println!("COMMAND LINE ARGUMENTS:");
-    println!();
-    println!("{program_name} <mode> <mode arguments>");
-    println!();
+        let log = JsonEvents::log_file(&path).map_err(|source| Error::Write {
+            what: format!("event log '{}'", path.display()),
+            source,
+        })?;
 
-    // type annotation provided for rust-analyzer
-    let detailed: &str = textwrap_macros::dedent!(
-        r#"
-        MODES:
+        arena.observers.push(Box::new(log));
+    }
 
-        [h]elp: Print this.
+    let console = match &mut mode {
+        Mode::Visual(visual) => &mut visual.console,
+        Mode::AIArena(arena) => &mut arena.console,
+        Mode::Spectate(spectate) => &mut spectate.console,
+    };
 
-        [ver]sion: Print version info.
+    console.set_level(level);
+    console.set_color(color);
+    console.print_boards = print_boards;
 
-        [v]isual <player 1> <player 2>: Play a game between two players.
+    // the window is watched instead, but the results are still kept; --log can override this
+    if window_options.present {
+        for category in [Category::EngineIo, Category::GameFlow, Category::Errors] {
+            console.set_category_level(category, Level::Off);
+        }
+    }
+
+    for (category, level) in category_levels {
+        console.set_category_level(category, level);
+    }
+
+    if let Mode::AIArena(arena) = &mut mode {
+        arena.exclusive_engines = exclusive_engines;
+        arena.max_load = max_load;
+        arena.finish_hooks = finish_hooks;
+        arena.report_every = report_every;
+        arena.poll_interval = poll_interval;
+        arena.hold_result = hold_result.take();
+
+        if let Some(name) = watch_engine.take() {
+            let filter = ViewFilter::Engine(name.clone());
+
+            if !arena.games.iter().any(|game| filter.matches(game)) {
+                return Err(Error::InvalidValue(format!(
+                    "--watch-engine {name} matches none of the AIs"
+                )));
+            }
+
+            arena.view_filter = filter;
+        }
+        arena.max_duration = max_duration;
+        arena.disk_scoring = disk_scoring;
+        arena.report_path = report_path.take();
+        arena.report_format = report_format.unwrap_or_default();
+
+        if arena.submode == Submode::Tournament {
+            arena.ratings_path = ratings_path.take();
+
+            if let Some(count) = groups.take() {
+                split_into_groups(arena, count, advance.take().unwrap_or(1), schedule)?;
+            }
+        }
+
+        schedule.arrange(&mut arena.games);
+
+        if arena.submode != Submode::Rerun {
+            arena.reference = reference.take();
+        }
+
+        if pin_cores {
+            arena.pinned_cores = Some(pinned_cores(&arena.console, arena.max_concurrency));
+        }
+
+        if let Some(max_empties) = adjudicate {
+            let solver: Box<dyn EndgameSolver> = match solver.take() {
+                Some(config) => Box::new(ExternalSolver { config }),
+                None => Box::new(BuiltinSolver),
+            };
+
+            arena.adjudication = Some(Adjudication {
+                solver,
+                max_empties,
+                unsolved: Vec::new(),
+                solving: BTreeMap::new(),
+            });
+        }
+    }
+
+    if watch_engine.is_some() {
+        return Err(Error::InvalidValue(
+            "--watch-engine can only be used in compare and tournament mode".to_owned(),
+        ));
+    }
+
+    if solver.is_some() {
+        return Err(Error::InvalidValue(
+            "--solver can only be used with --adjudicate in compare and tournament mode".to_owned(),
+        ));
+    }
+
+    if groups.is_some() {
+        return Err(Error::InvalidValue(
+            "--groups can only be used in tournament mode".to_owned(),
+        ));
+    }
+
+    if advance.is_some() {
+        return Err(Error::InvalidValue(
+            "--advance can only be used with --groups".to_owned(),
+        ));
+    }
+
+    if window_options.stats && !matches!(mode, Mode::AIArena(_)) {
+        return Err(Error::InvalidValue(
+            "--stats-window can only be used in compare and tournament mode".to_owned(),
+        ));
+    }
+
+    if report_path.is_some() {
+        return Err(Error::InvalidValue(
+            "--report can only be used in compare and tournament mode".to_owned(),
+        ));
+    }
+
+    if hold_result.is_some() {
+        return Err(Error::InvalidValue(
+            "--hold-result can only be used in compare and tournament mode".to_owned(),
+        ));
+    }
+
+    if report_format.is_some()
+        && !matches!(&mode, Mode::AIArena(arena) if arena.report_path.is_some())
+    {
+        return Err(Error::InvalidValue(
+            "--report-format can only be used with --report".to_owned(),
+        ));
+    }
+
+    if ratings_path.is_some() {
+        return Err(Error::InvalidValue(
+            "--ratings can only be used in tournament mode".to_owned(),
+        ));
+    }
+
+    if reference.is_some() {
+        return Err(Error::InvalidValue(
+            "--analyze can only be used in compare and tournament mode".to_owned(),
+        ));
+    }
+
+    if book.is_some() {
+        if start.is_some() {
+            return Err(Error::InvalidValue(
+                "--book and --start can't be used together".to_owned(),
+            ));
+        }
+
+        // the openings were already taken from it
+        if !matches!(
+            mode,
+            Mode::AIArena(AIArena {
+                submode: Submode::Compare,
+                challenge: None,
+                ..
+            })
+        ) {
+            return Err(Error::InvalidValue(
+                "--book can only be used in compare mode".to_owned(),
+            ));
+        }
+    }
+
+    if let Some(start) = start {
+        match &mut mode {
+            Mode::Visual(visual) => {
+                if position.is_some() {
+                    return Err(Error::InvalidValue(
+                        "--start and --position can't be used together".to_owned(),
+                    ));
+                }
+
+                // the other side wouldn't know about it
+                if visual
+                    .game
+                    .players
+                    .iter()
+                    .any(|player| matches!(player, Player::Network(_)))
+                {
+                    return Err(Error::InvalidValue(
+                        "--start can't be used in network games".to_owned(),
+                    ));
+                }
+
+                let pos = start.pos();
+                visual.game.pos = pos;
+                visual.game.history = vec![(pos, None)];
+            }
+            // the openings were already played from it
+            Mode::AIArena(AIArena {
+                submode: Submode::Compare,
+                challenge: None,
+                ..
+            }) => {}
+            _ => {
+                return Err(Error::InvalidValue(
+                    "--start can only be used in visual, console-play and compare mode".to_owned(),
+                ))
+            }
+        }
+    }
+
+    if let Some(rules) = rules {
+        let Mode::Visual(visual) = &mut mode else {
+            return Err(Error::InvalidValue(
+                "--rules can only be used in visual and console-play mode".to_owned(),
+            ));
+        };
+
+        if start.is_some() {
+            return Err(Error::InvalidValue(
+                "--rules and --start can't be used together".to_owned(),
+            ));
+        }
+
+        for player in &mut visual.game.players {
+            match player {
+                Player::Human => {}
+                Player::AI(ai) => ai.rules = rules,
+                // the other players can't be told the rules
+                _ => {
+                    return Err(Error::InvalidValue(
+                        "With --rules only humans and local AIs can play".to_owned(),
+                    ))
+                }
+            }
+        }
+
+        let pos = rules.start();
+        visual.game.rules = rules;
+        visual.game.pos = pos;
+        visual.game.history = vec![(pos, None)];
+    }
+
+    if let Some(path) = position {
+        let Mode::Visual(visual) = &mut mode else {
+            return Err(Error::InvalidValue(
+                "--position can only be used in visual and console-play mode".to_owned(),
+            ));
+        };
+
+        // the other side wouldn't know about it
+        if visual
+            .game
+            .players
+            .iter()
+            .any(|player| matches!(player, Player::Network(_)))
+        {
+            return Err(Error::InvalidValue(
+                "--position can't be used in network games".to_owned(),
+            ));
+        }
+
+        // the moves of humans are read from stdin then
+        if console_play && path == "-" {
+            return Err(Error::InvalidValue(
+                "--position can't read from stdin in console-play mode".to_owned(),
+            ));
+        }
+
+        let pos = visual.game.rules.with_next_player(read_diagram(&path)?);
+        visual.game.pos = pos;
+        visual.game.history = vec![(pos, None)];
+    }
+
+    if let Some(grace) = undo_grace {
+        let Mode::Visual(visual) = &mut mode else {
+            return Err(Error::InvalidValue(
+                "--undo-grace can only be used in visual mode".to_owned(),
+            ));
+        };
+
+        if visual
+            .game
+            .players
+            .iter()
+            .any(|player| matches!(player, Player::Network(_)))
+        {
+            return Err(Error::InvalidValue(
+                "--undo-grace can't be used in network games, in which undo isn't possible"
+                    .to_owned(),
+            ));
+        }
+
+        visual.undo_grace = Some(grace);
+    }
+
+    if let Some(delay) = min_move_delay {
+        let Mode::Visual(visual) = &mut mode else {
+            return Err(Error::InvalidValue(
+                "--min-move-delay can only be used in visual mode".to_owned(),
+            ));
+        };
+
+        visual.min_move_delay = Some(delay);
+    }
+
+    if let Some(port) = serve_port {
+        let server =
+            SpectatorServer::start(port).map_err(|source| Error::Serve { port, source })?;
+
+        let (console, observers) = match &mut mode {
+            Mode::Visual(visual) => (&visual.console, &mut visual.observers),
+            Mode::AIArena(arena) => (&arena.console, &mut arena.observers),
+            Mode::Spectate(_) => unreachable!("spectate mode has its own options"),
+        };
+
+        console.print(
+            Category::GameFlow,
+            &format!("Spectate at http://localhost:{port}"),
+        );
+        observers.push(Box::new(server));
+    }
+
+    if let Some(path) = results_path {
+        let writer = ResultsWriter::create(Path::new(&path)).map_err(|source| Error::Write {
+            what: format!("results file '{path}'"),
+            source,
+        })?;
+
+        match &mut mode {
+            Mode::Visual(visual) => visual.observers.push(Box::new(writer)),
+            Mode::AIArena(arena) => arena.observers.push(Box::new(writer)),
+            Mode::Spectate(_) => unreachable!("spectate mode has its own options"),
+        }
+    }
+
+    if deterministic_seed.is_some() {
+        let (games, reference) = match &mut mode {
+            Mode::Visual(visual) => (std::slice::from_mut(&mut visual.game), None),
+            Mode::AIArena(arena) => (&mut arena.games[..], arena.reference.as_mut()),
+            Mode::Spectate(_) => unreachable!("spectate mode has its own options"),
+        };
+
+        for player in games.iter_mut().flat_map(|game| game.players.iter_mut()) {
+            player.set_deterministic();
+        }
+
+        if let Some(reference) = reference {
+            reference.deterministic = true;
+        }
+    }
+
+    if warm_up {
+        let games = match &mut mode {
+            Mode::Visual(visual) => std::slice::from_mut(&mut visual.game),
+            Mode::AIArena(arena) => &mut arena.games[..],
+            Mode::Spectate(_) => unreachable!("spectate mode has its own options"),
+        };
+
+        for game in games {
+            game.warm_up = vec![Tile::X, Tile::O];
+        }
+    }
+
+    if let Some(path) = capture_path {
+        let writer = CaptureWriter::create(Path::new(&path)).map_err(|source| Error::Write {
+            what: format!("capture file '{path}'"),
+            source,
+        })?;
 
-        [c]ompare <depth> <game amount> <max concurrency> <ai 1> <ai 2>: Play some games to compare the strength of two ais. Each opening is played twice, once as white and once as black for each ai.
-        <depth>: Games are started from a position after <depth> plies. If depth >= 1, the first move is always d3.
+        match &mut mode {
+            Mode::Visual(visual) => visual.observers.push(Box::new(writer)),
+            Mode::AIArena(arena) => arena.observers.push(Box::new(writer)),
+            Mode::Spectate(_) => unreachable!("spectate mode has its own options"),
+        }
+    }
+
+    match &mut mode {
+        Mode::Visual(visual) => {
+            let configs = engine_configs(std::slice::from_ref(&visual.game));
+
+            if !prepare_engines(&configs, &visual.console).is_empty() {
+                return Err(Error::Build(
+                    "Unable to play the game, as not all AIs could be built".to_owned(),
+                ));
+            }
+        }
+        Mode::AIArena(arena) => run_prepare(arena)?,
+        Mode::Spectate(_) => {}
+    }
+
+    // arena games are started later, as there is a limit on how many can run at once
+    if let Mode::Visual(visual) = &mut mode {
+        visual.game.initialize(&mut Broadcast {
+            console: &mut visual.console,
+            observers: &mut visual.observers,
+        })?;
+    }
+
+    if let Mode::AIArena(arena) = &mut mode {
+        handle_identical_engines(arena, dedupe_engines)?;
+
+        if !skip_preflight {
+            run_preflight(arena)?;
+        }
+    }
+
+    Ok((mode, window_options))
+}
+
+// <category>=<level>,...
+fn read_log_filters(filters: &str) -> Result<Vec<(Category, Level)>, Error> {
+    filters
+        .split(',')
+        .map(|filter| {
+            let (category, level) = filter.split_once('=').ok_or_else(|| {
+                Error::InvalidValue(format!(
+                    "Log filter '{filter}' is not in the form <category>=<level>"
+                ))
+            })?;
+
+            Ok((
+                category.parse().map_err(Error::InvalidValue)?,
+                level.parse().map_err(Error::InvalidValue)?,
+            ))
+        })
+        .collect()
+}
+
+// Every error ending the program ends up here, so that exit codes are decided in one place.
+fn exit_with_error(err: Error) -> ! {
+    eprintln!("{err}");
+
+    if matches!(
+        err,
+        Error::NoArguments | Error::UnknownMode(_) | Error::UnrecognisedOption(_)
+    ) {
+        print_help(&env::args().next().unwrap_or_default());
+    }
+
+    ai::exit(exit_code(&err));
+}
+
+fn exit_code(err: &Error) -> i32 {
+    match err {
+        Error::RunEngine { .. } => 4,
+        Error::NoArguments => 5,
+        Error::UnknownMode(_) => 6,
+        Error::HumanNotAccepted => 9,
+        Error::MissingArgument(_) => 11,
+        Error::InvalidInteger { .. } => 12,
+        Error::InvalidValue(_) => 13,
+        Error::NotPositive(_) => 14,
+        Error::InvalidEngine(_) => 15,
+        Error::Read { .. } => 16,
+        Error::InvalidAIListEntry(_) => 17,
+        Error::UnrecognisedOption(_) => 18,
+        Error::InvalidAIList(_) => 19,
+        Error::InvalidEngineConfig(_) => 21,
+        Error::Preflight(_) => 22,
+        Error::InvalidOutput(_) => 23,
+        Error::CloneRunningEngine(_) => 24,
+        Error::Serve { .. } => 25,
+        Error::Network(_) => 26,
+        Error::Write { .. } => 27,
+        Error::Build(_) => 28,
+    }
+}
+
+fn print_help(program_name: &str) {
+    print_version_info();
+
+    println!("{}", tr(Text::CommandLineArguments));
+    println!();
+    println!("{program_name} <mode> <mode arguments>");
+    println!();
+
+    // type annotation provided for rust-analyzer
+    let detailed: &str = textwrap_macros::dedent!(
+        r#"
+        MODES:
+
+        [h]elp: Print this.
+
+        [ver]sion: Print version info.
+
+        [v]isual <player 1> <player 2>: Play a game between two players. Unfinished games are saved after every move next to the settings, so that after a crash, or if the window was closed by accident, the game can be continued by pressing R once visual mode is started again.
+
+        console-play <player 1> <player 2>: Like visual, but played in the terminal without a window, e.g. over SSH. Humans enter moves like d3, or undo, hint to list the valid moves, or quit.
+
+        eval <ai> <positions file> <output file> <max concurrency>: Ask <ai> for its move in every position of <positions file>, e.g. to build opening books or label datasets. A JSON line is written to <output file> for every position, in order, with the move and notes of the AI, or the error if it failed.
+        <positions file>: a position on every line, as the 64 tiles of the board row by row, with X, O or . for empty, then a space and the player to move, X or O. Empty lines and lines starting with # are skipped.
+
+        build-book <max plies> <output file> <results file>...: Merge the games of files written with --results into an opening tree of at most <max plies> moves, with how many games every move was played in and how the player making it scored. If <output file> ends with .json, the tree is written as JSON, otherwise every move is written on its own line as `<moves> <games> <score>`, e.g. `d3c5 12 7.5`. Games from openings whose moves aren't known are skipped. The book can be used with --book to choose the openings of compare mode.
+        ~ --merge-symmetric: count openings which are rotations or mirror images of each other as one, e.g. c4, e6 and f5 as d3.
+
+        reproduce <capture file> <game id>: Run the AI of the last move of game <game id> in a file written with --capture again with the same input, and compare what it writes with what it wrote then, for debugging failures which don't always happen.
+        ~ --ply <ply>: the move after <ply> moves instead of the last one.
+        ~ --runs <n>: run the AI <n> times, once by default.
+
+        ratings <ratings file>: Print the Elo rating of every AI in every tournament recorded with --ratings, with the change since its previous tournament, and the change over all of them.
+        ~ --svg <file>: also draw the ratings as a line chart into <file>.
+
+        render <results file> <game id> <output>: Draw the game with <game id> of a file written with --results, without a window. If <output> ends with .gif, an animated GIF is written, otherwise a PNG for every frame, numbered from 0000.png, into the directory <output>.
+        ~ --animate: add frames of the disks turning over after every move.
+        ~ --size <pixels>: the width and height of the frames, 480 by default.
+        ~ --delay <ms>: how long every move is shown in the GIF, 1000 by default. The final position is shown three times as long.
+        ~ --accessible: draw with the theme for color blindness.
+
+        viewer <results file> <game id> <output>: Write the game with <game id> of a file written with --results into <output> as a single HTML page, which shows the board after every move in any browser, without installing the GUI. Moves are stepped through with the buttons, the arrow keys, or by clicking them in the list.
+        ~ --analyze <engine> <max time>: evaluate every position with <engine>, running as many at once as there are logical cores, and mark the moves losing at least 6 discs compared to its best move with ?? in the list, with the loss and the best move shown when hovering them. <engine> has to report its eval in discs as `eval=<n>` in its notes, as with --analyze in compare and tournament mode.
+
+        heatmap <output> <results file>...: Count on which squares every AI played in the opening, until 40 squares are empty, in the games of files written with --results, to spot gaps in opening books. If <output> ends with .png, a board colored by how often every square was played on is drawn for every AI, into <output> numbered from 1, e.g. heatmap-1.png, otherwise the share of moves on every square is written as text.
+        ~ --size <pixels>: the width and height of the images, 480 by default.
+        ~ --accessible: draw with the theme for color blindness.
+
+        host <port>: Play a game against another player, who joins from another instance of the GUI, possibly on another machine. The host plays with dark.
+
+        join <address>: Join a game hosted on <address>, which is given like 192.168.0.2:5000. If the connection is lost, it is restored automatically, without losing moves.
+
+        spectate <address>: Watch the games of a compare or tournament started with --serve, e.g. headless on a server, in the window. <address> is that of the spectator server, like 192.168.0.2:8080. The next game with any activity is shown once the shown one ended; press the left and right arrow keys to choose a game instead, and A to move on automatically again. Only --square-window, --accessible, --present, --level, --color and --lang can be used.
+
+        [c]ompare <depth> <game amount> <max concurrency> <ai 1> <ai 2> [<ai>...]: Play some games to compare the strength of two ais. Each opening is played twice, once as white and once as black for each ai. At the end the results are also shown per pair of games and per opening, the most one-sided openings first.
+        With more AIs, <ai 1> is compared with every other one from the same openings, e.g. a new version with several older ones, in a batch of compares run as one. The AIs are numbered in the order they are given, the results are shown for every compare, then summarized. Their games are played one compare after the other, or with --schedule interleaved all at the same time.
+        <depth>: Games are started from a position after <depth> plies. If depth >= 1, the first move is always d3, unless another start is given with --start.
         <game amount>: all | <pairs of games>
-        - all: Play all possible openings defined by <depth>.
+        - all: Play all possible openings defined by <depth>. Openings which are rotations or mirror images of each other are played only once.
         - <pairs of games>: If depth = 0, play <pairs of games> * 2 games, otherwise randomly choose <pairs of games> openings from all possible openings defined by <depth>.
         
-        [t]ournament <ai list> <max time> <max concurrency>: Every AI plays every other AI twice once as white and once as black. At the end a score table and estimated élő is displayed. (If élő scores cannot be calculated properly, incorrect values are displayed.)
-        <ai list>: path of file containing list of ai paths.
+        [t]ournament <ai list> <max time> <max concurrency>: Every AI plays every other AI twice once as white and once as black. At the end a score table, also split by the color played with, and estimated élő is displayed. (If élő scores cannot be calculated properly, incorrect values are displayed.)
+        <ai list>: path of file containing a player on every line, in the same form as <player> without <max time>, so external AIs can play against remote, bridge, plugin, WebAssembly and built-in AIs. Paths are relative to the list.
+
+        rerun-failures <results file> <max concurrency>: Play the games of a file written with --results again which were lost by a failing AI, with the same players and openings, and replace their results in the file. Relative paths are resolved as in the original run, so run it from the same directory.
+
+        challenge <ratings file> <ai>: Compare <ai> with the AI with the highest latest rating in a file written with --ratings, which gets the same time limit, from all openings of depth 4, one game at a time, then add its rating to the file, estimated from its score against that AI. Only AIs rated by this version can be challenged, as older versions don't record how to run them.
+
+        run <match file>: Play a compare or tournament described in a TOML file, so that it can be repeated and shared without a long command line. Options given after the file override those in it. Paths in the file are relative to it. The keys are:
+        ~ mode: "compare" or "tournament".
+        ~ engines: the AIs, as on the command line. For compare, the first is compared with every other one, like with more than two AIs on the command line.
+        ~ time: <max time> of every AI.
+        ~ concurrency: <max concurrency>, 1 by default.
+        ~ depth, games: <depth> and <game amount> of compare mode, 0 and "all" by default.
+        ~ seed: the seed of the openings, like --deterministic.
+        ~ adjudicate, position, results, report, ratings, capture: like the options with the same names.
+        ~ options: any other options, as a list of arguments, e.g. ["--schedule", "interleaved"].
+
+        In the window, press Q to quit, Esc opens the settings menu instead. While games are unfinished, Q has to be pressed again within 3 seconds. The running AIs are then stopped, and in compare and tournament mode the interruption is logged and the report of the finished games is written with --report. Closing the window does the same without asking.
 
         COMMON MODE ARGUMENTS:
 
-        <player>: human | <ai>
-        <ai>: <path> <max time>
-        <max time>: integer, in milliseconds.
-        <max concurrency>: Maximum number of games that can be played at once.
+        <player>: human | <ai> | <remote ai> | <bridge> | <plugin> | <wasm ai> | <built-in ai>
+        <ai>: <engine> <max time>
+        <remote ai>: tcp:<host>:<port> <max time>, an AI running on another machine. For every move a connection is opened to <host>:<port>, on which the AI is sent the same input as AIs get on stdin. It has to reply with its usual output, then close the connection.
+        <bridge>: bridge:<engine> <max time>, a relay process, e.g. to an online server, which is started once per game and kept running. It is talked to with JSON lines on stdin and stdout, as described in the protocol specification.
+        <plugin>: dylib:<path> <max time>, a player loaded from a shared library (.so, .dll or .dylib) exporting `othello_player_v1`, as described in the protocol specification. It runs inside this program, without starting a process for every move, but can't be stopped: if it exceeds its time limit the game is lost, and it keeps running until it returns.
+        <wasm ai>: wasm:<path> <max time>, an AI compiled to WebAssembly for WASI, e.g. with the wasm32-wasi target of Rust. It is run in a sandbox without access to files, the network or the environment, with the same input and output as other AIs. Once its time limit is up it is interrupted, so it doesn't need to be killed. Like other AIs, it loses if it writes more than 1 MB, and it can use at most 512 MB of memory.
+        <built-in ai>: builtin:<name> <max time>, a reference AI built into this program, to test other AIs against.
+        - random: plays a random valid move.
+        - greedy: plays the move flipping the most disks.
+        <engine>: <path>[|<engine option>...], the same format is used in <ai list>. Python, Java (.jar) and JavaScript AIs are run with the appropriate interpreter. On Linux and macOS, scripts without the executable bit are run with the interpreter in their shebang line.
+        <engine option>:
+        - interpreter=<command>: Run the AI with <command>, e.g. `bot.jar|interpreter=java -jar`.
+        - cwd=<dir>: Run the AI in <dir>. In <ai list> it is relative to the list, like <path>.
+        - env=<name>=<value>: Set an environment variable for the AI, can be given multiple times.
+        - name=<name>: The engine the AI is a build of, shown in results instead of its path, e.g. `builds/0412/bot|name=bot|version=v1.2`.
+        - version=<version>: Which version of the engine the AI is, shown after its name. Reports and ratings group the versions of an engine together, so that all versions can be compared in one tournament.
+        - build=<command>: Command building the AI, like `cargo build --release` or `make`, run with the shell before the games. If building fails, the AI is excluded from tournaments, and other modes stop. The AI is only built again once a file in its source directory changed, hidden files and the directories the AI is in excluded. As options are separated by |, the command can't contain pipes.
+        - src=<dir>: The source directory of the AI, in which the build command is run. Defaults to the working directory given with cwd, or the current directory.
+        - option=<key>=<value>: A setting of the engine, like `option=hash=256`, can be given multiple times. Neither the key nor the value can contain spaces. The same program with different options is a different AI, e.g. to enter it into a tournament with several settings, and the options are shown after its name.
+        - pass-options=<how>: args | input, how the options are given to the AI, args by default.
+        ~ args: as command line arguments, like `--hash=256`.
+        ~ input: as lines like `option hash 256` before the board in the input of every move, as described in the protocol specification.
+        <max time>: integer, in milliseconds, or with a unit: `100ms`, `2s`. In compare mode the two AIs can be given different time limits, e.g. to see whether a new AI with 100ms is stronger than an old one with 1s.
+        ~ depth:<n>: the AI is told to search <n> moves deep instead, making games deterministic and independent of the hardware for AIs honoring it. Such AIs are only stopped after a minute.
+        ~ nodes:<n>: the AI is told to search at most <n> nodes instead, similarly to depth:<n>. If the AI reports the number of nodes it searched in its notes as `nodes=<n>`, exceeding the limit loses the game.
+        <max concurrency>: Maximum number of games that can be played at once, or auto to find it while the games are played: starting from a quarter of the physical cores, more games are played at once while the CPUs aren't fully used and the moves don't take longer than with fewer games, and fewer once they do, or once an AI exceeds its time limit. The CPU usage is only measured on Linux.
 
         OPTIONS:
 
+        --accessible: Use the theme for color blindness, in which the disks of O are rings, and the highlights are orange and blue, regardless of the theme chosen in the settings menu.
+
+        --adjudicate <empties>: In compare and tournament mode, end games once at most <empties> squares are empty, with the result of perfect play, found by the built-in solver or the one given with --solver. The games go on while they are solved. The built-in solver gives up on positions too hard for it, usually those with more than about 16 empty squares. If solving fails, the game is played to the end.
+
+        --advance <k>: With --groups, the number of AIs of every group playing in the final, 1 by default.
+
+        --analyze <engine> <max time>: In compare and tournament mode, replay the finished games through <engine> as a reference, and report the average loss per move, blunders (losing at least 6 discs) and accuracy (the share of moves <engine> would have played too) of every AI, also separately in the opening (40 or more empty squares), the midgame and the endgame (20 or fewer), with the time taken per move. <engine> has to report its eval in discs as `eval=<n>` in its notes.
+
+        --book <file>: In compare mode, play the openings of <depth> moves from a book written by build-book in the line based format, instead of all possible ones, e.g. to test the AIs on the openings played in earlier runs. <depth> can be more than 5 then. Can't be used with --start.
+
+        --capture <file>: Append a JSON line to <file> for every run of a local AI, containing the exact input it was sent and everything it wrote, to be used with reproduce mode.
+
+        --color <when>: auto | always | never, whether to color the output: results green, AI failures red and moves dim. With auto, which is the default, the output is colored if it goes to a terminal and NO_COLOR isn't set.
+
+        --dedupe-engines: In tournament mode, AIs in <ai list> which are byte-identical to an earlier one, and are run the same way, are excluded instead of only warned about, as they would skew Elo.
+
+        --deterministic <seed>: Ask the AIs not to use randomness, with an extra line `deterministic` in their input, and in compare mode choose the openings with <seed>, so that runs can be reproduced exactly. AIs taking less time than their limit may still play differently under different load, so this works best with depth or node limits.
+
+        --disk-scoring <scoring>: In compare and tournament mode, also report the total of disk scores, as some competitions score by disks.
+        ~ difference: the own disks minus those of the opponent at the end of every game.
+        ~ split: the own disks at the end of every game, with the empty squares awarded to the winner, or split on a draw, so every game is worth 64 disks.
+        Games not played to the end, because an AI failed or they were adjudicated, are scored with the empty squares awarded to the winner.
+
+        --exclusive-engines: In compare and tournament mode, never run two games involving the same AI at once, e.g. for AIs with large shared caches or license limits. Games are then started out of order, whenever none of their AIs are playing.
+
+        --groups <n>: In tournament mode, split the AIs into <n> groups playing a round-robin each, seeded in the order of <ai list>: the first AI to the first group, the second to the second, and so on. The best AIs of every group, see --advance, then play a final round-robin, whose results are reported like those of a tournament. Ties are broken by the order of <ai list>.
+
+        --hold-result <ms>: In compare and tournament mode, when the shown game ends, show its final position with the result for <ms> milliseconds before switching to a running game.
+
+        --max-duration <minutes>: In compare and tournament mode, stop starting new games after <minutes>, finish the running ones, and report the results of the games played, marked as truncated.
+
+        --max-load <load>: In compare and tournament mode, don't start new games while the load average of the last minute is above <load>, so that other programs running on the machine don't skew the timing of the AIs. The running games count towards the load, so <load> should be above <max concurrency>. As the load average follows the load with a delay, starts are paused and resumed up to about a minute late. Only supported on Linux.
+
+        --min-move-delay <ms>: In visual mode, show AI moves at least <ms> milliseconds after the previous move, so that games of fast AIs can be followed. The AIs aren't slowed down, only their moves are shown later, and their thinking time is still measured until they moved.
+
+        --notify: In compare and tournament mode, show a desktop notification with the result when all games are done, or the reason if the run is aborted.
+
+        --on-finish <command>: In compare and tournament mode, run <command> with the shell when all games are done or the run is aborted. The environment variable OTHELLO_GUI_STATUS is set to `finished` or `aborted`, and OTHELLO_GUI_RESULT to the result or the reason of aborting.
+
+        --pin-cores: In compare and tournament mode, pin the AIs of every running game to a different physical core, making timing fairer. Only supported on Linux.
+
+        --json-events: Write every game start, move, AI failure, undo and game end to stdout as a JSON line as soon as it happens, e.g. `{"event":"move","game_id":0,"player":"X","move":"d3","notes":""}`, for scripts following the games. In compare and tournament mode, changes of the number of games played at once by auto, pauses because of --max-load, and --max-duration running out are written too. Everything else is written to stderr instead.
+
+        --event-log <file>: In compare and tournament mode, write the same events as --json-events to <file>, each with `time_ms`, the Unix time in milliseconds, and `elapsed_ms`, the time since the start, for finding out afterwards why a long run was slow or which AIs failed when.
+
+        --poll-interval <interval>: In compare and tournament mode, update the window when an AI finished, and otherwise only every <interval> milliseconds, instead of every frame, e.g. `--poll-interval 1000` to save CPU. Moves are played and the next AIs started as soon as an AI finishes, and time limits are enforced to the millisecond regardless.
+
+        --position <file>: In visual and console-play mode, start the game from the position drawn in <file>, or pasted on stdin if <file> is -, which only works in visual mode. The position is given as 8 rows of 8 tiles, with . for empty tiles and x or o for disks, optionally followed by a line with the next player, X by default. Spaces, row numbers and column letters are ignored, so boards printed by --print-boards can be used too.
+
+        --start <start>: In visual, console-play and compare mode, the arrangement of the four disks in the center the game or the openings start from, for variety. Can't be used with --position, which gives any other start.
+        - standard: each player's disks on a diagonal, the default.
+        - parallel: each player's disks in the same column.
+
+        --rules <rules>: In visual and console-play mode, the rules the game is played by. Only humans and local AIs can play by other rules than those of Othello, the AIs are told the rules as described in protocol-specification.md.
+        - othello: the default.
+        - reversi: classic Reversi, where the board starts empty, and the players take turns placing the first four disks on the center squares, X first, without turning any over. The hints show the free center squares until then. Can't be used with --start.
+
+        --print-boards: Print the board after every move, with the last move in brackets, for following games without the GUI, e.g. in logs.
+
+        --present: Presentation mode, e.g. for projecting live tournaments. The window is fullscreen, the board is maximized, and the names of the players and a big score are shown above it. Only the results are printed, unless set otherwise with --log. In compare and tournament mode, a is used to turn switching to a running game when the shown one ends on or off.
+
+        --solver <engine>: The program used by --adjudicate instead of the built-in solver. It is given the same input as AIs, and has to output the winner with perfect play: X, O or draw, within 10 seconds.
+
+        --lang <language>: en | hu, the language of the window, of the messages of the console when games start, end or moves are undone, and of the parts of this help needed for playing. Other messages of the console, errors, reports and the details of modes and options are always in English.
+
         --[l]evel: [i]nfo | [w]arn | [n]ecessary
         ~ info: output everything, default.
         ~ warn: only output AI errors, crashes and necessary.
         ~ necessary: only output progress and end results.
+        ~ off: output nothing, only for --log.
+
+        --log <category>=<level>[,<category>=<level>...]: Set the level of categories of output separately, overriding --level, e.g. `--log engine-io=off,game-flow=warn` to see AI errors without every move.
+        ~ engine-io: the input and output of AIs shown when they fail, with the board and the valid moves.
+        ~ game-flow: games starting, moves, undos and arena progress.
+        ~ results: results of games, scores and élő.
+        ~ errors: AI failures and other problems.
+
+        --ratings <file>: In tournament mode, append the Elo ratings of the AIs with the current time to <file> when all games are done, so that their progress over many tournaments can be followed with ratings mode.
+
+        --report <file>: In compare and tournament mode, write a JSON report to <file> when all games are done, with the standings, the total score of every AI after every finished game, for plotting how it changed, and the machine the games were played on. Reports of tournaments also have the standings after every round, in which every pairing of AIs played one game, which are printed as the rounds end too.
+
+        --report-format <format>: How --report is written: json (the default), markdown, with the standings, the win, draw and loss counts of every AI and a crosstable, to paste into a forum post or an issue, or html, the same as a standalone page.
+
+        --report-every <pairs>: In compare mode, print the score so far and the estimated Elo difference with its 95% confidence interval every time <pairs> more pairs of games are finished, so the run can be stopped early once the result is clear.
+
+        --results <file>: Append a JSON line to <file> for every finished game, containing the players with their arguments, the versions they reported and the hashes of their files, the opening, the seed it was chosen with in compare mode, the moves, the winner, the final X and O disk counts, the kind of AI failure the game was lost by, if any, and the machine it was played on: the CPU model, the number of cores, the OS and the version of othello_gui. Such games can be played again with rerun-failures.
+
+        --schedule <schedule>: in-order | interleaved, in which order the games of compare and tournament mode are started.
+        ~ in-order: the games of every pair of AIs after each other, default.
+        ~ interleaved: a game of every pair of AIs in turn, so that if the run is stopped early, e.g. by --max-duration, every AI has played a similar number of games against every other.
 
-        VISUAL PLAY:
+        --serve <port>: Serve a page on <port>, on which the games can be watched live from a browser, also from other machines. The state of the game is available as JSON at /state. All games can be watched in the GUI with spectate mode.
 
-        left click: place disk.
-        z: undo.
+        --skip-preflight: In compare and tournament mode, every AI is run once on the starting position before any games are played, at most <max concurrency> at once, to check that it works. AIs crashing, writing invalid output or playing an invalid move are excluded from tournaments, and abort compares. AIs only exceeding the time limit of the check, at most 1 second, are warned about if their own limit is longer. This option disables the check.
+
+        --square-window: Keep the window square when it is resized, so that there is no empty space next to the board.
+
+        --stats-window: In compare and tournament mode, open a second window showing the progress, the score and number of failures of every AI, the results of the last games, and a graph of the total score of every AI as the games finish.
+
+        --undo-grace <ms>: In visual mode, a human move can be taken back by clicking its square again within <ms> milliseconds, even after the AI replied, to protect against accidental clicks.
+
+        --watch-engine <name>: In compare and tournament mode, only move on to and choose with the arrow keys the games of the AI called <name>, given as in <ai list> or just its file name. Press F to switch between all games, the games lost by a failure, and the games of every AI in turn. Choosing a game with the left and right arrow keys stops moving on, until A is pressed.
+
+        --warm-up: Before the first move of every game, run each local AI of the game once on the starting position, with its time limit, and discard the result, so that JIT compilation and cold caches, e.g. of interpreted AIs, don't slow down its first timed move. The warm-up runs aren't counted in the timing or the results, and AIs failing them are only warned about.
+
+        --verify-moves: After every move, play it again with a second, independent implementation of the rules, and warn if the resulting position differs, which is a bug of the GUI, not of the AIs. It is meant for checking that results can be trusted while the rules are being changed, and slows down every move.
+
+    "#
+    );
+
+    let exit_codes: &str = textwrap_macros::dedent!(
+        r#"
+        EXIT CODES:
+
+        0: success.
+        4: unable to start an AI.
+        5: no arguments given.
+        6: unknown mode.
+        9: human player given where only AIs are accepted.
+        11: missing argument.
+        12: argument is not an integer.
+        13: argument out of range or unknown.
+        14: argument is zero where it must be positive.
+        15: AI cannot be run, e.g. its path is invalid.
+        16: unable to read a file.
+        17: an AI in <ai list> cannot be run.
+        18: unrecognised option.
+        19: invalid <ai list>, e.g. it contains duplicates.
+        21: invalid <engine option>.
+        22: AIs failed preflight.
+        23: invalid output of an AI.
+        24: an AI was cloned while running.
+        25: unable to start the spectator server.
+        26: network play failed, e.g. the two sides got out of sync.
+        27: unable to write a file.
+        28: building an AI failed.
     "#
     );
 
     let terminal_width = crossterm::terminal::size().map(|size| size.0).unwrap_or(80);
     let wrap_options = textwrap::Options::new(terminal_width as usize).subsequent_indent("    ");
 
+    // only the parts most needed for playing are translated
+    let detailed = format!(
+        "{}\n{detailed}\n{}\n{exit_codes}",
+        tr(Text::ReferenceInEnglish),
+        tr(Text::VisualPlayHelp)
+    );
+
     // I couldn't get it to work without a collect() in the middle
     let detailed = detailed
         .lines()
@@ -259,459 +1886,3955 @@ fn print_version_info() {
     println!();
 }
 
-fn handle_compare_mode(arg_iter: &mut Iter<String>) -> Mode {
-    let depth: usize = read_int(arg_iter, "<depth>");
-    if depth > 5 {
-        eprintln!("depth can be at most 5");
-        process::exit(13);
-    }
+// Nothing is played when spectating, so only the options of the window and the console apply.
+fn handle_spectate_mode(arg_iter: &mut Iter<String>) -> Result<(Mode, WindowOptions), Error> {
+    let address = read_string(arg_iter, "<address>")?;
 
-    let pairs_of_games = read_string(arg_iter, "<game amount>");
-    let game_amount_mode = match pairs_of_games.as_str() {
-        "a" | "all" => GameAmountMode::All,
-        num => GameAmountMode::Some(handled_parse(num, "<game amount> (which isn't 'all')")),
-    };
+    let mut console = Console::new(Level::Info);
+    let mut window_options = WindowOptions::default();
 
-    let max_concurrency = read_int(arg_iter, "<max concurrency>");
-    if max_concurrency == 0 {
-        eprintln!("max_concurrency must be at least 1");
-        process::exit(14);
+    while let Some(option) = arg_iter.next() {
+        match option.to_lowercase().as_str() {
+            "--square-window" => window_options.square = true,
+            "--accessible" => window_options.accessible = true,
+            "--present" => window_options.present = true,
+            // already set in `main`
+            "--lang" => {
+                read_string(arg_iter, "<language>")?;
+            }
+            "-l" | "--level" => console.set_level(
+                read_string(arg_iter, "<level>")?
+                    .parse()
+                    .map_err(Error::InvalidValue)?,
+            ),
+            "--color" => console.set_color(
+                read_string(arg_iter, "<when>")?
+                    .parse()
+                    .map_err(Error::InvalidValue)?,
+            ),
+            other => return Err(Error::UnrecognisedOption(other.to_owned())),
+        }
     }
 
-    let player_a = read_ai_player(arg_iter);
-    let player_b = read_ai_player(arg_iter);
+    let client = SpectatorClient::connect(&address)?;
 
-    let mut games = Vec::new();
+    console.print(
+        Category::GameFlow,
+        &format!("Spectating the arena at {address}"),
+    );
 
-    let possible_starts = if depth == 0 {
-        vec![Pos::new()]
-    } else {
-        Pos::new()
-            .play_clone(othello_gui::Vec2::new(3, 4))
-            .tree_end(depth - 1)
+    let spectate = Spectate {
+        client,
+        games: BTreeMap::new(),
+        showed: None,
+        auto_advance: true,
+        console,
+        waiting: Game::new(0, [Player::Human, Player::Human]),
     };
 
-    let starts = match game_amount_mode {
-        GameAmountMode::All => possible_starts,
-        GameAmountMode::Some(mut pairs_of_games) => {
-            if depth == 0 {
-                possible_starts.repeat(pairs_of_games)
-            } else {
-                if pairs_of_games > possible_starts.len() {
-                    println!(
-                        "Warning: specified pairs of games is higher than possible game starts,"
-                    );
-                    println!("number of games adjusted");
-                    pairs_of_games = possible_starts.len();
-                }
+    Ok((Mode::Spectate(spectate), window_options))
+}
+
+fn handle_compare_mode(
+    arg_iter: &mut Iter<String>,
+    seed: Option<u64>,
+    start: Option<Start>,
+    book: Option<&[BookLine]>,
+) -> Result<Mode, Error> {
+    let depth: usize = read_int(arg_iter, "<depth>")?;
+    let pairs_of_games = read_string(arg_iter, "<game amount>")?;
+    let (max_concurrency, auto_concurrency) = read_max_concurrency(arg_iter)?;
+
+    let mut players = vec![read_ai_player(arg_iter)?, read_ai_player(arg_iter)?];
+
+    // further AIs until the options
+    while arg_iter
+        .clone()
+        .next()
+        .is_some_and(|arg| !arg.starts_with("--"))
+    {
+        players.push(read_ai_player(arg_iter)?);
+    }
+
+    compare_batch(
+        depth,
+        &pairs_of_games,
+        (max_concurrency, auto_concurrency),
+        players,
+        seed,
+        start.unwrap_or_default(),
+        book,
+    )
+}
+
+// compares the first AI with every other one, in one arena with the games of all compares
+fn compare_batch(
+    depth: usize,
+    pairs_of_games: &str,
+    concurrency: (usize, Option<AutoConcurrency>),
+    mut players: Vec<Player>,
+    seed: Option<u64>,
+    start: Start,
+    book: Option<&[BookLine]>,
+) -> Result<Mode, Error> {
+    if players.len() == 2 {
+        let [player_a, player_b]: [Player; 2] = players.try_into().expect("two players");
+        return compare_arena(
+            depth,
+            pairs_of_games,
+            concurrency,
+            [player_a, player_b],
+            seed,
+            start,
+            book,
+        );
+    }
+
+    let first = players.remove(0);
+    // the same openings for every compare, so that their results can be compared
+    let seed = Some(seed.unwrap_or_else(rand::random));
+
+    let mut batch: Option<AIArena> = None;
+
+    for (compare, opponent) in players.into_iter().enumerate() {
+        let Mode::AIArena(mut arena) = compare_arena(
+            depth,
+            pairs_of_games,
+            concurrency.clone(),
+            [first.try_clone()?, opponent],
+            seed,
+            start,
+            book,
+        )?
+        else {
+            unreachable!("compare_arena creates an arena");
+        };
+
+        let (game_offset, pair_offset) = match &batch {
+            Some(batch) => (batch.games.len(), batch.games.len() / 2),
+            None => (0, 0),
+        };
+
+        for game in &mut arena.games {
+            game.id += game_offset;
+
+            if let Some(pair) = &mut game.pair {
+                pair.id += pair_offset;
+                pair.compare = compare;
+            }
+        }
+
+        match &mut batch {
+            Some(batch) => batch.games.append(&mut arena.games),
+            None => batch = Some(arena),
+        }
+    }
+
+    Ok(Mode::AIArena(batch.expect("at least two compares")))
+}
+
+// the openings of challenges, 60 of them played twice
+const CHALLENGE_DEPTH: usize = 4;
+
+fn handle_challenge_mode(arg_iter: &mut Iter<String>, seed: Option<u64>) -> Result<Mode, Error> {
+    let ratings_path = PathBuf::from(read_string(arg_iter, "<ratings file>")?);
+    let challenger = read_ai_player(arg_iter)?;
+
+    let snapshots = ratings::read_snapshots(&ratings_path)?;
+    let champion = ratings::top_rated(&snapshots).cloned().ok_or_else(|| {
+        Error::InvalidValue(format!(
+            "Ratings file '{}' has no AI which can be played again",
+            ratings_path.display()
+        ))
+    })?;
+
+    // the champion plays with the time of the challenger, so that it is a fair fight
+    let limit = challenger.limit().map(Limit::arg);
+    let args: Vec<String> = champion.arg.iter().cloned().chain(limit).collect();
+    let champion_player = read_ai_player(&mut args.iter())?;
+
+    println!(
+        "{} (Elo {:.0}) is challenged by {}",
+        champion_player.name(),
+        champion.rating,
+        challenger.name()
+    );
+
+    let mut mode = compare_arena(
+        CHALLENGE_DEPTH,
+        "all",
+        (1, None),
+        [champion_player, challenger],
+        seed,
+        // the ratings are from the standard start
+        Start::Standard,
+        None,
+    )?;
+
+    if let Mode::AIArena(arena) = &mut mode {
+        arena.challenge = Some(Challenge {
+            ratings_path,
+            champion,
+        });
+    }
+
+    Ok(mode)
+}
+
+fn compare_arena(
+    depth: usize,
+    pairs_of_games: &str,
+    (max_concurrency, auto_concurrency): (usize, Option<AutoConcurrency>),
+    [player_a, player_b]: [Player; 2],
+    seed: Option<u64>,
+    start: Start,
+    book: Option<&[BookLine]>,
+) -> Result<Mode, Error> {
+    // all openings of a depth are generated, unless they are taken from a book
+    if depth > 5 && book.is_none() {
+        return Err(Error::InvalidValue("depth can be at most 5".to_owned()));
+    }
+
+    let game_amount_mode = match pairs_of_games {
+        "a" | "all" => GameAmountMode::All,
+        num => GameAmountMode::Some(handled_parse(num, "<game amount> (which isn't 'all')")?),
+    };
+
+    let mut games = Vec::new();
+
+    // from the standard start all first moves are the same under a symmetry
+    let first_moves = if depth == 0 || start != Start::Standard {
+        Vec::new()
+    } else {
+        vec![othello_gui::Vec2::new(3, 4)]
+    };
+
+    let possible_starts: Box<dyn Iterator<Item = openings::Opening>> = match book {
+        Some(book) => Box::new(book::openings(book, depth)?.into_iter()),
+        // the same opening rotated or mirrored would test the same thing again
+        None => Box::new(
+            openings::Openings::from_start(start.pos(), first_moves, depth).merge_symmetric(),
+        ),
+    };
+
+    // kept so that results record how the openings were chosen
+    let given_seed = seed;
+    let mut seed = None;
+
+    let starts = match game_amount_mode {
+        GameAmountMode::All => possible_starts.collect(),
+        GameAmountMode::Some(pairs_of_games) => {
+            if depth == 0 {
+                possible_starts
+                    .flat_map(|opening| vec![opening; pairs_of_games])
+                    .collect()
+            } else {
+                let chosen_seed = given_seed.unwrap_or_else(rand::random);
+                seed = Some(chosen_seed);
+
+                let mut rng = StdRng::seed_from_u64(chosen_seed);
+
+                let chosen = possible_starts.choose_multiple(&mut rng, pairs_of_games);
+
+                if chosen.len() < pairs_of_games {
+                    println!(
+                        "Warning: specified pairs of games is higher than possible game starts,"
+                    );
+                    println!("number of games adjusted");
+                }
+
+                chosen
+            }
+        }
+    };
+
+    for (i, openings::Opening { pos: start, moves }) in starts.into_iter().enumerate() {
+        let opening = Some(moves);
+
+        let players1 = [player_a.try_clone()?, player_b.try_clone()?];
+        let players2 = [player_b.try_clone()?, player_a.try_clone()?];
+
+        for (id, players, first) in [(i * 2, players1, Tile::X), (i * 2 + 1, players2, Tile::O)] {
+            let mut game = Game::from_pos(id, players, start);
+            game.seed = seed;
+            game.pair = Some(Pair {
+                id: i,
+                first,
+                compare: 0,
+            });
+            game.opening = opening.clone();
+            games.push(game);
+        }
+    }
+
+    Ok(Mode::AIArena(AIArena::new(
+        games,
+        Submode::Compare,
+        max_concurrency,
+        auto_concurrency,
+    )))
+}
+
+fn handle_tournament_mode(arg_iter: &mut Iter<String>) -> Result<Mode, Error> {
+    let ai_list_path_string = read_string(arg_iter, "<ai list>")?;
+    let ai_list_path_path: PathBuf = ai_list_path_string.clone().into();
+    let limit = read_limit(arg_iter)?;
+    let concurrency = read_max_concurrency(arg_iter)?;
+
+    let players: Vec<Player> = std::fs::read_to_string(ai_list_path_string)
+        .map_err(|source| Error::Read {
+            what: "<ai list>".to_owned(),
+            source,
+        })?
+        .trim()
+        .lines()
+        .map(|ln| {
+            ai_player(ln.trim(), limit, ai_list_path_path.parent().unwrap()).map_err(
+                |err| match err {
+                    Error::InvalidEngine(err) => Error::InvalidAIListEntry(err),
+                    err => err,
+                },
+            )
+        })
+        .collect::<Result<_, Error>>()?;
+
+    tournament_arena(players, concurrency)
+}
+
+fn tournament_arena(
+    players: Vec<Player>,
+    (max_concurrency, auto_concurrency): (usize, Option<AutoConcurrency>),
+) -> Result<Mode, Error> {
+    if players.is_empty() {
+        return Err(Error::InvalidAIList("AI list file is empty".to_owned()));
+    }
+
+    if players.len() == 1 {
+        return Err(Error::InvalidAIList(format!(
+            "AI list only contains one element: '{}'",
+            players[0].name()
+        )));
+    }
+
+    // results are told apart by the ids of the players
+    if !has_unique_elements(players.iter().map(Player::id)) {
+        return Err(Error::InvalidAIList(
+            "AI list contains duplicate elements".to_owned(),
+        ));
+    }
+
+    let ai_configs: Vec<EngineConfig> = players
+        .iter()
+        .filter_map(|player| match player {
+            Player::AI(ai) => Some(ai.config.clone()),
+            _ => None,
+        })
+        .collect();
+
+    let identical_engines = find_identical_engines(&ai_configs)?;
+
+    let mut arena = AIArena::new(
+        round_robin(&players, 0)?,
+        Submode::Tournament,
+        max_concurrency,
+        auto_concurrency,
+    );
+    arena.identical_engines = identical_engines;
+
+    Ok(Mode::AIArena(arena))
+}
+
+// Seeds the AIs into groups in the order of <ai list>, the first AI to the first group, the
+// second to the second, and so on, and only keeps the games within the groups.
+fn split_into_groups(
+    arena: &mut AIArena,
+    count: usize,
+    advance: usize,
+    schedule: Schedule,
+) -> Result<(), Error> {
+    let mut ids: Vec<PlayerId> = Vec::new();
+
+    for game in &arena.games {
+        for player in &game.players {
+            let id = player.id();
+
+            if !ids.contains(&id) {
+                ids.push(id);
+            }
+        }
+    }
+
+    if count < 2 || ids.len() < count * 2 {
+        return Err(Error::InvalidValue(format!(
+            "<groups> must be between 2 and {}, so that every group has at least two AIs",
+            ids.len() / 2
+        )));
+    }
+
+    let mut members = vec![Vec::new(); count];
+
+    for (i, id) in ids.into_iter().enumerate() {
+        members[i % count].push(id);
+    }
+
+    let smallest = members.iter().map(Vec::len).min().unwrap_or(0);
+
+    if advance == 0 || advance > smallest {
+        return Err(Error::InvalidValue(format!(
+            "<advance> must be between 1 and {smallest}, the size of the smallest group"
+        )));
+    }
+
+    let group_of = |id: PlayerId| members.iter().position(|group| group.contains(&id));
+
+    arena
+        .games
+        .retain(|game| group_of(game.players[0].id()) == group_of(game.players[1].id()));
+
+    for (id, game) in arena.games.iter_mut().enumerate() {
+        game.id = id;
+    }
+
+    arena.groups = Some(Groups {
+        members,
+        advance,
+        schedule,
+        group_games: None,
+    });
+
+    Ok(())
+}
+
+// Once the group stage is over, reports the groups and replaces the games with the final,
+// returning whether it did.
+fn start_final(arena: &mut AIArena) -> Result<bool, Error> {
+    let Some(groups) = &mut arena.groups else {
+        return Ok(false);
+    };
+
+    if groups.group_games.is_some() || arena.truncated_from.is_some() {
+        return Ok(false);
+    }
+
+    arena.console.unpin();
+
+    let mut scores: HashMap<PlayerId, f32> = HashMap::new();
+    let mut names: HashMap<PlayerId, String> = HashMap::new();
+
+    for game in &arena.games {
+        for (i, tile) in Tile::opponent_iter().enumerate() {
+            let player = &game.players[i];
+
+            *scores.entry(player.id()).or_insert(0.0) += game.score_for(tile, Scoring::WinDrawLoss);
+            names.entry(player.id()).or_insert_with(|| player.name());
+        }
+    }
+
+    let mut finalists = Vec::new();
+
+    for (i, group) in groups.members.iter().enumerate() {
+        // excluded by --dedupe-engines if they have no games
+        let mut standings: Vec<&PlayerId> =
+            group.iter().filter(|id| scores.contains_key(*id)).collect();
+        // stable, so ties are broken by the order of <ai list>
+        standings.sort_by(|id1, id2| scores[*id2].partial_cmp(&scores[*id1]).unwrap());
+
+        arena
+            .console
+            .print(Category::Results, &format!("Group {}:", i + 1));
+
+        for (place, id) in standings.iter().enumerate() {
+            let advances = place < groups.advance;
+
+            arena.console.print(
+                Category::Results,
+                &format!(
+                    "{: >5.1} {}{}",
+                    scores[*id],
+                    names[*id],
+                    if advances { " (advances)" } else { "" }
+                ),
+            );
+
+            if advances {
+                finalists.push((*id).clone());
+            }
+        }
+    }
+
+    if finalists.len() < 2 {
+        return Ok(false);
+    }
+
+    let mut players = Vec::new();
+
+    for id in &finalists {
+        let player = arena
+            .games
+            .iter()
+            .flat_map(|game| &game.players)
+            .find(|player| player.id() == *id)
+            .expect("Expected a game of every finalist");
+
+        players.push(player.try_clone()?);
+    }
+
+    let first_id = arena
+        .games
+        .iter()
+        .map(|game| game.id + 1)
+        .max()
+        .unwrap_or(0);
+    let mut games = round_robin(&players, first_id)?;
+    groups.schedule.arrange(&mut games);
+
+    arena.console.print(
+        Category::GameFlow,
+        &format!(
+            "Group stage done, starting the final of {} AIs",
+            finalists.len()
+        ),
+    );
+
+    groups.group_games = Some(std::mem::replace(&mut arena.games, games));
+    arena.showed_game_idx = 0;
+    arena.reported_rounds = 0;
+    arena.rounds_checked_at = 0;
+
+    Ok(true)
+}
+
+// two games of every pair of players, one with either color, with ids from `first_id`
+fn round_robin(players: &[Player], first_id: usize) -> Result<Vec<Game>, Error> {
+    let mut games = Vec::new();
+
+    let mut id = first_id;
+
+    for (i, player_1) in players.iter().enumerate() {
+        for player_2 in &players[i + 1..] {
+            games.push(Game::new(
+                id,
+                [player_1.try_clone()?, player_2.try_clone()?],
+            ));
+            id += 1;
+
+            games.push(Game::new(
+                id,
+                [player_2.try_clone()?, player_1.try_clone()?],
+            ));
+            id += 1;
+        }
+    }
+
+    Ok(games)
+}
+
+fn run_eval_mode(arg_iter: &mut Iter<String>) -> Result<(), Error> {
+    let Player::AI(ai) = read_ai_player(arg_iter)? else {
+        return Err(Error::InvalidValue(
+            "Only local AIs can be used in eval mode".to_owned(),
+        ));
+    };
+
+    let positions_path = read_string(arg_iter, "<positions file>")?;
+    let output_path = read_string(arg_iter, "<output file>")?;
+    let max_concurrency = read_int(arg_iter, "<max concurrency>")?;
+
+    if max_concurrency == 0 {
+        return Err(Error::NotPositive("<max concurrency>".to_owned()));
+    }
+
+    if let Some(option) = arg_iter.next() {
+        return Err(Error::UnrecognisedOption(option.to_owned()));
+    }
+
+    let content = std::fs::read_to_string(&positions_path).map_err(|source| Error::Read {
+        what: "<positions file>".to_owned(),
+        source,
+    })?;
+
+    let positions = eval::read_positions(&content)?;
+
+    let mut output = std::fs::File::create(&output_path).map_err(|source| Error::Write {
+        what: format!("output file '{output_path}'"),
+        source,
+    })?;
+
+    let mut console = Console::new(Level::Info);
+
+    eval::evaluate(&ai, &positions, max_concurrency, &mut output, &mut console)?;
+
+    console.print(
+        Category::Results,
+        &format!(
+            "Evaluated {} positions, written to '{output_path}'",
+            positions.len()
+        ),
+    );
+
+    Ok(())
+}
+
+fn run_reproduce_mode(arg_iter: &mut Iter<String>) -> Result<(), Error> {
+    let capture_path = read_string(arg_iter, "<capture file>")?;
+    let game_id = read_int(arg_iter, "<game id>")?;
+
+    let mut ply = None;
+    let mut runs = 1;
+
+    while let Some(option) = arg_iter.next() {
+        match option.as_str() {
+            "--ply" => ply = Some(read_int(arg_iter, "<ply>")?),
+            "--runs" => runs = read_int(arg_iter, "<runs>")?,
+            other => return Err(Error::UnrecognisedOption(other.to_owned())),
+        }
+    }
+
+    let captures = capture::read_captures(Path::new(&capture_path), game_id)?;
+
+    // the last run is the one a failure ended the game with
+    let capture = match ply {
+        Some(ply) => captures.iter().find(|capture| capture.ply == ply),
+        None => captures.last(),
+    }
+    .ok_or_else(|| {
+        Error::InvalidValue(format!(
+            "There is no such run of game #{game_id} in capture file '{capture_path}'"
+        ))
+    })?;
+
+    let (Some(engine), Some(limit)) = (&capture.engine, &capture.limit) else {
+        return Err(Error::InvalidValue(format!(
+            "The AI of the run of game #{game_id} can't be recreated"
+        )));
+    };
+
+    let Player::AI(mut ai) = read_ai_player(&mut [engine.clone(), limit.clone()].iter())? else {
+        return Err(Error::InvalidValue(
+            "Only local AIs can be reproduced".to_owned(),
+        ));
+    };
+
+    println!(
+        "Game #{game_id}, after {} moves, AI {} '{engine}' with {limit} was sent:",
+        capture.ply, capture.player
+    );
+    print!("{}", capture.input);
+    println!("It wrote:");
+    print!("{}", capture.stdout);
+
+    if !capture.stderr.is_empty() {
+        println!("and to stderr:");
+        print!("{}", capture.stderr);
+    }
+
+    for run in 1..=runs {
+        ai.run_input(capture.input.clone())
+            .map_err(|source| Error::RunEngine {
+                path: ai.config.path.clone(),
+                source,
+            })?;
+
+        let mut handle = ai.ai_run_handle.take().expect("AI not running");
+
+        let result = loop {
+            match handle.check() {
+                AIRunResult::Running => thread::sleep(REPRODUCE_POLL_INTERVAL),
+                result => break result,
+            }
+        };
+
+        let io = handle.io().expect("no io of a local AI");
+
+        let outcome = match result {
+            AIRunResult::Success(_) => "moved".to_owned(),
+            AIRunResult::TimeOut { .. } => "timed out".to_owned(),
+            AIRunResult::RuntimeError { status, .. } => format!("exited with {status}"),
+            AIRunResult::InvalidOuput(err) => format!("wrote invalid output: {err}"),
+            AIRunResult::ConnectionError(err) => err,
+            AIRunResult::Running => unreachable!("the run is over"),
+        };
+
+        if io.stdout == capture.stdout {
+            println!("Run {run}: {outcome}, with the same output");
+        } else {
+            println!("Run {run}: {outcome}, with different output:");
+            print!("{}", io.stdout);
+        }
+    }
+
+    Ok(())
+}
+
+fn run_ratings_mode(arg_iter: &mut Iter<String>) -> Result<(), Error> {
+    let ratings_path = read_string(arg_iter, "<ratings file>")?;
+    let mut svg_path = None;
+
+    while let Some(option) = arg_iter.next() {
+        match option.as_str() {
+            "--svg" => svg_path = Some(read_string(arg_iter, "<file>")?),
+            other => return Err(Error::UnrecognisedOption(other.to_owned())),
+        }
+    }
+
+    let snapshots = ratings::read_snapshots(Path::new(&ratings_path))?;
+
+    if snapshots.is_empty() {
+        println!("No tournaments in '{ratings_path}'");
+        return Ok(());
+    }
+
+    print!("{}", ratings::history_table(&snapshots));
+
+    if let Some(svg_path) = svg_path {
+        std::fs::write(&svg_path, ratings::svg_chart(&snapshots)).map_err(|source| {
+            Error::Write {
+                what: format!("chart '{svg_path}'"),
+                source,
+            }
+        })?;
+
+        println!("Chart written to '{svg_path}'");
+    }
+
+    Ok(())
+}
+
+fn run_render_mode(arg_iter: &mut Iter<String>) -> Result<(), Error> {
+    let results_path = read_string(arg_iter, "<results file>")?;
+    let game_id = read_int(arg_iter, "<game id>")?;
+    let output_path = read_string(arg_iter, "<output>")?;
+
+    let mut options = render::RenderOptions {
+        size: RENDER_SIZE,
+        animate: false,
+        delay: RENDER_DELAY,
+        palette: theme::CLASSIC,
+    };
+
+    while let Some(option) = arg_iter.next() {
+        match option.as_str() {
+            "--animate" => options.animate = true,
+            "--size" => {
+                options.size = read_int(arg_iter, "<pixels>")?;
+
+                // the disks wouldn't fit inside the strokes of the tiles
+                if options.size < MIN_WINDOW_SIZE {
+                    return Err(Error::InvalidValue(format!(
+                        "<pixels> must be at least {MIN_WINDOW_SIZE}"
+                    )));
+                }
+            }
+            "--delay" => options.delay = Duration::from_millis(read_int(arg_iter, "<ms>")?),
+            "--accessible" => options.palette = theme::ACCESSIBLE,
+            other => return Err(Error::UnrecognisedOption(other.to_owned())),
+        }
+    }
+
+    let transcript = results::read_transcript(Path::new(&results_path), game_id)?;
+    let frames = render::frames(&transcript, &options)?;
+    let frame_count = frames.len();
+
+    if output_path.to_lowercase().ends_with(".gif") {
+        render::write_gif(frames, Path::new(&output_path))?;
+    } else {
+        render::write_pngs(frames, Path::new(&output_path))?;
+    }
+
+    println!("Rendered game #{game_id} in {frame_count} frames, written to '{output_path}'");
+
+    Ok(())
+}
+
+fn run_viewer_mode(arg_iter: &mut Iter<String>) -> Result<(), Error> {
+    let results_path = read_string(arg_iter, "<results file>")?;
+    let game_id = read_int(arg_iter, "<game id>")?;
+    let output_path = read_string(arg_iter, "<output>")?;
+
+    let mut reference = None;
+
+    while let Some(option) = arg_iter.next() {
+        match option.to_lowercase().as_str() {
+            "--analyze" => {
+                let Player::AI(ai) = read_ai_player(arg_iter)? else {
+                    return Err(Error::InvalidValue(
+                        "Only local AIs can be used with --analyze".to_owned(),
+                    ));
+                };
+                reference = Some(ai);
+            }
+            _ => return Err(Error::UnrecognisedOption(option.to_owned())),
+        }
+    }
+
+    let transcript = results::read_transcript(Path::new(&results_path), game_id)?;
+
+    let analysis = match &reference {
+        Some(reference) => {
+            let game = transcript.game()?;
+            let mut console = Console::new(Level::Info);
+            let cores = std::thread::available_parallelism().map_or(1, |count| count.get());
+
+            println!("Analyzing game #{game_id} with {}", reference.config);
+
+            analysis::analyze_moves(reference, &[&game], cores, &mut console)?
+                .pop()
+                .unwrap_or_default()
+        }
+        None => Vec::new(),
+    };
+
+    let blunders = analysis
+        .iter()
+        .filter(|analyzed| analyzed.is_blunder())
+        .count();
+    let page = report::game_viewer(&transcript, &analysis)?;
+
+    std::fs::write(&output_path, page).map_err(|source| Error::Write {
+        what: format!("game viewer '{output_path}'"),
+        source,
+    })?;
+
+    println!("Game #{game_id} written to '{output_path}', which can be opened in any browser");
+
+    if reference.is_some() {
+        println!("{blunders} blunders are marked in the move list");
+    }
+
+    Ok(())
+}
+
+fn run_heatmap_mode(arg_iter: &mut Iter<String>) -> Result<(), Error> {
+    let output_path = PathBuf::from(read_string(arg_iter, "<output>")?);
+
+    let mut size = RENDER_SIZE;
+    let mut palette = theme::CLASSIC;
+    let mut transcripts = Vec::new();
+    let mut skipped = 0;
+
+    while let Some(arg) = arg_iter.next() {
+        match arg.as_str() {
+            "--size" => {
+                size = read_int(arg_iter, "<pixels>")?;
+
+                if size < MIN_WINDOW_SIZE {
+                    return Err(Error::InvalidValue(format!(
+                        "<pixels> must be at least {MIN_WINDOW_SIZE}"
+                    )));
+                }
+            }
+            "--accessible" => palette = theme::ACCESSIBLE,
+            results_path => {
+                let (read, skipped_here) = results::read_transcripts(Path::new(results_path))?;
+
+                transcripts.extend(read);
+                skipped += skipped_here;
+            }
+        }
+    }
+
+    if transcripts.is_empty() && skipped == 0 {
+        return Err(Error::MissingArgument("<results file>".to_owned()));
+    }
+
+    let heatmaps = heatmap::heatmaps(&transcripts);
+    let is_png = output_path
+        .extension()
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("png"));
+
+    if is_png {
+        let stem = output_path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .unwrap_or_default();
+
+        for (i, (name, heatmap)) in heatmaps.iter().enumerate() {
+            let path = output_path.with_file_name(format!("{stem}-{}.png", i + 1));
+
+            render::heatmap_image(heatmap, size, &palette)
+                .save(&path)
+                .map_err(|err| Error::Write {
+                    what: format!("PNG '{}'", path.display()),
+                    source: io::Error::other(err),
+                })?;
+
+            println!(
+                "Heatmap of {name} ({} moves): '{}'",
+                heatmap.moves,
+                path.display()
+            );
+        }
+    } else {
+        let content: String = heatmaps
+            .iter()
+            .map(|(name, heatmap)| {
+                format!(
+                    "{name} ({} moves)\n{}\n\n",
+                    heatmap.moves,
+                    heatmap.to_text()
+                )
+            })
+            .collect();
+
+        std::fs::write(&output_path, content).map_err(|source| Error::Write {
+            what: format!("output file '{}'", output_path.display()),
+            source,
+        })?;
+
+        println!(
+            "Heatmaps of {} AIs written to '{}'",
+            heatmaps.len(),
+            output_path.display()
+        );
+    }
+
+    if skipped > 0 {
+        println!("Skipped {skipped} games which couldn't be read");
+    }
+
+    Ok(())
+}
+
+fn run_book_mode(arg_iter: &mut Iter<String>) -> Result<(), Error> {
+    let max_plies = read_int(arg_iter, "<max plies>")?;
+    let output_path = read_string(arg_iter, "<output file>")?;
+
+    let mut games = Vec::new();
+    let mut skipped = 0;
+    let mut merge_symmetric = false;
+
+    for arg in arg_iter {
+        if arg == "--merge-symmetric" {
+            merge_symmetric = true;
+            continue;
+        }
+
+        let results_path = arg;
+        let (played, skipped_here) = results::read_played_games(Path::new(results_path))?;
+
+        games.extend(played);
+        skipped += skipped_here;
+    }
+
+    if games.is_empty() && skipped == 0 {
+        return Err(Error::MissingArgument("<results file>".to_owned()));
+    }
+
+    let book = book::build(&games, max_plies, merge_symmetric);
+
+    let content = if output_path.ends_with(".json") {
+        serde_json::to_string_pretty(&book).expect("Error serializing book")
+    } else {
+        book.to_lines()
+    };
+
+    std::fs::write(&output_path, content).map_err(|source| Error::Write {
+        what: format!("output file '{output_path}'"),
+        source,
+    })?;
+
+    println!(
+        "Built book of {} games, written to '{output_path}'",
+        games.len()
+    );
+
+    if skipped > 0 {
+        println!("Skipped {skipped} games not known to be started from the initial position");
+    }
+
+    Ok(())
+}
+
+fn handle_match_mode(
+    match_file: &MatchFile,
+    seed: Option<u64>,
+    start: Option<Start>,
+    book: Option<&[BookLine]>,
+) -> Result<Mode, Error> {
+    let limit = read_limit(&mut [match_file.time.clone()].iter())?;
+    let concurrency = read_max_concurrency(&mut [match_file.concurrency_arg()].iter())?;
+
+    let players = match_file
+        .engines
+        .iter()
+        .map(|engine| ai_player(engine, limit, &match_file.base))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    match match_file.mode {
+        MatchMode::Compare => compare_batch(
+            match_file.depth,
+            &match_file.games_arg(),
+            concurrency,
+            players,
+            seed.or(match_file.seed),
+            start.unwrap_or_default(),
+            book,
+        ),
+        MatchMode::Tournament => tournament_arena(players, concurrency),
+    }
+}
+
+fn handle_rerun_mode(arg_iter: &mut Iter<String>) -> Result<Mode, Error> {
+    let results_path = read_string(arg_iter, "<results file>")?;
+    let (max_concurrency, auto_concurrency) = read_max_concurrency(arg_iter)?;
+
+    let (writer, failed) = ResultsWriter::rerun(Path::new(&results_path))?;
+
+    if failed.is_empty() {
+        println!("No failed games in '{results_path}'");
+        process::exit(0);
+    }
+
+    let mut games = Vec::new();
+
+    for failed_game in failed {
+        let [args1, args2] = &failed_game.players;
+
+        let players = [
+            read_ai_player(&mut args1.iter())?,
+            read_ai_player(&mut args2.iter())?,
+        ];
+
+        let mut game = Game::from_pos(failed_game.game_id, players, failed_game.opening);
+        game.seed = failed_game.seed;
+        game.opening = failed_game.opening_line;
+        games.push(game);
+    }
+
+    let mut arena = AIArena::new(games, Submode::Rerun, max_concurrency, auto_concurrency);
+    arena.observers.push(Box::new(writer));
+
+    Ok(Mode::AIArena(arena))
+}
+
+// Different paths can still point to byte-identical programs, e.g. copies of the same build.
+// They are only considered identical if they are also launched the same way, as options like
+// `env` may be used to run the same program with different settings.
+fn find_identical_engines(configs: &[EngineConfig]) -> Result<Vec<(EngineConfig, PathBuf)>, Error> {
+    let mut seen: Vec<(String, &EngineConfig)> = Vec::new();
+    let mut identical = Vec::new();
+
+    // AIs with a build command may not have been built yet, so they aren't compared
+    for config in configs.iter().filter(|config| config.build.is_none()) {
+        let hash = run::file_hash(&config.path).map_err(|source| Error::Read {
+            what: format!("'{}'", config.path.display()),
+            source,
+        })?;
+
+        let launch_options = |config: &EngineConfig| EngineConfig {
+            path: PathBuf::new(),
+            ..config.clone()
+        };
+
+        let original = seen.iter().find(|(other_hash, other)| {
+            *other_hash == hash && launch_options(other) == launch_options(config)
+        });
+
+        match original {
+            Some((_, original)) => identical.push((config.clone(), original.path.clone())),
+            None => seen.push((hash, config)),
+        }
+    }
+
+    Ok(identical)
+}
+
+fn handle_identical_engines(arena: &mut AIArena, dedupe: bool) -> Result<(), Error> {
+    if arena.identical_engines.is_empty() {
+        return Ok(());
+    }
+
+    for (config, original) in &arena.identical_engines {
+        arena.console.warn(
+            Category::Errors,
+            &format!(
+                "'{}' is identical to '{}', which skews Elo",
+                config.path.display(),
+                original.display()
+            ),
+        );
+    }
+
+    if !dedupe {
+        arena.console.warn(
+            Category::Errors,
+            "Use --dedupe-engines to only keep the first of identical AIs",
+        );
+        return Ok(());
+    }
+
+    let identical: Vec<&EngineConfig> = arena
+        .identical_engines
+        .iter()
+        .map(|(config, _)| config)
+        .collect();
+
+    arena.games.retain(|game| {
+        game.players
+            .iter()
+            .all(|player| !matches!(player, Player::AI(ai) if identical.contains(&&ai.config)))
+    });
+
+    if arena.games.is_empty() {
+        return Err(Error::InvalidAIList(
+            "AI list contains less than two different AIs".to_owned(),
+        ));
+    }
+
+    arena.console.print(
+        Category::GameFlow,
+        &format!(
+            "Excluded {} AI(s) identical to others from the tournament",
+            identical.len()
+        ),
+    );
+
+    Ok(())
+}
+
+// the AIs with a build command, without duplicates
+fn engine_configs(games: &[Game]) -> Vec<EngineConfig> {
+    let mut configs: Vec<EngineConfig> = Vec::new();
+
+    for game in games {
+        for config in game.players.iter().filter_map(Player::config) {
+            if config.build.is_some() && !configs.contains(config) {
+                configs.push(config.clone());
+            }
+        }
+    }
+
+    configs
+}
+
+// Builds the AIs, returning those which failed to build.
+fn prepare_engines(configs: &[EngineConfig], console: &Console) -> Vec<EngineConfig> {
+    let mut failed = Vec::new();
+
+    for config in configs {
+        let path = config.path.display();
+
+        if build::is_up_to_date(config) {
+            console.info(Category::GameFlow, &format!("'{path}' is up to date"));
+            continue;
+        }
+
+        console.info(Category::GameFlow, &format!("Building '{path}'"));
+
+        match build::build(config) {
+            Ok(BuildOutcome::Built(elapsed)) => console.info(
+                Category::GameFlow,
+                &format!("Built '{path}' in {:.1} s", elapsed.as_secs_f32()),
+            ),
+            Ok(BuildOutcome::UpToDate) => {}
+            Err(err) => {
+                console.warn(
+                    Category::Errors,
+                    &format!("Building '{path}' failed: {err}"),
+                );
+                failed.push(config.clone());
+            }
+        }
+    }
+
+    failed
+}
+
+fn run_prepare(arena: &mut AIArena) -> Result<(), Error> {
+    let configs = engine_configs(&arena.games);
+
+    if configs.is_empty() {
+        return Ok(());
+    }
+
+    let failed = prepare_engines(&configs, &arena.console);
+
+    if failed.is_empty() {
+        return Ok(());
+    }
+
+    match arena.submode {
+        Submode::Compare => Err(Error::Build(
+            "Unable to compare AIs, as not all of them could be built".to_owned(),
+        )),
+        Submode::Rerun => Err(Error::Build(
+            "Unable to rerun failed games, as not all AIs could be built".to_owned(),
+        )),
+        Submode::Tournament => {
+            arena.games.retain(|game| {
+                game.players.iter().all(|player| {
+                    !player
+                        .config()
+                        .is_some_and(|config| failed.contains(config))
+                })
+            });
+
+            if arena.games.is_empty() {
+                return Err(Error::Build("Less than two AIs could be built".to_owned()));
+            }
+
+            arena.console.print(
+                Category::GameFlow,
+                &format!(
+                    "Excluded {} AI(s) failing to build from the tournament",
+                    failed.len()
+                ),
+            );
+
+            Ok(())
+        }
+    }
+}
+
+fn run_preflight(arena: &mut AIArena) -> Result<(), Error> {
+    let mut ais: Vec<AI> = Vec::new();
+
+    for game in &arena.games {
+        for player in &game.players {
+            let Player::AI(ai) = player else {
+                continue;
+            };
+
+            if !ais.iter().any(|other| other.config == ai.config) {
+                ais.push(ai.try_clone()?);
+            }
+        }
+    }
+
+    arena
+        .console
+        .info(Category::GameFlow, "Running preflight checks");
+
+    let concurrency = arena
+        .auto_concurrency
+        .as_ref()
+        .map_or(arena.max_concurrency, |auto| auto.current);
+
+    let results = preflight(&mut ais, concurrency);
+
+    let mut failed = Vec::new();
+
+    for (ai, result) in ais.into_iter().zip(results) {
+        match result {
+            Preflight::Passed => {}
+            // it isn't excluded for that, its games will show if it's too slow
+            Preflight::Slow(err) => arena.console.warn(
+                Category::Errors,
+                &format!(
+                    "Preflight of '{}' was too slow: {err}",
+                    ai.config.path.display()
+                ),
+            ),
+            Preflight::Failed(err) => {
+                arena.console.warn(
+                    Category::Errors,
+                    &format!("Preflight of '{}' failed: {err}", ai.config.path.display()),
+                );
+                failed.push(ai.config);
+            }
+        }
+    }
+
+    if failed.is_empty() {
+        return Ok(());
+    }
+
+    match arena.submode {
+        Submode::Compare => Err(Error::Preflight(
+            "Unable to compare AIs, as not all of them passed preflight".to_owned(),
+        )),
+        Submode::Rerun => Err(Error::Preflight(
+            "Unable to rerun failed games, as not all AIs passed preflight".to_owned(),
+        )),
+        Submode::Tournament => {
+            arena.games.retain(|game| {
+                game.players
+                    .iter()
+                    .all(|player| !matches!(player, Player::AI(ai) if failed.contains(&ai.config)))
+            });
+
+            if arena.games.is_empty() {
+                return Err(Error::Preflight(
+                    "Less than two AIs passed preflight".to_owned(),
+                ));
+            }
+
+            arena.console.print(
+                Category::GameFlow,
+                &format!(
+                    "Excluded {} AI(s) failing preflight from the tournament",
+                    failed.len()
+                ),
+            );
+
+            Ok(())
+        }
+    }
+}
+
+enum GameAmountMode {
+    All,
+    Some(usize),
+}
+
+fn read_ai_player(arg_iter: &mut Iter<String>) -> Result<Player, Error> {
+    let player = read_player(arg_iter)?;
+
+    if let Player::Human = player {
+        return Err(Error::HumanNotAccepted);
+    }
+
+    Ok(player)
+}
+
+fn read_player(arg_iter: &mut Iter<String>) -> Result<Player, Error> {
+    let player_arg = read_string(arg_iter, "<player>")?;
+
+    if player_arg.eq_ignore_ascii_case("human") {
+        return Ok(Player::Human);
+    }
+
+    let limit = read_limit(arg_iter)?;
+
+    ai_player(&player_arg, limit, Path::new(""))
+}
+
+// Any player but a human, in the form it is given on the command line, in AI lists and match
+// files. Paths are relative to `base`, the directory of the file the player is given in.
+fn ai_player(player_arg: &str, limit: Limit, base: &Path) -> Result<Player, Error> {
+    if let Some(address) = player_arg.strip_prefix("tcp:") {
+        if !address.contains(':') {
+            return Err(Error::InvalidValue(format!(
+                "Remote AI '{player_arg}' is not in the form tcp:<host>:<port>"
+            )));
+        }
+
+        return Ok(Player::Remote(RemoteEngine::new(address.to_owned(), limit)));
+    }
+
+    if let Some(path) = player_arg.strip_prefix("dylib:") {
+        return Ok(Player::Plugin(PluginPlayer::load(&base.join(path), limit)?));
+    }
+
+    if let Some(name) = player_arg.strip_prefix("builtin:") {
+        return EmbeddedPlayer::builtin(name, limit)
+            .map(Player::Embedded)
+            .ok_or_else(|| {
+                Error::InvalidValue(format!(
+                    "Unknown built-in AI '{name}', expected random or greedy"
+                ))
+            });
+    }
+
+    if let Some(path) = player_arg.strip_prefix("wasm:") {
+        return Ok(Player::Wasm(WasmPlayer::load(&base.join(path), limit)?));
+    }
+
+    if let Some(engine) = player_arg.strip_prefix("bridge:") {
+        let config = read_engine_config(engine)?.relative_to(base);
+
+        run::validate(&config)?;
+
+        return Ok(Player::Bridge(BridgePlayer::new(config, limit)));
+    }
+
+    let config = read_engine_config(player_arg)?.relative_to(base);
+
+    run::validate(&config)?;
+
+    Ok(Player::AI(AI::new(config, limit)))
+}
+
+fn read_limit(arg_iter: &mut Iter<String>) -> Result<Limit, Error> {
+    let arg = read_string(arg_iter, "<max time>")?;
+
+    if let Some(depth) = arg.strip_prefix("depth:") {
+        let depth = handled_parse(depth, "depth in <max time>")?;
+
+        if depth == 0 {
+            return Err(Error::NotPositive("depth".to_owned()));
+        }
+
+        return Ok(Limit::Depth(depth));
+    }
+
+    if let Some(nodes) = arg.strip_prefix("nodes:") {
+        let nodes = handled_parse(nodes, "node count in <max time>")?;
+
+        if nodes == 0 {
+            return Err(Error::NotPositive("node count".to_owned()));
+        }
+
+        return Ok(Limit::Nodes(nodes));
+    }
+
+    let (number, unit_ms) = if let Some(number) = arg.strip_suffix("ms") {
+        (number, 1)
+    } else if let Some(number) = arg.strip_suffix('s') {
+        (number, 1000)
+    } else {
+        (arg.as_str(), 1)
+    };
+
+    let time_limit_ms: u64 = handled_parse::<u64>(number, "<max time>")?
+        .checked_mul(unit_ms)
+        .ok_or_else(|| Error::InvalidValue(format!("<max time> '{arg}' is too long")))?;
+
+    if time_limit_ms == 0 {
+        return Err(Error::NotPositive("<max time>".to_owned()));
+    }
+
+    Ok(Limit::Time(Duration::from_millis(time_limit_ms)))
+}
+
+fn read_engine_config(str: &str) -> Result<EngineConfig, Error> {
+    str.parse().map_err(Error::InvalidEngineConfig)
+}
+
+// from stdin if `path` is -
+fn read_diagram(path: &str) -> Result<Pos, Error> {
+    let what = if path == "-" {
+        "position from stdin".to_owned()
+    } else {
+        format!("position file '{path}'")
+    };
+
+    let content = if path == "-" {
+        io::read_to_string(io::stdin())
+    } else {
+        std::fs::read_to_string(path)
+    }
+    .map_err(|source| Error::Read {
+        what: what.clone(),
+        source,
+    })?;
+
+    let pos = diagram::parse_diagram(&content)
+        .map_err(|err| Error::InvalidValue(format!("Invalid {what}: {err}")))?;
+
+    if pos.next_player == Tile::Empty {
+        return Err(Error::InvalidValue(format!(
+            "Invalid {what}: neither player can move"
+        )));
+    }
+
+    Ok(pos)
+}
+
+fn read_int<T: FromStr>(arg_iter: &mut Iter<String>, what: &str) -> Result<T, Error> {
+    handled_parse(read_string(arg_iter, what)?.as_str(), what)
+}
+
+fn handled_parse<T: FromStr>(str: &str, what: &str) -> Result<T, Error> {
+    str.parse().map_err(|_| Error::InvalidInteger {
+        what: what.to_owned(),
+        value: str.to_owned(),
+    })
+}
+
+// a positive number, or auto, for which the number of physical cores is the maximum
+fn read_max_concurrency(
+    arg_iter: &mut Iter<String>,
+) -> Result<(usize, Option<AutoConcurrency>), Error> {
+    let max_concurrency = read_string(arg_iter, "<max concurrency>")?;
+
+    if max_concurrency.eq_ignore_ascii_case("auto") {
+        let max = system::physical_cores().len();
+        return Ok((max, Some(AutoConcurrency::new(max))));
+    }
+
+    let max_concurrency = handled_parse(&max_concurrency, "<max concurrency>")?;
+
+    if max_concurrency == 0 {
+        return Err(Error::NotPositive("<max concurrency>".to_owned()));
+    }
+
+    Ok((max_concurrency, None))
+}
+
+fn read_string(arg_iter: &mut Iter<String>, what: &str) -> Result<String, Error> {
+    arg_iter
+        .next()
+        .cloned()
+        .ok_or_else(|| Error::MissingArgument(what.to_owned()))
+}
+
+// UPDATE
+
+fn event(app: &App, model: &mut Model, event: Event) {
+    let Event::WindowEvent { id, simple: Some(event) } = event else {
+        return;
+    };
+
+    // the stats window only shows things
+    if id != model.window_id {
+        return;
+    }
+
+    if model.menu_open {
+        handle_menu_event(app, model, event);
+        return;
+    }
+
+    let result = match event {
+        // systems emulating the mouse for touches would play taps twice
+        WindowEvent::MousePressed(MouseButton::Left)
+            if model
+                .touches
+                .last_touch
+                .is_some_and(|at| at.elapsed() < TOUCH_MOUSE_DELAY) =>
+        {
+            Ok(())
+        }
+        WindowEvent::MousePressed(MouseButton::Left) => handle_left_mouse_click(app, model),
+        WindowEvent::Touch(touch) => handle_touch(app, model, touch),
+        WindowEvent::KeyPressed(Key::Z) => handle_undo(model),
+        WindowEvent::KeyPressed(Key::R) => handle_restore(model),
+        WindowEvent::KeyPressed(Key::C) if shortcut_modifier(app) => {
+            handle_copy(model);
+            Ok(())
+        }
+        WindowEvent::KeyPressed(Key::V) if shortcut_modifier(app) => handle_paste(model),
+        WindowEvent::KeyPressed(Key::A) => {
+            match &mut model.mode {
+                Mode::AIArena(arena) => arena.auto_advance = !arena.auto_advance,
+                Mode::Spectate(spectate) => spectate.auto_advance = !spectate.auto_advance,
+                Mode::Visual(_) => {}
+            }
+            Ok(())
+        }
+        WindowEvent::KeyPressed(key @ (Key::Left | Key::Right)) => {
+            let offset = if key == Key::Left { -1 } else { 1 };
+
+            // choosing a game stops moving on from it
+            match &mut model.mode {
+                Mode::AIArena(arena) => {
+                    arena.auto_advance = false;
+                    arena.step(offset);
+                }
+                Mode::Spectate(spectate) => {
+                    spectate.auto_advance = false;
+                    spectate.step(offset);
+                }
+                Mode::Visual(_) => {}
+            }
+            Ok(())
+        }
+        WindowEvent::KeyPressed(Key::F) => {
+            if let Mode::AIArena(arena) = &mut model.mode {
+                arena.view_filter = arena.next_view_filter();
+                arena.console.print(
+                    Category::GameFlow,
+                    &format!("Watching {}", arena.view_filter.description()),
+                );
+            }
+            Ok(())
+        }
+        WindowEvent::KeyPressed(Key::Escape) => {
+            model.menu_open = true;
+            Ok(())
+        }
+        WindowEvent::KeyPressed(Key::Q) => {
+            handle_quit(app, model);
+            Ok(())
+        }
+        WindowEvent::Resized(size) => {
+            handle_resize(app, model, size);
+            Ok(())
+        }
+        _ => Ok(()),
+    };
+
+    result.unwrap_or_else(|err| exit_with_error(err));
+}
+
+// Quits, after asking first if games would be stopped. The shutdown itself is done by `exit`,
+// which also runs when the window is closed.
+fn handle_quit(app: &App, model: &mut Model) {
+    let unfinished = unfinished_games(&model.mode);
+    let confirmed = model
+        .quit_requested
+        .is_some_and(|at| at.elapsed() < QUIT_CONFIRM_TIME);
+
+    if unfinished == 0 || confirmed {
+        app.quit();
+        return;
+    }
+
+    model.quit_requested = Some(Instant::now());
+    model.mode.console().warn(
+        Category::GameFlow,
+        &format!(
+            "{unfinished} games are unfinished, press Q again within {} seconds to stop them and quit",
+            QUIT_CONFIRM_TIME.as_secs()
+        ),
+    );
+}
+
+// the games which would be lost by quitting now
+fn unfinished_games(mode: &Mode) -> usize {
+    match mode {
+        Mode::Visual(visual) => {
+            usize::from(visual.game.history.len() > 1 && !visual.game.is_game_over())
+        }
+        Mode::AIArena(arena) => arena
+            .games
+            .iter()
+            .filter(|game| !game.is_game_over())
+            .count(),
+        // the games are played elsewhere, and go on without the window
+        Mode::Spectate(_) => 0,
+    }
+}
+
+fn handle_resize(app: &App, model: &Model, size: nannou::prelude::Vec2) {
+    if !model.window_options.square || size.x == size.y {
+        return;
+    }
+
+    let window = app.window(model.window_id).expect("Error finding window.");
+
+    // the larger side follows the smaller one, so the window never grows off the screen
+    let side = f32::min(size.x, size.y);
+    window.set_inner_size_points(side, side);
+}
+
+// the settings menu is open, nothing else reacts to input until it is closed
+fn handle_menu_event(app: &App, model: &mut Model, event: WindowEvent) {
+    let item = match event {
+        WindowEvent::KeyPressed(Key::Escape) => {
+            model.menu_open = false;
+            return;
+        }
+        WindowEvent::KeyPressed(Key::Key1) => 0,
+        WindowEvent::KeyPressed(Key::Key2) => 1,
+        WindowEvent::KeyPressed(Key::Key3) => 2,
+        WindowEvent::MousePressed(MouseButton::Left) => {
+            let window = app.window(model.window_id).expect("Error finding window.");
+            let mouse_pos = app.mouse.position();
+
+            match menu_item_areas(&window)
+                .iter()
+                .position(|area| area.contains(mouse_pos))
+            {
+                Some(item) => item,
+                None => return,
+            }
+        }
+        WindowEvent::Resized(size) => {
+            handle_resize(app, model, size);
+            return;
+        }
+        _ => return,
+    };
+
+    let settings = &mut model.settings;
+
+    match item {
+        0 => settings.hints = !settings.hints,
+        1 => settings.theme = settings.theme.next(),
+        _ => settings.undo = settings.undo.next(),
+    }
+
+    if let Err(err) = settings.save() {
+        model.mode.console().warn(
+            Category::Errors,
+            &format!("Unable to save the settings: {err}"),
+        );
+    }
+}
+
+// the lines of the settings menu, in the order of `menu_lines`
+fn menu_item_areas(window: &Window) -> Vec<Rect> {
+    let (width, height) = window.inner_size_points();
+    let line_height = f32::min(width, height) * MENU_SIZE * 2.0;
+
+    (0..MENU_ITEMS)
+        .map(|i| Rect::from_x_y_w_h(0.0, -line_height * i as f32, width, line_height))
+        .collect()
+}
+
+fn menu_lines(settings: &Settings) -> [String; MENU_ITEMS] {
+    let on_off = |on| if on { tr(Text::On) } else { tr(Text::Off) };
+
+    [
+        format!("1  {}: {}", tr(Text::Hints), on_off(settings.hints)),
+        format!("2  {}: {}", tr(Text::Theme), settings.theme.name()),
+        format!("3  {}: {}", tr(Text::Undo), settings.undo.name()),
+    ]
+}
+
+// Ctrl, or Cmd on macOS
+fn shortcut_modifier(app: &App) -> bool {
+    app.keys.mods.ctrl() || app.keys.mods.logo()
+}
+
+fn handle_copy(model: &mut Model) {
+    let text = clipboard::game_text(model.mode.showed_game());
+
+    match model.clipboard.copy(text) {
+        Ok(()) => model
+            .mode
+            .console()
+            .print(Category::GameFlow, tr(Text::CopiedPosition)),
+        Err(err) => model.mode.console().warn(Category::Errors, &err),
+    }
+}
+
+fn handle_paste(model: &mut Model) -> Result<(), Error> {
+    let Mode::Visual(visual) = &mut model.mode else {
+        return Ok(());
+    };
+
+    // the other side couldn't follow
+    if visual
+        .game
+        .players
+        .iter()
+        .any(|player| matches!(player, Player::Network(_)))
+    {
+        visual
+            .console
+            .warn(Category::Errors, "Pasting is not possible in network games");
+        return Ok(());
+    }
+
+    let pasted = model
+        .clipboard
+        .paste()
+        .and_then(|text| clipboard::read_pasted(&text));
+
+    let (start, moves) = match pasted {
+        Ok(pasted) => pasted,
+        Err(err) => {
+            visual.console.warn(Category::Errors, &err);
+            return Ok(());
+        }
+    };
+
+    let mut broadcast = Broadcast {
+        console: &mut visual.console,
+        observers: &mut visual.observers,
+    };
+
+    visual.grace_move = None;
+
+    // invalid moves leave the game as it was
+    match visual.game.load(start, &moves, &mut broadcast) {
+        Err(Error::InvalidValue(err)) => {
+            visual.console.warn(Category::Errors, &err);
+            Ok(())
+        }
+        Err(err) => Err(err),
+        Ok(()) => {
+            visual
+                .console
+                .print(Category::GameFlow, tr(Text::LoadedPosition));
+            Ok(())
+        }
+    }
+}
+
+// the other side of network games couldn't follow
+fn enable_autosave(visual: &mut Visual, args: &[String]) {
+    if !visual
+        .game
+        .players
+        .iter()
+        .any(|player| matches!(player, Player::Network(_)))
+    {
+        visual
+            .observers
+            .push(Box::new(Autosaver::new(args.to_vec())));
+    }
+}
+
+// The game of the last run is kept until the window is closed, as the autosave is overwritten by
+// the first move of this one.
+fn offer_restore(visual: &mut Visual) {
+    if visual
+        .game
+        .players
+        .iter()
+        .any(|player| matches!(player, Player::Network(_)))
+    {
+        return;
+    }
+
+    let saved = match autosave::load() {
+        Ok(Some(saved)) => saved,
+        Ok(None) => return,
+        Err(err) => {
+            visual.console.warn(Category::Errors, &err.to_string());
+            return;
+        }
+    };
+
+    visual.console.pin(format!(
+        "Press R to continue the unfinished game of {} vs {} after {} moves",
+        saved.players[0],
+        saved.players[1],
+        saved.move_count()
+    ));
+    visual.restorable = Some(saved);
+}
+
+fn handle_restore(model: &mut Model) -> Result<(), Error> {
+    let Mode::Visual(visual) = &mut model.mode else {
+        return Ok(());
+    };
+
+    let Some(saved) = visual.restorable.take() else {
+        return Ok(());
+    };
+
+    visual.console.unpin();
+
+    let restored = saved
+        .start_and_moves()
+        .map_err(Error::InvalidValue)
+        .and_then(|(start, moves)| {
+            let (Mode::Visual(mut restored), _) = read_mode(&saved.args)? else {
+                return Err(Error::InvalidValue(
+                    "The autosave isn't of a visual game".to_owned(),
+                ));
+            };
+
+            restored.game.load(
+                start,
+                &moves,
+                &mut Broadcast {
+                    console: &mut restored.console,
+                    observers: &mut restored.observers,
+                },
+            )?;
+
+            Ok(restored)
+        });
+
+    // the game of this run goes on
+    let mut restored = match restored {
+        Ok(restored) => restored,
+        Err(err) => {
+            visual.console.warn(
+                Category::Errors,
+                &format!("Unable to continue the saved game: {err}"),
+            );
+            return Ok(());
+        }
+    };
+
+    enable_autosave(&mut restored, &saved.args);
+    restored
+        .console
+        .print(Category::GameFlow, tr(Text::LoadedPosition));
+
+    *visual = restored;
+
+    Ok(())
+}
+
+fn handle_undo(model: &mut Model) -> Result<(), Error> {
+    let Mode::Visual(visual) = &mut model.mode else {
+        return Ok(());
+    };
+
+    undo_visual(visual, model.settings.undo)
+}
+
+fn undo_visual(visual: &mut Visual, granularity: UndoGranularity) -> Result<(), Error> {
+    visual.grace_move = None;
+
+    // the other side couldn't follow
+    if visual
+        .game
+        .players
+        .iter()
+        .any(|player| matches!(player, Player::Network(_)))
+    {
+        visual
+            .console
+            .warn(Category::Errors, "Undo is not possible in network games");
+        return Ok(());
+    }
+
+    let mut broadcast = Broadcast {
+        console: &mut visual.console,
+        observers: &mut visual.observers,
+    };
+
+    visual.game.undo(granularity, &mut broadcast)
+}
+
+fn handle_left_mouse_click(app: &App, model: &mut Model) -> Result<(), Error> {
+    handle_board_press(app, model, app.mouse.position())
+}
+
+// a tap is a press of one finger which was lifted soon, without moving, a two-finger tap undoes
+fn handle_touch(app: &App, model: &mut Model, touch: TouchEvent) -> Result<(), Error> {
+    let touches = &mut model.touches;
+
+    match touch.phase {
+        TouchPhase::Started => {
+            if touches.down.is_empty() {
+                touches.most = 0;
+                touches.moved = false;
+            }
+
+            touches
+                .down
+                .insert(touch.id, (touch.position, Instant::now()));
+            touches.most = touches.most.max(touches.down.len());
+            Ok(())
+        }
+        TouchPhase::Moved => {
+            if let Some((start, _)) = touches.down.get(&touch.id) {
+                if start.distance(touch.position) > TAP_DISTANCE {
+                    touches.moved = true;
+                }
+            }
+            Ok(())
+        }
+        TouchPhase::Ended | TouchPhase::Cancelled => {
+            let Some((start, at)) = touches.down.remove(&touch.id) else {
+                return Ok(());
+            };
+
+            touches.last_touch = Some(Instant::now());
+
+            if touch.phase == TouchPhase::Cancelled {
+                touches.moved = true;
+            }
+
+            if !touches.down.is_empty() || touches.moved {
+                return Ok(());
+            }
+
+            match touches.most {
+                // long presses only show the hints
+                1 if at.elapsed() < LONG_PRESS => handle_board_press(app, model, start),
+                2 => handle_undo(model),
+                _ => Ok(()),
+            }
+        }
+    }
+}
+
+fn handle_board_press(
+    app: &App,
+    model: &mut Model,
+    position: nannou::prelude::Vec2,
+) -> Result<(), Error> {
+    let Mode::Visual(visual) = &mut model.mode else {
+        return Ok(());
+    };
+
+    let window = app.window(model.window_id).expect("Error finding window.");
+
+    let layout = Layout::new(&window, model.window_options.present);
+
+    let Some(coor) = othello_gui::Vec2::board_iter()
+        .find(|coor| layout.tiles[coor.x as usize][coor.y as usize].contains(position))
+    else {
+        return Ok(());
+    };
+
+    // a misplaced move is taken back by clicking it again, before the grace period is over
+    if visual.grace_move.is_some_and(|(mv, _)| mv == coor) {
+        visual.grace_move = None;
+        return undo_visual(visual, UndoGranularity::Turn);
+    }
+
+    let Some(Player::Human) = visual.game.next_player() else {
+        return Ok(());
+    };
+
+    if visual.game.rules.is_valid_move(&visual.game.pos, coor) {
+        visual.game.play(
+            coor,
+            "human",
+            &mut Broadcast {
+                console: &mut visual.console,
+                observers: &mut visual.observers,
+            },
+        );
+
+        if visual.undo_grace.is_some() {
+            visual.grace_move = Some((coor, Instant::now()));
+        }
+    }
+
+    visual.game.initialize_next_player()
+}
+
+fn update(app: &App, model: &mut Model, _update: Update) {
+    let result = match &mut model.mode {
+        Mode::Visual(visual) => update_visual(visual),
+        Mode::AIArena(arena) => update_ai_arena(arena),
+        Mode::Spectate(spectate) => {
+            update_spectate(spectate);
+            Ok(())
+        }
+    };
+
+    let [x_disks, o_disks] = model.mode.showed_game().disk_counts();
+    let mut title = match &model.mode {
+        // the progress of the whole run, which shows even when the window is minimized
+        Mode::AIArena(arena) => format!("Othello GUI - v{VERSION} - {}", arena_status(arena)),
+        _ => format!("Othello GUI - v{VERSION} - X {x_disks} : O {o_disks}"),
+    };
+
+    if let Some((elapsed, limit)) = model.mode.thinking() {
+        let next_player = model.mode.showed_game().pos.next_player;
+
+        title += &format!(
+            " - {next_player} {} {:.1} s",
+            tr(Text::Thinking),
+            elapsed.as_secs_f32()
+        );
+
+        if let Some(limit) = limit {
+            title += &format!(" / {limit}");
+        }
+    }
+
+    if title != model.title {
+        if let Some(window) = app.window(model.window_id) {
+            window.set_title(&title);
+        }
+
+        model.title = title;
+    }
+
+    if let Err(err) = result {
+        if let Mode::AIArena(arena) = &mut model.mode {
+            arena.console.unpin();
+            arena
+                .finish_hooks
+                .run(&arena.console, false, &err.to_string());
+        }
+
+        exit_with_error(err);
+    }
+}
+
+// the finished and all games, and the AI with the highest score with its lead over the second
+fn arena_status(arena: &AIArena) -> String {
+    let mut status = format!("{}/{}", arena.ended.len(), arena.games.len());
+
+    let Some(point) = arena.score_history.points.last() else {
+        return status;
+    };
+
+    let mut scores: Vec<(&PlayerId, f32)> = point
+        .scores
+        .iter()
+        .map(|(id, &score)| (id, score))
+        .collect();
+    scores.sort_by(|(_, score1), (_, score2)| score2.total_cmp(score1));
+
+    if let [(leader, first), rest @ ..] = scores.as_slice() {
+        let second = rest.first().map_or(0.0, |&(_, score)| score);
+        status += &format!(
+            " - {}: {} {:+}",
+            tr(Text::Leader),
+            arena.score_history.name(leader),
+            first - second
+        );
+    }
+
+    status
+}
+
+fn update_visual(visual: &mut Visual) -> Result<(), Error> {
+    if let (Some((_, played_at)), Some(grace)) = (visual.grace_move, visual.undo_grace) {
+        if played_at.elapsed() > grace {
+            visual.grace_move = None;
+        }
+    }
+
+    // the AI keeps running, only its move is held back until the delay is over
+    let held_back = visual.game.thinking().is_some()
+        && visual
+            .min_move_delay
+            .is_some_and(|delay| visual.shown_moves.1.elapsed() < delay);
+
+    if !held_back {
+        visual.game.update(&mut Broadcast {
+            console: &mut visual.console,
+            observers: &mut visual.observers,
+        })?;
+    }
+
+    if visual.game.history.len() != visual.shown_moves.0 {
+        visual.shown_moves = (visual.game.history.len(), Instant::now());
+    }
+
+    let connected = visual.game.players.iter().find_map(|player| match player {
+        Player::Network(net) => Some(net.is_connected()),
+        _ => None,
+    });
+
+    match connected {
+        Some(true) => visual.console.pin("Connected".to_owned()),
+        Some(false) => visual
+            .console
+            .pin("Waiting for the other player to connect...".to_owned()),
+        None => {}
+    }
+
+    Ok(())
+}
+
+// TERMINAL
+
+fn update_spectate(spectate: &mut Spectate) {
+    let was_connected = spectate.client.is_connected();
+
+    for snapshot in spectate.client.receive() {
+        let Some(pos) = snapshot.pos() else {
+            continue;
+        };
+
+        let id = snapshot.game_id;
+        let last_move = snapshot.last_move();
+
+        let spectated = spectate.games.entry(id).or_insert_with(|| SpectatedGame {
+            game: Game::from_pos(id, [Player::Human, Player::Human], pos),
+            names: snapshot.players.clone().map(|player| player.name),
+        });
+
+        let game = &mut spectated.game;
+
+        if game.history.len() == 1 && same_pos(&game.pos, &pos) {
+            // the first snapshot of a game joined in the middle
+            game.history = vec![(pos, last_move)];
+        } else if !same_pos(&game.pos, &pos) {
+            game.history.push((pos, last_move));
+        }
+
+        game.pos = pos;
+        game.winner = snapshot.winner();
+
+        let showed_running = spectate
+            .showed
+            .and_then(|showed| spectate.games.get(&showed))
+            .is_some_and(|showed| showed.game.winner.is_none());
+
+        if spectate.showed.is_none() || (spectate.auto_advance && !showed_running) {
+            spectate.showed = Some(id);
+        }
+    }
+
+    if was_connected && !spectate.client.is_connected() {
+        spectate.console.warn(
+            Category::Errors,
+            "Lost the connection to the arena, the games are shown as they were",
+        );
+    }
+}
+
+fn play_in_terminal(mut visual: Visual) -> Result<(), Error> {
+    let mut shown_plies = None;
+
+    loop {
+        update_visual(&mut visual)?;
+
+        let plies = visual.game.history.len();
+
+        if visual.game.is_game_over() {
+            print_terminal_board(&visual);
+            return Ok(());
+        }
+
+        if !matches!(visual.game.next_player(), Some(Player::Human)) {
+            thread::sleep(Duration::from_millis(5));
+            continue;
+        }
+
+        if shown_plies != Some(plies) {
+            print_terminal_board(&visual);
+            shown_plies = Some(plies);
+        }
+
+        print!(
+            "{} to move (<move>, undo, hint or quit): ",
+            visual.game.pos.next_player
+        );
+        io::stdout().flush().expect("Error flushing stdout");
+
+        let mut line = String::new();
+
+        let read = io::stdin()
+            .read_line(&mut line)
+            .map_err(|source| Error::Read {
+                what: "move from stdin".to_owned(),
+                source,
+            })?;
+
+        match line.trim().to_lowercase().as_str() {
+            // end of input
+            _ if read == 0 => return Ok(()),
+            "quit" | "q" => return Ok(()),
+            "undo" | "u" => {
+                undo_visual(&mut visual, UndoGranularity::Turn)?;
+                shown_plies = None;
+            }
+            "hint" | "h" => {
+                let moves: Vec<_> = visual
+                    .game
+                    .rules
+                    .valid_moves(&visual.game.pos)
+                    .iter()
+                    .map(|mv| mv.move_string())
+                    .collect();
+
+                println!("Valid moves: {}", moves.join(" "));
+            }
+            input => match net::parse_move(input) {
+                Some(mv) if visual.game.rules.is_valid_move(&visual.game.pos, mv) => {
+                    visual.game.play(
+                        mv,
+                        "human",
+                        &mut Broadcast {
+                            console: &mut visual.console,
+                            observers: &mut visual.observers,
+                        },
+                    );
+                    visual.game.initialize_next_player()?;
+                }
+                Some(_) => println!("'{input}' is not a valid move, type hint to see them"),
+                None => println!("'{input}' is not a move, moves are given like d3"),
+            },
+        }
+    }
+}
+
+fn print_terminal_board(visual: &Visual) {
+    let last_move = visual.game.history.last().expect("history empty").1;
+
+    visual.console.print(
+        Category::GameFlow,
+        &board_diagram(&visual.game.pos, last_move),
+    );
+}
+
+fn update_ai_arena(arena: &mut AIArena) -> Result<(), Error> {
+    if arena
+        .max_duration
+        .is_some_and(|max_duration| arena.started_at.elapsed() >= max_duration)
+        && arena.truncated_from.is_none()
+        // the report needs at least one game
+        && arena.games.iter().any(|game| game.started)
+    {
+        truncate_run(arena);
+    }
+
+    start_games(arena)?;
+
+    if !arena.games[arena.showed_game_idx].is_game_over() {
+        arena.showed_ended = None;
+    } else if arena.showed_ended.map(|(idx, _)| idx) != Some(arena.showed_game_idx) {
+        arena.showed_ended = Some((arena.showed_game_idx, Instant::now()));
+    }
+
+    let result_held = arena
+        .showed_ended
+        .zip(arena.hold_result)
+        .is_some_and(|((_, ended_at), hold)| ended_at.elapsed() < hold);
+
+    let showed = &arena.games[arena.showed_game_idx];
+    let move_on = showed.is_game_over() && !result_held || !arena.view_filter.matches(showed);
+
+    if arena.auto_advance && move_on {
+        if let Some(idx) = arena.next_watched_game() {
+            arena.showed_game_idx = idx;
+        }
+    }
+
+    let mut broadcast = Broadcast {
+        console: &mut arena.console,
+        observers: &mut arena.observers,
+    };
+
+    // the games only change when an AI finished, which is counted after its result is sent, so
+    // results finishing during the loop are seen in the next update
+    let finished_runs = ai::finished_runs();
+    let any_finished = finished_runs != arena.seen_runs;
+    arena.seen_runs = finished_runs;
+
+    for game in arena
+        .games
+        .iter_mut()
+        .filter(|game| any_finished && game.started)
+    {
+        let was_over = game.is_game_over();
+        let moves = game.history.len();
+        let thinking = game.thinking();
+
+        let result = game.update(&mut broadcast);
+        game.forfeit_on_start_failure(result, &mut broadcast)?;
+
+        if let Some(auto) = &mut arena.auto_concurrency {
+            // measured to the poll interval, which is precise enough for averages
+            if let (true, Some((elapsed, Some(limit)))) = (game.history.len() > moves, thinking) {
+                auto.record_move(elapsed, limit.time_limit());
+            }
+
+            if game.failure == Some(Failure::TimeOut) && !was_over {
+                auto.record_timeout();
+            }
+        }
+
+        if !was_over && game.is_game_over() {
+            arena.ended.push(game.id);
+            arena.score_history.record(game, arena.started_at.elapsed());
+        }
+    }
+
+    if let Some(auto) = &mut arena.auto_concurrency {
+        let previous = auto.current;
+
+        if auto.adjust(system::cpu_times()) {
+            let event = ArenaEvent::ConcurrencyChanged {
+                from: previous,
+                to: auto.current,
+            };
+
+            for observer in &mut arena.observers {
+                observer.on_arena_event(&event);
+            }
+
+            arena.console.info(
+                Category::GameFlow,
+                &format!("Playing up to {} games at once", auto.current),
+            );
+        }
+    }
+
+    adjudicate_games(arena);
+
+    let finished = arena
+        .games
+        .iter()
+        .filter(|&game| game.is_game_over())
+        .count();
+
+    match arena.submode {
+        Submode::Compare => report_compare_progress(arena),
+        Submode::Tournament => report_rounds(arena),
+        Submode::Rerun => {}
+    }
+
+    let progress = format!("Games done: {}/{}", finished, arena.games.len());
+
+    let pinned = match standings(arena) {
+        Some(standings) => format!("{progress}\n{standings}"),
+        None => progress,
+    };
+
+    arena.console.pin(pinned);
+
+    if arena.games.iter().all(|game| game.is_game_over()) {
+        if start_final(arena)? {
+            return Ok(());
+        }
+
+        if let Some(planned) = arena.truncated_from {
+            arena.console.print(
+                Category::Results,
+                &format!(
+                    "Truncated by --max-duration, the results are of only {} of the {planned} games",
+                    arena.games.len()
+                ),
+            );
+        }
+
+        let mut headline = match arena.submode {
+            Submode::Compare => finish_compares(arena),
+            Submode::Tournament => finish_tournament(arena),
+            Submode::Rerun => finish_rerun(arena),
+        };
+
+        if let Some(planned) = arena.truncated_from {
+            headline = format!(
+                "{headline} (truncated, {} of {planned} games)",
+                arena.games.len()
+            );
+        }
+
+        if let Some(path) = &arena.report_path {
+            write_arena_report(arena, path);
+        }
+
+        arena.finish_hooks.run(&arena.console, true, &headline);
+
+        ai::exit(0);
+    }
+
+    Ok(())
+}
+
+fn write_arena_report(arena: &AIArena, path: &Path) {
+    let mode = match arena.submode {
+        Submode::Compare => "compare",
+        Submode::Tournament => "tournament",
+        Submode::Rerun => "rerun",
+    };
+
+    let standings = report::standings(&arena.games);
+
+    let report = Report {
+        mode: mode.to_owned(),
+        games: arena.games.len(),
+        crosstable: report::crosstable(&arena.games, &standings),
+        standings,
+        score_history: arena.score_history.points.clone(),
+        rounds: match arena.submode {
+            Submode::Tournament => report::round_standings(&arena.games),
+            _ => Vec::new(),
+        },
+        system: system::SystemInfo::capture(),
+    };
+
+    // the results were already printed, so they aren't lost
+    if let Err(err) = report::write_report(&report, path, arena.report_format) {
+        arena.console.warn(Category::Errors, &err.to_string());
+    }
+}
+
+fn adjudicate_games(arena: &mut AIArena) {
+    let Some(adjudication) = &mut arena.adjudication else {
+        return;
+    };
+
+    let mut broadcast = Broadcast {
+        console: &mut arena.console,
+        observers: &mut arena.observers,
+    };
+
+    for game in arena
+        .games
+        .iter_mut()
+        .filter(|game| game.started && !game.is_game_over())
+    {
+        if game.empty_count() > adjudication.max_empties || adjudication.unsolved.contains(&game.id)
+        {
+            continue;
+        }
+
+        let moves = game.history.len();
+
+        let Some((solved_moves, handle)) = adjudication.solving.get_mut(&game.id) else {
+            let handle = adjudication.solver.start(game.pos);
+            adjudication.solving.insert(game.id, (moves, handle));
+            continue;
+        };
+
+        let Some(result) = handle.check() else {
+            continue;
+        };
+
+        // the game goes on while it is solved, a result for an earlier position is dropped and
+        // the game solved again
+        let solved_moves = *solved_moves;
+        adjudication.solving.remove(&game.id);
+
+        if solved_moves != moves {
+            continue;
+        }
+
+        match result {
+            Ok(winner) => {
+                game.adjudicate(winner, &mut broadcast);
+                arena.ended.push(game.id);
+                arena.score_history.record(game, arena.started_at.elapsed());
+            }
+            Err(err) => {
+                broadcast.console.warn(
+                    Category::Errors,
+                    &format!(
+                        "Unable to solve game #{}, it is played to the end: {err}",
+                        game.id
+                    ),
+                );
+                adjudication.unsolved.push(game.id);
+            }
+        }
+    }
+
+    // the solvers of games which ended in the meantime are stopped
+    adjudication.solving.retain(|id, _| {
+        arena
+            .games
+            .iter()
+            .any(|game| game.id == *id && !game.is_game_over())
+    });
+}
+
+// The games not started yet are dropped, the running ones are played to the end.
+fn truncate_run(arena: &mut AIArena) {
+    let planned = arena.games.len();
+
+    arena.games.retain(|game| game.started);
+    arena.truncated_from = Some(planned);
+
+    let event = ArenaEvent::Truncated {
+        dropped: planned - arena.games.len(),
+    };
+
+    for observer in &mut arena.observers {
+        observer.on_arena_event(&event);
+    }
+    arena.showed_game_idx = arena.showed_game_idx.min(arena.games.len() - 1);
+
+    arena.console.warn(
+        Category::GameFlow,
+        &format!(
+            "--max-duration exceeded, finishing the {} started games of {planned}",
+            arena.games.len()
+        ),
+    );
+}
+
+// Games are started in order, but with --exclusive-engines games whose AIs are already playing
+// are skipped until those games finish, so later games may start first.
+fn start_games(arena: &mut AIArena) -> Result<(), Error> {
+    let ongoing: Vec<&Game> = arena
+        .games
+        .iter()
+        .filter(|&game| game.started && !game.is_game_over())
+        .collect();
+
+    let concurrency = arena
+        .auto_concurrency
+        .as_ref()
+        .map_or(arena.max_concurrency, |auto| auto.current);
+
+    // after lowering the concurrency, the running games are finished first
+    let mut can_start = concurrency.saturating_sub(ongoing.len());
+
+    if let Some(max_load) = arena.max_load {
+        let load = system::load_average().filter(|&load| load > max_load);
+
+        let event = match load {
+            Some(load) if !arena.starts_paused => Some(ArenaEvent::StartsPaused { load }),
+            None if arena.starts_paused => Some(ArenaEvent::StartsResumed),
+            _ => None,
+        };
+
+        if let Some(event) = event {
+            arena.starts_paused = load.is_some();
+
+            for observer in &mut arena.observers {
+                observer.on_arena_event(&event);
+            }
+        }
+
+        if load.is_some() {
+            return Ok(());
+        }
+    }
+
+    let mut busy_engines: Vec<PathBuf> =
+        ongoing.iter().flat_map(|game| engine_paths(game)).collect();
+
+    let mut busy_cores: Vec<usize> = ongoing
+        .iter()
+        .flat_map(|game| &game.players)
+        .filter_map(|player| match player {
+            Player::AI(ai) => ai.pinned_core,
+            _ => None,
+        })
+        .collect();
+
+    let mut broadcast = Broadcast {
+        console: &mut arena.console,
+        observers: &mut arena.observers,
+    };
+
+    for game in &mut arena.games {
+        if can_start == 0 {
+            break;
+        }
+
+        if game.started {
+            continue;
+        }
+
+        let engines = engine_paths(game);
+
+        if arena.exclusive_engines && engines.iter().any(|path| busy_engines.contains(path)) {
+            continue;
+        }
+
+        if let Some(cores) = &arena.pinned_cores {
+            // if there are more games than cores, the rest run unpinned
+            let core = cores
+                .iter()
+                .copied()
+                .find(|core| !busy_cores.contains(core));
+
+            for player in &mut game.players {
+                if let Player::AI(ai) = player {
+                    ai.pinned_core = core;
+                }
+            }
+
+            busy_cores.extend(core);
+        }
+
+        let result = game.initialize(&mut broadcast);
+        game.forfeit_on_start_failure(result, &mut broadcast)?;
+        can_start -= 1;
+        busy_engines.extend(engines);
+    }
+
+    Ok(())
+}
+
+fn pinned_cores(console: &Console, max_concurrency: usize) -> Vec<usize> {
+    if !system::PINNING_SUPPORTED {
+        console.warn(
+            Category::Errors,
+            "Pinning AIs to cores is not supported on this platform, --pin-cores is ignored",
+        );
+        return Vec::new();
+    }
+
+    let cores = system::physical_cores();
+
+    if cores.len() < max_concurrency {
+        console.warn(Category::Errors, &format!(
+            "Only {} physical cores are available for {max_concurrency} concurrent games, games beyond that run unpinned",
+            cores.len()
+        ));
+    }
+
+    cores
+}
+
+fn engine_paths(game: &Game) -> Vec<PathBuf> {
+    game.players
+        .iter()
+        .filter_map(|player| player.engine_path().map(Path::to_owned))
+        .collect()
+}
+
+fn report_compare_progress(arena: &mut AIArena) {
+    let Some(report_every) = arena.report_every else {
+        return;
+    };
+
+    let finished = finished_pairs(&arena.games).len();
+
+    // the final result is printed anyway
+    if finished < arena.reported_pairs + report_every || arena.games.iter().all(Game::is_game_over)
+    {
+        return;
+    }
+
+    arena.reported_pairs = finished;
+
+    for compare in 0..compare_count(arena) {
+        let games: Vec<&Game> = compare_games(arena, compare).collect();
+        let pairs = finished_pairs(games.iter().copied()).len();
+        let scores = compare_scores(games);
+        let (score1, score2) = compare_totals(&scores);
+
+        arena.console.print(
+            Category::Results,
+            &format!(
+                "After {pairs} pairs: score 1: {score1:.1}, score {}: {score2:.1}, {}",
+                second_number(compare),
+                elo_difference(&scores)
+            ),
+        );
+    }
+}
+
+// the standings after every round which ended since the last call, a round being a game of every
+// pairing of AIs
+fn report_rounds(arena: &mut AIArena) {
+    // the final standings are printed anyway
+    if arena.games.iter().all(Game::is_game_over) {
+        return;
+    }
+
+    // a round can only end when a game does
+    let finished = arena
+        .games
+        .iter()
+        .filter(|game| game.is_game_over())
+        .count();
+
+    if finished == arena.rounds_checked_at {
+        return;
+    }
+
+    arena.rounds_checked_at = finished;
+
+    let rounds = report::round_standings(&arena.games);
+
+    for round in rounds.iter().skip(arena.reported_rounds) {
+        let standings: Vec<String> = round
+            .standings
+            .iter()
+            .enumerate()
+            .map(|(place, standing)| {
+                format!(
+                    "{: >3}. {: >5.1} {}",
+                    place + 1,
+                    standing.score,
+                    standing.name
+                )
+            })
+            .collect();
+
+        arena.console.print(
+            Category::Results,
+            &format!(
+                "Standings after round {}:\n{}",
+                round.round,
+                standings.join("\n")
+            ),
+        );
+    }
+
+    arena.reported_rounds = arena.reported_rounds.max(rounds.len());
+}
+
+// the score so far, pinned below the progress
+fn standings(arena: &AIArena) -> Option<String> {
+    if !arena.games.iter().any(Game::is_game_over) {
+        return None;
+    }
+
+    match arena.submode {
+        Submode::Compare => {
+            let scores: Vec<String> = (0..compare_count(arena))
+                .map(|compare| {
+                    let (score1, score2) =
+                        compare_totals(&compare_scores(compare_games(arena, compare)));
+
+                    format!(
+                        "Score 1: {score1:.1}, score {}: {score2:.1}",
+                        second_number(compare)
+                    )
+                })
+                .collect();
+
+            Some(scores.join("\n"))
+        }
+        Submode::Tournament => {
+            let mut scores: Vec<(String, f32)> = Vec::new();
+
+            for game in arena.games.iter().filter(|game| game.is_game_over()) {
+                for (player, tile) in game.players.iter().zip(Tile::opponent_iter()) {
+                    let name = player.name();
+                    let score = game.score_for(tile, Scoring::WinDrawLoss);
+
+                    match scores.iter_mut().find(|(other, _)| *other == name) {
+                        Some((_, total)) => *total += score,
+                        None => scores.push((name, score)),
+                    }
+                }
+            }
+
+            scores.sort_by(|(_, s1), (_, s2)| s2.total_cmp(s1));
+
+            let leading: Vec<String> = scores
+                .iter()
+                .take(STANDINGS_SHOWN)
+                .map(|(name, score)| format!("{name} {score:.1}"))
+                .collect();
+
+            Some(format!("Leading: {}", leading.join(", ")))
+        }
+        Submode::Rerun => None,
+    }
+}
+
+// the number of compares in the arena, more than one in a batch
+fn compare_count(arena: &AIArena) -> usize {
+    arena
+        .games
+        .iter()
+        .map(|game| compare_pair(game).compare + 1)
+        .max()
+        .unwrap_or(1)
+}
+
+// the number of the second AI of a compare, as the AIs of a batch are numbered in the order they
+// were given, the first being 1
+fn second_number(compare: usize) -> usize {
+    compare + 2
+}
+
+fn compare_games(arena: &AIArena, compare: usize) -> impl Iterator<Item = &Game> {
+    arena
+        .games
+        .iter()
+        .filter(move |game| compare_pair(game).compare == compare)
+}
+
+// the scores of the first AI in the finished games
+fn compare_scores<'a>(games: impl IntoIterator<Item = &'a Game>) -> Vec<f32> {
+    games
+        .into_iter()
+        .filter(|game| game.is_game_over())
+        .map(|game| game.score_for(compare_pair(game).first, Scoring::WinDrawLoss))
+        .collect()
+}
+
+// the scores of the first AI in the pairs of which both games are finished
+fn finished_pairs<'a>(games: impl IntoIterator<Item = &'a Game>) -> Vec<[f32; 2]> {
+    let mut pairs: BTreeMap<usize, Vec<f32>> = BTreeMap::new();
+
+    for game in games.into_iter().filter(|game| game.is_game_over()) {
+        let pair = compare_pair(game);
+
+        pairs
+            .entry(pair.id)
+            .or_default()
+            .push(game.score_for(pair.first, Scoring::WinDrawLoss));
+    }
+
+    pairs
+        .into_values()
+        .filter_map(|scores| scores.try_into().ok())
+        .collect()
+}
+
+fn compare_pair(game: &Game) -> Pair {
+    game.pair.expect("compare games should be paired")
+}
+
+// counts of pairs by result for the first AI, e.g. 1.5 is a win and a draw
+fn pair_results(pairs: &[[f32; 2]]) -> String {
+    let names = [
+        (2.0, "win-win"),
+        (1.5, "win-draw"),
+        (1.0, "win-loss"),
+        (0.5, "draw-loss"),
+        (0.0, "loss-loss"),
+    ];
+
+    let mut counts = Vec::new();
+
+    for (total, name) in names {
+        let matching: Vec<_> = pairs
+            .iter()
+            .filter(|pair| pair[0] + pair[1] == total)
+            .collect();
+
+        // a win and a loss scores the same as two draws, but they are different results
+        if total == 1.0 {
+            let draws = matching.iter().filter(|pair| pair[0] == 0.5).count();
+            counts.push(format!("{name}: {}", matching.len() - draws));
+            counts.push(format!("draw-draw: {draws}"));
+        } else {
+            counts.push(format!("{name}: {}", matching.len()));
+        }
+    }
+
+    format!("Pairs for 1: {}", counts.join(", "))
+}
+
+fn compare_totals(scores: &[f32]) -> (f32, f32) {
+    let score1: f32 = scores.iter().sum();
+
+    (score1, scores.len() as f32 - score1)
+}
+
+fn elo_difference(scores: &[f32]) -> String {
+    match elo::difference_estimate(scores) {
+        Some((difference, margin)) => {
+            format!("Elo difference: {difference:+.0} ± {margin:.0} (95%)")
+        }
+        None if !scores.is_empty() && scores.iter().all(|&score| score == 1.0) => {
+            "Elo difference: +∞ (all games won)".to_owned()
+        }
+        None if !scores.is_empty() && scores.iter().all(|&score| score == 0.0) => {
+            "Elo difference: -∞ (all games lost)".to_owned()
+        }
+        None => "Elo difference: not enough games to estimate".to_owned(),
+    }
+}
+
+// in challenge mode, adds the challenger to the ratings file, rated by its score against the
+// champion, whose rating stays the same
+fn rate_challenger(arena: &mut AIArena, scores: &[f32]) {
+    let Some(Challenge {
+        ratings_path,
+        champion,
+    }) = &arena.challenge
+    else {
+        return;
+    };
+
+    // the scores are of the champion
+    let Some(difference) = elo::rating_difference(scores) else {
+        return;
+    };
+
+    let first = compare_pair(&arena.games[0]).first;
+    let challenger = &arena.games[0].players[first.opponent() as usize];
+    let (engine, version) = challenger.engine_and_version();
+
+    let rating = Rating {
+        engine,
+        version,
+        rating: champion.rating - difference,
+        arg: challenger.arg(),
+    };
+
+    arena.console.print(
+        Category::Results,
+        &format!(
+            "Rated {} at Elo {:.0} ({:+.0} against {})",
+            challenger.name(),
+            rating.rating,
+            -difference,
+            champion.engine
+        ),
+    );
+
+    let snapshot = RatingSnapshot::now(vec![champion.clone(), rating]);
+
+    if let Err(err) = ratings::append_snapshot(ratings_path, &snapshot) {
+        arena.console.warn(Category::Errors, &err.to_string());
+    }
+}
+
+// returns the headline of the results, of every compare of a batch, which are reported one after
+// the other and then summarized
+fn finish_compares(arena: &mut AIArena) -> String {
+    let count = compare_count(arena);
+
+    if count == 1 {
+        return finish_compare(arena, second_number(0));
+    }
+
+    let mut batch: Vec<Vec<Game>> = (0..count).map(|_| Vec::new()).collect();
+
+    for game in std::mem::take(&mut arena.games) {
+        batch[compare_pair(&game).compare].push(game);
+    }
+
+    let mut summary = Vec::new();
+
+    for (compare, games) in batch.iter_mut().enumerate() {
+        // every game of a compare may have been dropped by --max-duration
+        if games.is_empty() {
+            continue;
+        }
+
+        arena.games = std::mem::take(games);
+
+        let first = compare_pair(&arena.games[0]).first;
+        let opponent = arena.games[0].players[first.opponent() as usize].name();
+
+        arena.console.print(
+            Category::Results,
+            &format!(
+                "1 vs {}: {} vs {opponent}",
+                second_number(compare),
+                arena.games[0].players[first as usize].name()
+            ),
+        );
+
+        finish_compare(arena, second_number(compare));
+
+        let scores = compare_scores(&arena.games);
+        let (score1, score2) = compare_totals(&scores);
+
+        summary.push(format!(
+            "1 vs {}: {score1:.1} - {score2:.1} against {opponent}, {}",
+            second_number(compare),
+            elo_difference(&scores)
+        ));
+
+        *games = std::mem::take(&mut arena.games);
+    }
+
+    arena.games = batch.into_iter().flatten().collect();
+
+    arena
+        .console
+        .print(Category::Results, "Summary of the compares:");
+
+    for line in &summary {
+        arena.console.print(Category::Results, line);
+    }
+
+    summary.join("; ")
+}
+
+// returns the headline of the result
+fn finish_compare(arena: &mut AIArena, second: usize) -> String {
+    arena.console.unpin();
+
+    let scores = compare_scores(&arena.games);
+    let (score1, score2) = compare_totals(&scores);
+
+    let first = compare_pair(&arena.games[0]).first;
+    let (player1, player2) = (
+        &arena.games[0].players[first as usize],
+        &arena.games[0].players[first.opponent() as usize],
+    );
+    let (limit1, limit2) = (player1.limit(), player2.limit());
+
+    if limit1 != limit2 {
+        let label = |player: &Player, limit: Option<Limit>| match limit {
+            Some(limit) => format!("{} ({limit})", player.name()),
+            None => player.name(),
+        };
+
+        arena.console.print(
+            Category::Results,
+            &format!(
+                "Odds: 1: {}, {second}: {}",
+                label(player1, limit1),
+                label(player2, limit2)
+            ),
+        );
+    }
+
+    let headline = format!("Score 1: {score1:.1}, score {second}: {score2:.1}");
+
+    arena.console.print(Category::Results, &headline);
+
+    if let Some(scoring) = arena.disk_scoring {
+        let (disks1, disks2) = arena
+            .games
+            .iter()
+            .fold((0.0, 0.0), |(disks1, disks2), game| {
+                let first = compare_pair(game).first;
+
+                (
+                    disks1 + game.score_for(first, scoring),
+                    disks2 + game.score_for(first.opponent(), scoring),
+                )
+            });
+
+        arena.console.print(
+            Category::Results,
+            &format!("Disks 1: {disks1:.1}, disks {second}: {disks2:.1}"),
+        );
+    }
+    arena
+        .console
+        .print(Category::Results, &elo_difference(&scores));
+    arena.console.print(
+        Category::Results,
+        &pair_results(&finished_pairs(&arena.games)),
+    );
+
+    rate_challenger(arena, &scores);
+
+    report_openings(arena);
+
+    let label = |game: &Game, tile| {
+        if tile == compare_pair(game).first {
+            "1".to_owned()
+        } else {
+            second.to_string()
+        }
+    };
+
+    report_failures(arena, label);
+    report_move_quality(arena, label);
+
+    headline
+}
+
+// the score of the first AI by opening, the most one-sided openings first
+fn report_openings(arena: &AIArena) {
+    let mut openings: HashMap<String, (usize, f32)> = HashMap::new();
+
+    for game in &arena.games {
+        let Some(opening) = &game.opening else {
+            continue;
+        };
+
+        let line = if opening.is_empty() {
+            "start".to_owned()
+        } else {
+            opening.iter().map(|mv| mv.move_string()).collect()
+        };
+
+        let (games, score) = openings.entry(line).or_default();
+        *games += 1;
+        *score += game.score_for(compare_pair(game).first, Scoring::WinDrawLoss);
+    }
+
+    // with a depth of 0 every game is from the same position
+    if openings.len() < 2 {
+        return;
+    }
+
+    let deviation = |&(games, score): &(usize, f32)| (score - games as f32 / 2.0).abs();
+
+    let mut openings: Vec<_> = openings.into_iter().collect();
+    openings.sort_by(|(line1, result1), (line2, result2)| {
+        deviation(result2)
+            .partial_cmp(&deviation(result1))
+            .unwrap()
+            .then_with(|| line1.cmp(line2))
+    });
+
+    arena.console.print(
+        Category::Results,
+        &format!("{: <12} {: >5} {: >7}", "Opening", "Games", "Score 1"),
+    );
+
+    for (line, (games, score)) in openings {
+        // every game of the opening was won by the same AI
+        let one_sided = score == 0.0 || score == games as f32;
+
+        arena.console.print(
+            Category::Results,
+            &format!(
+                "{line: <12} {games: >5} {score: >7.1}{}",
+                if one_sided { "  one-sided" } else { "" }
+            ),
+        );
+    }
+}
+
+// returns the headline of the result
+fn finish_tournament(arena: &mut AIArena) -> String {
+    arena.console.unpin();
+
+    if arena
+        .groups
+        .as_ref()
+        .is_some_and(|groups| groups.group_games.is_some())
+    {
+        arena.console.print(Category::Results, "Final:");
+    }
 
-                let mut rng = rand::thread_rng();
+    let mut scores: HashMap<PlayerId, f32> = HashMap::new();
+    // score and number of games with X and with O, as the first player may have an advantage
+    let mut color_scores: HashMap<PlayerId, [(f32, usize); 2]> = HashMap::new();
+    let mut disks: HashMap<PlayerId, f32> = HashMap::new();
+    // how the players are shown, which need not tell them apart
+    let mut names: HashMap<PlayerId, String> = HashMap::new();
+    // the engines and versions of the players, for the ratings
+    let mut engines: HashMap<PlayerId, (String, Option<String>)> = HashMap::new();
+    let mut args: HashMap<PlayerId, Option<String>> = HashMap::new();
 
-                possible_starts
-                    .into_iter()
-                    .choose_multiple(&mut rng, pairs_of_games)
+    for game in &arena.games {
+        for (player, tile) in game.players.iter().zip(Tile::opponent_iter()) {
+            let score = game.score_for(tile, Scoring::WinDrawLoss);
+            let id = player.id();
+
+            *scores.entry(id.clone()).or_insert(0.0) += score;
+            names.entry(id.clone()).or_insert_with(|| player.name());
+            engines
+                .entry(id.clone())
+                .or_insert_with(|| player.engine_and_version());
+            args.entry(id.clone()).or_insert_with(|| player.arg());
+
+            if let Some(scoring) = arena.disk_scoring {
+                *disks.entry(id.clone()).or_insert(0.0) += game.score_for(tile, scoring);
             }
-        }
-    };
 
-    for (i, &start) in starts.iter().enumerate() {
-        let players1 = [player_a.try_clone().unwrap(), player_b.try_clone().unwrap()];
-        let players2 = [player_b.try_clone().unwrap(), player_a.try_clone().unwrap()];
-
-        games.push(Game::from_pos(i * 2, players1, start));
-        games.push(Game::from_pos(i * 2 + 1, players2, start));
+            let (color_score, games) = &mut color_scores.entry(id).or_default()[tile as usize];
+            *color_score += score;
+            *games += 1;
+        }
     }
 
-    Mode::AIArena(AIArena {
-        games,
-        showed_game_idx: 0,
-        first_unstarted: 0,
-        max_concurrency,
-        console: Console::new(Level::Info),
-        submode: Submode::Compare,
-    })
-}
-
-fn handle_tournament_mode(arg_iter: &mut Iter<String>) -> Mode {
-    let ai_list_path_string = read_string(arg_iter, "<ai list>");
-    let ai_list_path_path: PathBuf = ai_list_path_string.clone().into();
-    let time_limit = Duration::from_millis(read_int(arg_iter, "<max time>"));
-    let max_concurrency = read_int(arg_iter, "<max concurrency>");
+    let elos = elo::from_single_tournament(
+        &arena
+            .games
+            .iter()
+            .map(|game| elo::Game {
+                players: [game.players[0].id(), game.players[1].id()],
+                score: game.score_for(Tile::X, Scoring::WinDrawLoss),
+            })
+            .collect::<Vec<_>>(),
+        50,
+        16.0,
+    );
 
-    let ai_paths: Vec<PathBuf> = std::fs::read_to_string(ai_list_path_string)
-        .unwrap_or_else(|err| {
-            eprintln!("Unable to read <ai list>: {err}");
-            process::exit(16);
-        })
-        .trim()
-        .lines()
-        .map(|ln| {
-            let mut base_path: PathBuf = ai_list_path_path.parent().unwrap().to_owned();
-            let extend: PathBuf = ln.trim().to_owned().into();
+    let mut scores: Vec<_> = scores.into_iter().collect();
+    scores.sort_by(|(_, s1), (_, s2)| s2.partial_cmp(s1).unwrap());
 
-            base_path.push(extend);
+    // the disk scores are only shown if asked for
+    let disks_column = |disks: String| match arena.disk_scoring {
+        Some(_) => format!(" {disks: >6}"),
+        None => String::new(),
+    };
 
-            base_path
-        })
-        .collect();
+    arena.console.print(
+        Category::Results,
+        &format!(
+            "{: >4} {: >5}{} {: >9} {: >9} AI",
+            "Elo",
+            "Score",
+            disks_column("Disks".to_owned()),
+            "As X",
+            "As O"
+        ),
+    );
 
-    if ai_paths.is_empty() {
-        eprintln!("AI list file is empty");
-        process::exit(19);
-    }
+    for (id, score) in &scores {
+        let [as_x, as_o] = color_scores[id].map(|(score, games)| format!("{score:.1}/{games}"));
+        let disks = disks_column(format!("{:.1}", disks.get(id).copied().unwrap_or(0.0)));
 
-    if ai_paths.len() == 1 {
-        eprintln!(
-            "AI list only contains one element: '{}'",
-            ai_paths[0].to_string_lossy()
+        arena.console.print(
+            Category::Results,
+            &format!(
+                "{: >4.0} {: >5.1}{disks} {as_x: >9} {as_o: >9} {}",
+                elos[id], score, names[id],
+            ),
         );
-        process::exit(19);
     }
 
-    for path in &ai_paths {
-        if !path.exists() {
-            eprintln!("Path '{}' is not valid", path.display());
-            process::exit(17);
-        }
+    let x_score: f32 = arena
+        .games
+        .iter()
+        .map(|game| game.score_for(Tile::X, Scoring::WinDrawLoss))
+        .sum();
+
+    arena.console.print(
+        Category::Results,
+        &format!(
+            "X scored {x_score:.1} of {} games ({:.1}%)",
+            arena.games.len(),
+            x_score / arena.games.len() as f32 * 100.0
+        ),
+    );
+
+    report_failures(arena, |game, tile| game.players[tile as usize].name());
+    report_move_quality(arena, |game, tile| game.players[tile as usize].name());
+
+    if let Some(path) = &arena.ratings_path {
+        let snapshot = RatingSnapshot::now(
+            elos.iter()
+                .map(|(id, &elo)| Rating {
+                    engine: engines[id].0.clone(),
+                    version: engines[id].1.clone(),
+                    rating: elo,
+                    arg: args[id].clone(),
+                })
+                .collect(),
+        );
 
-        if path.is_dir() {
-            eprintln!("Path '{}' points to something not a file", path.display());
+        // the results were already printed, so they aren't lost
+        if let Err(err) = ratings::append_snapshot(path, &snapshot) {
+            arena.console.warn(Category::Errors, &err.to_string());
         }
     }
 
-    if !has_unique_elements(ai_paths.clone()) {
-        eprintln!("AI list contains duplicate elements");
-        process::exit(20);
-    }
+    let (winner, score) = &scores[0];
 
-    let mut games = Vec::new();
+    format!(
+        "Winner: {} with score {score:.1} (Elo {:.0})",
+        names[winner], elos[winner]
+    )
+}
 
-    let mut id = 0;
+// returns the headline of the result
+fn finish_rerun(arena: &mut AIArena) -> String {
+    arena.console.unpin();
 
-    for (i, path_1) in ai_paths.iter().enumerate() {
-        for path_2 in &ai_paths[i + 1..] {
-            let player_1 = Player::AI(AI::new(path_1.clone(), time_limit));
-            let player_2 = Player::AI(AI::new(path_2.clone(), time_limit));
+    let failed_again = arena
+        .games
+        .iter()
+        .filter(|game| game.failure.is_some())
+        .count();
 
-            games.push(Game::new(
-                id,
-                [player_1.try_clone().unwrap(), player_2.try_clone().unwrap()],
-            ));
-            id += 1;
+    let headline = format!(
+        "Reran {} failed games, {failed_again} failed again",
+        arena.games.len()
+    );
 
-            games.push(Game::new(
-                id,
-                [player_2.try_clone().unwrap(), player_1.try_clone().unwrap()],
-            ));
-            id += 1;
-        }
-    }
+    arena.console.print(Category::Results, &headline);
 
-    Mode::AIArena(AIArena {
-        games,
-        showed_game_idx: 0,
-        first_unstarted: 0,
-        max_concurrency,
-        console: Console::new(Level::Info),
-        submode: Submode::Tournament,
-    })
-}
+    report_failures(arena, |game, tile| game.players[tile as usize].name());
 
-enum GameAmountMode {
-    All,
-    Some(usize),
+    headline
 }
 
-fn read_ai_player(arg_iter: &mut Iter<String>) -> Player {
-    let player = read_player(arg_iter);
+// prints how many games every engine lost by failing, if any did
+fn report_failures(arena: &AIArena, label: impl Fn(&Game, Tile) -> String) {
+    let mut failures: BTreeMap<String, BTreeMap<Failure, usize>> = BTreeMap::new();
 
-    if let Player::Human = player {
-        eprintln!("Human player is not accepted");
-        process::exit(9);
+    for game in &arena.games {
+        let (Some(failure), Some(winner)) = (game.failure, game.winner) else {
+            continue;
+        };
+
+        *failures
+            .entry(label(game, winner.opponent()))
+            .or_default()
+            .entry(failure)
+            .or_default() += 1;
     }
 
-    player
+    for (engine, counts) in failures {
+        let counts: Vec<_> = counts
+            .into_iter()
+            .map(|(failure, count)| format!("{failure}: {count}"))
+            .collect();
+
+        arena.console.print(
+            Category::Results,
+            &format!("Failures of {engine}: {}", counts.join(", ")),
+        );
+    }
 }
 
-fn read_player(arg_iter: &mut Iter<String>) -> Player {
-    let player_arg = read_string(arg_iter, "<player>");
+// prints how well every engine played according to the reference engine, if one was given
+fn report_move_quality(arena: &mut AIArena, label: impl Fn(&Game, Tile) -> String) {
+    let Some(reference) = &arena.reference else {
+        return;
+    };
 
-    match player_arg.to_lowercase().as_str() {
-        "human" => Player::Human,
-        path => {
-            let time_limit_ms = read_int(arg_iter, "<max time>");
+    arena.console.print(
+        Category::Results,
+        &format!("Analyzing the games with {}", reference.config),
+    );
 
-            if time_limit_ms == 0 {
-                eprintln!("<max time> must be positive");
-                process::exit(14);
+    let games: Vec<&Game> = arena.games.iter().collect();
+
+    let qualities =
+        match analysis::analyze(reference, &games, arena.max_concurrency, &mut arena.console) {
+            Ok(qualities) => qualities,
+            Err(err) => {
+                arena.console.warn(
+                    Category::Errors,
+                    &format!("Unable to analyze the games: {err}"),
+                );
+                return;
             }
+        };
 
-            let time_limit = Duration::from_millis(time_limit_ms);
+    // by engine, then by phase
+    let mut totals: BTreeMap<String, [(MoveQuality, TimeUsage); 3]> = BTreeMap::new();
 
-            // TODO: this is unused
-            let mut base_path = env::current_dir().expect("error getting current path");
-            base_path.push(path);
+    for (game, quality) in arena.games.iter().zip(&qualities) {
+        for tile in [Tile::X, Tile::O] {
+            let times = analysis::time_by_phase(game, tile);
+            let total = totals.entry(label(game, tile)).or_default();
 
-            if !base_path.is_file() {
-                if base_path.exists() {
-                    eprintln!(
-                        "Path '{}' points to something not a file",
-                        base_path.display()
-                    );
-                    process::exit(15);
-                } else {
-                    eprintln!("Path '{}' is not valid", base_path.display());
-                    process::exit(16);
-                }
+            for phase in Phase::ALL {
+                let (total_quality, total_time) = &mut total[phase as usize];
+                total_quality.add(&quality[tile as usize][phase as usize]);
+                total_time.add(&times[phase as usize]);
             }
-
-            Player::AI(AI::new(path.into(), time_limit))
         }
     }
-}
 
-fn read_int<T: FromStr>(arg_iter: &mut Iter<String>, what: &str) -> T {
-    handled_parse(read_string(arg_iter, what).as_str(), what)
-}
+    for (engine, phases) in totals {
+        let mut quality = MoveQuality::default();
 
-fn handled_parse<T: FromStr>(str: &str, what: &str) -> T {
-    str.parse().unwrap_or_else(|_| {
-        eprintln!("Error converting {what} to integer, which is '{str}'");
-        process::exit(12);
-    })
+        for (phase_quality, _) in &phases {
+            quality.add(phase_quality);
+        }
+
+        let text = if quality.moves == 0 {
+            format!("Move quality of {engine}: no moves analyzed")
+        } else {
+            format!(
+                "Move quality of {engine}: average loss {:.2} discs, {} blunders, accuracy {:.1}% ({} moves)",
+                quality.average_loss(),
+                quality.blunders,
+                quality.accuracy(),
+                quality.moves
+            )
+        };
+
+        arena.console.print(Category::Results, &text);
+
+        for (phase, (quality, time)) in Phase::ALL.into_iter().zip(phases) {
+            if quality.moves == 0 && time.moves == 0 {
+                continue;
+            }
+
+            let mut parts = Vec::new();
+
+            if quality.moves > 0 {
+                parts.push(format!(
+                    "average loss {:.2} discs, blunder rate {:.1}%",
+                    quality.average_loss(),
+                    quality.blunder_rate()
+                ));
+            }
+
+            if time.moves > 0 {
+                parts.push(format!("{} ms per move", time.average().as_millis()));
+            }
+
+            arena.console.print(
+                Category::Results,
+                &format!(
+                    "  in the {phase}: {} ({} moves)",
+                    parts.join(", "),
+                    quality.moves.max(time.moves)
+                ),
+            );
+        }
+    }
 }
 
-fn read_string(arg_iter: &mut Iter<String>, what: &str) -> String {
-    arg_iter
-        .next()
-        .unwrap_or_else(|| {
-            eprintln!("Unexpected end of arguemtns, expected {what}");
-            process::exit(11);
-        })
-        .clone()
+fn exit(_app: &App, mut model: Model) {
+    shutdown(&mut model.mode);
 }
 
-// UPDATE
+// The window was closed or Q was pressed. The AIs are stopped first, so that no moves arrive while
+// the unfinished run is recorded. Results, captures and event logs are written as the games go, so
+// only the interruption and the report of the finished games are left.
+fn shutdown(mode: &mut Mode) {
+    kill_all_children();
 
-fn event(app: &App, model: &mut Model, event: Event) {
-    let Event::WindowEvent { id: _, simple: Some(event) } = event else {
+    let unfinished = unfinished_games(mode);
+
+    let Mode::AIArena(arena) = mode else {
         return;
     };
 
-    match event {
-        WindowEvent::MousePressed(MouseButton::Left) => handle_left_mouse_click(app, model),
-        WindowEvent::KeyPressed(Key::Z) => handle_undo(model),
-        _ => {}
+    if unfinished == 0 {
+        return;
     }
-}
 
-fn handle_undo(model: &mut Model) {
-    let Mode::Visual(visual) = &mut model.mode else {
-        return;
-    };
+    let event = ArenaEvent::Interrupted { unfinished };
+
+    for observer in &mut arena.observers {
+        observer.on_arena_event(&event);
+    }
+
+    arena.console.warn(
+        Category::GameFlow,
+        &format!(
+            "Stopped with {unfinished} of {} games unfinished",
+            arena.games.len()
+        ),
+    );
 
-    visual.game.undo(&visual.console);
+    if let Some(path) = arena.report_path.clone() {
+        write_arena_report(arena, &path);
+    }
 }
 
-fn handle_left_mouse_click(app: &App, model: &mut Model) {
-    let Mode::Visual(visual) = &mut model.mode else {
-        return;
-    };
+// VIEW
 
-    let Some(Player::Human) = visual.game.next_player() else {
-        return;
-    };
+const TRANSPARENT: Rgba8 = rgba8(0, 0, 0, 0);
+const THINKING_DIM_COLOR: Rgba8 = rgba8(0, 0, 0, 40);
+const RESULT_DIM_COLOR: Rgba8 = rgba8(0, 0, 0, 120);
+const MENU_DIM_COLOR: Rgba8 = rgba8(0, 0, 0, 200);
+// the part of the smaller side of the window used, leaving a thin border around the board
+const WINDOW_FRACTION: f32 = 0.95;
+// the part of the used side taken by the board, the rest is for labels
+const BOARD_FRACTION: f32 = 0.9;
+// the height of the names and the score in presentation mode, relative to the window
+const SCORE_BAND_FRACTION: f32 = 0.15;
+// text sizes, relative to the smaller side of the window
+const LABEL_SIZE: f32 = 0.03;
+const RESULT_SIZE: f32 = 0.08;
+const SCORE_SIZE: f32 = 0.1;
+const MENU_SIZE: f32 = 0.045;
+// the part of the smaller side of the window taken by the size of hints
+const HINT_SIZE: f32 = 0.015;
+// touches held at least this long show the hints instead of playing a move
+const LONG_PRESS: Duration = Duration::from_millis(500);
+// in points, touches moving farther aren't taps
+const TAP_DISTANCE: f32 = 20.0;
+// pressing Q again within this time quits even though games are unfinished
+const QUIT_CONFIRM_TIME: Duration = Duration::from_secs(3);
+// mouse presses this soon after a touch are taken as emulated by the system
+const TOUCH_MOUSE_DELAY: Duration = Duration::from_millis(500);
+// in points, below which text isn't readable
+const MIN_FONT_SIZE: u32 = 10;
+const TILE_STROKE_WEIGHT: f32 = 5.0;
+// the width of the rings of the accessible theme, relative to the disks
+const RING_FRACTION: f32 = 0.2;
+// in points, below which the tiles would be too small to click
+const MIN_WINDOW_SIZE: u32 = 240;
+const REPRODUCE_POLL_INTERVAL: Duration = Duration::from_millis(10);
+// the defaults of the render mode
+const RENDER_SIZE: u32 = 480;
+const RENDER_DELAY: Duration = Duration::from_secs(1);
+const STATS_WINDOW_SIZE: (u32, u32) = (480, 640);
+const STATS_PADDING: f32 = 20.0;
+const STATS_TEXT_SIZE: f32 = 0.03;
+const STATS_RECENT_GAMES: usize = 10;
+// the part of the stats window taken by the graph of the scores, and by the names next to it
+const STATS_GRAPH_FRACTION: f32 = 0.35;
+const STATS_GRAPH_LABEL_FRACTION: f32 = 0.25;
+const STATS_GRAPH_WEIGHT: f32 = 2.0;
+const STATS_GRAPH_TEXT_SIZE: f32 = 0.022;
+// the number of AIs pinned below the progress of tournaments
+const STANDINGS_SHOWN: usize = 3;
+const MENU_ITEMS: usize = 3;
 
+fn view(app: &App, model: &Model, frame: Frame) {
     let window = app.window(model.window_id).expect("Error finding window.");
-    let mouse_pos = app.mouse.position();
+    let game = model.mode.showed_game();
 
-    let rects = Model::get_rects(&window);
+    let palette = model.palette();
 
-    for coor in othello_gui::Vec2::board_iter() {
-        if !rects[coor.x as usize][coor.y as usize].contains(mouse_pos) {
-            continue;
-        }
+    let draw = app.draw();
+    draw.background().color(palette.background);
+
+    let present = model.window_options.present;
+    let layout = Layout::new(&window, present);
 
-        if visual.game.pos.is_valid_move(coor) {
-            visual.game.play(coor, "human", &visual.console);
+    // the valid moves are only marked for humans, and not on the other side of a network game
+    let show_hints = (model.settings.hints || model.touches.is_long_press())
+        && matches!(model.mode, Mode::Visual(_))
+        && matches!(game.next_player(), Some(Player::Human));
+
+    for x in 0..8 {
+        for y in 0..8 {
+            draw_tile(x, y, game, &layout.tiles, &draw, &palette);
+
+            let coor = othello_gui::Vec2::new(x as isize, y as isize);
+
+            if show_hints && game.rules.is_valid_move(&game.pos, coor) {
+                let (width, height) = window.inner_size_points();
+                let radius = f32::min(width, height) * HINT_SIZE;
+
+                draw.ellipse()
+                    .xy(layout.tiles[x][y].xy())
+                    .radius(radius)
+                    .color(palette.move_highlight);
+            }
         }
-        break;
     }
 
-    visual.game.initialize_next_player(&visual.console);
-}
+    for i in (0..8).filter(|_| !present) {
+        let (column, row) = (char::from(b'a' + i as u8).to_string(), (i + 1).to_string());
 
-fn update(_app: &App, model: &mut Model, _update: Update) {
-    match &mut model.mode {
-        Mode::AIArena(arena) => update_ai_arena(arena),
-        _ => {}
+        let (column_area, row_area) = (layout.column_label(i), layout.row_label(i));
+
+        text(
+            &draw,
+            &window,
+            &column,
+            column_area,
+            LABEL_SIZE,
+            TextAnchor::Center,
+            palette.light,
+        );
+        text(
+            &draw,
+            &window,
+            &row,
+            row_area,
+            LABEL_SIZE,
+            TextAnchor::Center,
+            palette.light,
+        );
     }
-}
 
-fn update_ai_arena(arena: &mut AIArena) {
-    let ongoing = arena.games[..arena.first_unstarted]
-        .iter()
-        .filter(|&game| !game.is_game_over())
-        .count();
-    let can_start = arena.max_concurrency - ongoing;
+    let thinking = model.mode.thinking();
 
-    let model_games_len = arena.games.len();
-    for game in arena.games
-        [arena.first_unstarted..(arena.first_unstarted + can_start).min(model_games_len)]
-        .iter_mut()
-    {
-        game.initialize(&arena.console);
-        arena.first_unstarted += 1;
+    if thinking.is_some() {
+        draw.rect()
+            .xy(layout.board.xy())
+            .wh(layout.board.wh())
+            .color(THINKING_DIM_COLOR);
     }
 
-    if arena.games[arena.showed_game_idx].is_game_over() {
-        arena.showed_game_idx = arena.first_unstarted - 1;
+    if present {
+        draw_score_band(&draw, &window, game, model.mode.player_names(), &palette);
+    } else {
+        let hud = match &model.mode {
+            Mode::Spectate(spectate) => {
+                format!("{}   {}", spectate.hud_prefix(), hud_text(game, thinking))
+            }
+            _ => hud_text(game, thinking),
+        };
+
+        text(
+            &draw,
+            &window,
+            &hud,
+            layout.hud(),
+            LABEL_SIZE,
+            TextAnchor::Center,
+            palette.light,
+        );
     }
 
-    for game in arena.games[..arena.first_unstarted].iter_mut() {
-        game.update(&arena.console);
+    // games of the arena are followed by the next one, unless shown in presentation mode or held
+    // with --hold-result
+    let show_result = present
+        || match &model.mode {
+            Mode::AIArena(arena) => arena.hold_result.is_some(),
+            Mode::Visual(_) | Mode::Spectate(_) => true,
+        };
+
+    if let Some(winner) = game.winner.filter(|_| show_result) {
+        draw.rect()
+            .xy(layout.board.xy())
+            .wh(layout.board.wh())
+            .color(RESULT_DIM_COLOR);
+
+        let [x_disks, o_disks] = game.disk_counts();
+        let result = match winner {
+            Tile::Empty => tr(Text::Draw).to_owned(),
+            winner => format!("{winner} {}", tr(Text::Wins)),
+        };
+
+        text(
+            &draw,
+            &window,
+            &format!("{result}\nX {x_disks} : O {o_disks}"),
+            layout.board,
+            RESULT_SIZE,
+            TextAnchor::Center,
+            palette.light,
+        );
     }
 
-    let finished = arena.games[..arena.first_unstarted]
-        .iter()
-        .filter(|&game| game.is_game_over())
-        .count();
+    if model.menu_open {
+        draw_menu(&draw, &window, &model.settings, &palette);
+    }
 
-    arena
-        .console
-        .pin(format!("Games done: {}/{}", finished, arena.games.len()));
+    //draw.rect().stroke(WHITE).stroke_weight(3.0).color(Color::TRANSPARENT);
 
-    if arena.games.iter().all(|game| game.is_game_over()) {
-        match arena.submode {
-            Submode::Compare => finish_compare(arena),
-            Submode::Tournament => finish_tournament(arena),
+    draw.to_frame(app, &frame).unwrap();
+}
+
+fn draw_menu(draw: &Draw, window: &Window, settings: &Settings, palette: &Palette) {
+    let area = window.rect();
+
+    draw.rect()
+        .xy(area.xy())
+        .wh(area.wh())
+        .color(MENU_DIM_COLOR);
+
+    let title_area = Rect::from_x_y_w_h(0.0, area.top() / 2.0, area.w(), area.h() / 4.0);
+
+    text(
+        draw,
+        window,
+        tr(Text::SettingsTitle),
+        title_area,
+        LABEL_SIZE,
+        TextAnchor::Center,
+        palette.light,
+    );
+
+    for (line, item_area) in menu_lines(settings).iter().zip(menu_item_areas(window)) {
+        text(
+            draw,
+            window,
+            line,
+            item_area,
+            MENU_SIZE,
+            TextAnchor::Center,
+            palette.light,
+        );
+    }
+}
+
+// the disk counts, and the AI to move while it is thinking
+fn hud_text(game: &Game, thinking: Option<(Duration, Option<Limit>)>) -> String {
+    let [x_disks, o_disks] = game.disk_counts();
+    let mut hud = format!("X {x_disks} : O {o_disks}");
+
+    if let Some((elapsed, limit)) = thinking {
+        hud += &format!(
+            "   {} {} {:.1} s",
+            game.pos.next_player,
+            tr(Text::Thinking),
+            elapsed.as_secs_f32()
+        );
+
+        if let Some(limit) = limit {
+            hud += &format!(" / {limit}");
         }
     }
+
+    hud
 }
 
-fn finish_compare(arena: &mut AIArena) -> ! {
-    arena.console.unpin();
+fn draw_score_band(
+    draw: &Draw,
+    window: &Window,
+    game: &Game,
+    [x_name, o_name]: [String; 2],
+    palette: &Palette,
+) {
+    let band = Layout::score_band(window);
+    let third = band.w() / 3.0;
+    let [x_disks, o_disks] = game.disk_counts();
+
+    for (i, (content, size)) in [
+        (format!("X: {x_name}"), LABEL_SIZE),
+        (format!("{x_disks} : {o_disks}"), SCORE_SIZE),
+        (format!("O: {o_name}"), LABEL_SIZE),
+    ]
+    .into_iter()
+    .enumerate()
+    {
+        let area = Rect::from_x_y_w_h(
+            band.left() + third * (i as f32 + 0.5),
+            band.y(),
+            third,
+            band.h(),
+        );
 
-    let mut score1 = 0.0;
-    let mut score2 = 0.0;
+        text(
+            draw,
+            window,
+            &content,
+            area,
+            size,
+            TextAnchor::Center,
+            palette.light,
+        );
+    }
+}
 
-    for i in 0..arena.games.len() {
-        if i % 2 == 0 {
-            score1 += arena.games[i].score_for(Tile::X);
-            score2 += arena.games[i].score_for(Tile::O);
-        } else {
-            score1 += arena.games[i].score_for(Tile::O);
-            score2 += arena.games[i].score_for(Tile::X);
-        }
+// without the directories, which are rarely interesting to an audience
+fn presented_name(player: &Player) -> String {
+    match player {
+        Player::AI(ai) => ai
+            .config
+            .path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_else(|| player.name()),
+        player => player.name(),
     }
+}
 
-    arena
-        .console
-        .print(&format!("Score 1: {score1:.1}, score 2: {score2:.1}"));
+/// Where text is placed in its area.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TextAnchor {
+    Center,
+    TopLeft,
+}
 
-    process::exit(0);
+/// Draws `content` in `area` with the bundled font. `size` is relative to the smaller side of
+/// the window, so that text keeps its proportions at any window size, and as sizes are in
+/// points, at any pixel density.
+fn text(
+    draw: &Draw,
+    window: &Window,
+    content: &str,
+    area: Rect,
+    size: f32,
+    anchor: TextAnchor,
+    color: Rgba8,
+) {
+    let (width, height) = window.inner_size_points();
+    let font_size = ((f32::min(width, height) * size).round() as u32).max(MIN_FONT_SIZE);
+
+    let drawing = draw
+        .text(content)
+        .font(font())
+        .font_size(font_size)
+        .xy(area.xy())
+        .wh(area.wh())
+        .color(color);
+
+    match anchor {
+        TextAnchor::Center => drawing.center_justify().align_text_middle_y(),
+        TextAnchor::TopLeft => drawing.left_justify().align_text_top(),
+    };
 }
 
-fn finish_tournament(arena: &mut AIArena) -> ! {
-    arena.console.unpin();
+// loaded once, as parsing it every frame would be slow
+fn font() -> &'static nannou::text::Font {
+    static FONT: OnceLock<nannou::text::Font> = OnceLock::new();
 
-    let mut scores: HashMap<PathBuf, f32> = HashMap::new();
+    FONT.get_or_init(nannou::text::font::default_notosans)
+}
 
-    for game in &arena.games {
-        for (i, tile) in Tile::opponent_iter().enumerate() {
-            let score = game.score_for(tile);
+fn view_stats(app: &App, model: &Model, frame: Frame) {
+    let Some(window) = model.stats_window.and_then(|id| app.window(id)) else {
+        return;
+    };
 
-            let Player::AI(ai) = &game.players[i] else {
-                panic!("tournament shouldn't contain human players");
-            };
+    let palette = model.palette();
 
-            *scores.entry(ai.path.clone()).or_insert(0.0) += score;
-        }
+    let draw = app.draw();
+    draw.background().color(palette.background);
+
+    if let Mode::AIArena(arena) = &model.mode {
+        let area = window.rect().pad(STATS_PADDING);
+        let graph_height = area.h() * STATS_GRAPH_FRACTION;
+
+        text(
+            &draw,
+            &window,
+            &stats_text(arena),
+            area.pad_bottom(graph_height),
+            STATS_TEXT_SIZE,
+            TextAnchor::TopLeft,
+            palette.light,
+        );
+
+        draw_score_graph(
+            &draw,
+            &window,
+            &arena.score_history,
+            area.pad_top(area.h() - graph_height),
+            &palette,
+        );
     }
 
-    let elos = elo::from_single_tournament(
-        &arena
-            .games
-            .iter()
-            .map(|game| elo::Game {
-                players: game
-                    .players
-                    .iter()
-                    .map(|player| {
-                        let Player::AI(player) = player else {
-                            panic!("tournament shouldn't contain human players");
-                        };
-                        player.path.clone()
-                    })
-                    .collect::<Vec<PathBuf>>()
-                    .try_into()
-                    .unwrap(),
-                score: game.score_for(Tile::X),
-            })
-            .collect::<Vec<_>>(),
-        50,
-        16.0,
+    draw.to_frame(app, &frame).unwrap();
+}
+
+// the progress, the standings with failures of every AI, and the last results of the arena
+fn stats_text(arena: &AIArena) -> String {
+    let finished: Vec<&Game> = arena
+        .games
+        .iter()
+        .filter(|game| game.is_game_over())
+        .collect();
+    let running = arena
+        .games
+        .iter()
+        .filter(|game| game.started && !game.is_game_over())
+        .count();
+
+    let mut text = format!(
+        "{}: {}/{}, {}: {running}\n{}: {} s\n\n{}:\n",
+        tr(Text::GamesDone),
+        finished.len(),
+        arena.games.len(),
+        tr(Text::Running),
+        tr(Text::Elapsed),
+        arena.started_at.elapsed().as_secs(),
+        tr(Text::Standings)
     );
 
-    let mut scores: Vec<_> = scores.into_iter().collect();
-    scores.sort_by(|(_, s1), (_, s2)| s2.partial_cmp(s1).unwrap());
+    for standing in report::standings(&arena.games) {
+        text += &format!(
+            "{: >5.1}/{: <4} {: >3} {}  {}\n",
+            standing.score,
+            standing.games,
+            standing.failures,
+            tr(Text::Failed),
+            standing.name
+        );
+    }
 
-    arena
-        .console
-        .print(&format!("{: >4} {: >5} Path", "Elo", "Score"));
-
-    for (path, score) in scores {
-        arena.console.print(&format!(
-            "{: >4.0} {: >5.1} {}",
-            elos[&path],
-            score,
-            path.display()
-        ));
+    text += &format!("\n{}:\n", tr(Text::LastGames));
+
+    for id in arena.ended.iter().rev().take(STATS_RECENT_GAMES) {
+        let Some(game) = arena.games.iter().find(|game| game.id == *id) else {
+            continue;
+        };
+
+        text += &format!(
+            "#{id} {} - {}: {}\n",
+            game.players[0].name(),
+            game.players[1].name(),
+            match game.winner.expect("Expected a finished game") {
+                Tile::Empty => tr(Text::Draw).to_owned(),
+                winner => winner_string(winner),
+            }
+        );
     }
 
-    process::exit(0);
+    text
 }
 
-// VIEW
-
-const BACKGROUND_COLOR: Rgba8 = rgba8(30, 90, 60, 255);
-const CHANGE_HIGHLIGHT_COLOR: Rgba8 = rgba8(91, 203, 215, 255);
-const MOVE_HIGHLIGHT_COLOR: Rgba8 = rgba8(53, 103, 202, 255);
-const TRANSPARENT: Rgba8 = rgba8(0, 0, 0, 0);
-const TILE_STROKE_COLOR: Rgba8 = rgba8(250, 250, 230, 255);
-const LIGHT_COLOR: Rgba8 = TILE_STROKE_COLOR;
-const DARK_COLOR: Rgba8 = rgba8(5, 10, 15, 255);
-const TILE_STROKE_WEIGHT: f32 = 5.0;
+// the total score of every AI after every finished game, with the name of the AI at the end of
+// its line
+fn draw_score_graph(
+    draw: &Draw,
+    window: &Window,
+    history: &ScoreHistory,
+    area: Rect,
+    palette: &Palette,
+) {
+    if history.points.len() < 2 {
+        return;
+    }
 
-fn view(app: &App, model: &Model, frame: Frame) {
-    let window = app.window(model.window_id).expect("Error finding window.");
-    let game = model.mode.showed_game();
+    // room for the names at the right
+    let plot = area.pad_right(area.w() * STATS_GRAPH_LABEL_FRACTION);
+
+    draw.line()
+        .start(plot.bottom_left())
+        .end(plot.top_left())
+        .weight(STATS_GRAPH_WEIGHT)
+        .color(palette.light);
+    draw.line()
+        .start(plot.bottom_left())
+        .end(pt2(plot.right(), plot.bottom()))
+        .weight(STATS_GRAPH_WEIGHT)
+        .color(palette.light);
+
+    let max_score = history
+        .points
+        .last()
+        .and_then(|point| point.scores.values().copied().reduce(f32::max))
+        .unwrap_or(0.0)
+        .max(1.0);
+    let games = history.points.len() as f32;
+
+    for (i, (id, name)) in history.players().into_iter().enumerate() {
+        let color = SERIES_COLORS[i % SERIES_COLORS.len()];
+
+        let points: Vec<nannou::prelude::Vec2> = history
+            .points
+            .iter()
+            .map(|point| {
+                let score = point.scores.get(id).copied().unwrap_or(0.0);
 
-    let draw = app.draw();
-    draw.background().color(BACKGROUND_COLOR);
+                pt2(
+                    plot.left() + plot.w() * point.games as f32 / games,
+                    plot.bottom() + plot.h() * score / max_score,
+                )
+            })
+            .collect();
 
-    let rects = Model::get_rects(&window);
+        let end = *points.last().expect("history empty");
 
-    for x in 0..8 {
-        for y in 0..8 {
-            draw_tile(x, y, game, &rects, &draw);
-        }
-    }
+        draw.polyline()
+            .weight(STATS_GRAPH_WEIGHT)
+            .points(points)
+            .color(color);
 
-    //draw.rect().stroke(WHITE).stroke_weight(3.0).color(Color::TRANSPARENT);
+        let label = Rect::from_x_y_w_h(
+            plot.right() + area.w() * STATS_GRAPH_LABEL_FRACTION / 2.0,
+            end.y,
+            area.w() * STATS_GRAPH_LABEL_FRACTION,
+            area.h() / 10.0,
+        );
 
-    draw.to_frame(app, &frame).unwrap();
+        text(
+            draw,
+            window,
+            name,
+            label.shift_x(STATS_GRAPH_WEIGHT * 2.0),
+            STATS_GRAPH_TEXT_SIZE,
+            TextAnchor::TopLeft,
+            color,
+        );
+    }
 }
 
-fn draw_tile(x: usize, y: usize, game: &Game, rects: &[[Rect; 8]; 8], draw: &Draw) {
+fn draw_tile(
+    x: usize,
+    y: usize,
+    game: &Game,
+    rects: &[[Rect; 8]; 8],
+    draw: &Draw,
+    palette: &Palette,
+) {
     let vec2 = othello_gui::Vec2::new(x as isize, y as isize);
 
     let fill_color = if Some(vec2) == game.history.last().expect("history empty").1 {
-        MOVE_HIGHLIGHT_COLOR
+        palette.move_highlight
     } else if game.history.len() >= 2
         && game.pos.board.get(vec2) != game.history[game.history.len() - 2].0.board.get(vec2)
     {
-        CHANGE_HIGHLIGHT_COLOR
+        palette.change_highlight
     } else {
         TRANSPARENT
     };
@@ -721,31 +5844,33 @@ fn draw_tile(x: usize, y: usize, game: &Game, rects: &[[Rect; 8]; 8], draw: &Dra
         .xy(rect.xy())
         .wh(rect.wh())
         .color(fill_color)
-        .stroke(TILE_STROKE_COLOR)
+        .stroke(palette.tile_stroke)
         .stroke_weight(TILE_STROKE_WEIGHT);
 
-    if game.pos.board.get(vec2) != Tile::Empty {
-        let circle = rect.pad(TILE_STROKE_WEIGHT);
-        draw.ellipse()
-            .xy(circle.xy())
-            .wh(circle.wh())
-            .color(match game.pos.board.get(vec2) {
-                Tile::X => DARK_COLOR,
-                Tile::O => LIGHT_COLOR,
-                _ => panic!("Invalid tile while drawing"),
-            });
-    }
-}
-
-// reimplementation required, so it is a constant function
-const fn rgba8(red: u8, green: u8, blue: u8, alpha: u8) -> Rgba8 {
-    Rgba8 {
-        color: Rgb8 {
-            red,
-            green,
-            blue,
-            standard: std::marker::PhantomData,
-        },
-        alpha,
+    let circle = rect.pad(TILE_STROKE_WEIGHT);
+
+    match game.pos.board.get(vec2) {
+        Tile::Empty => {}
+        Tile::O if palette.rings => {
+            let weight = circle.w() * RING_FRACTION;
+            // the stroke is centered on the edge
+            let ring = circle.pad(weight / 2.0);
+
+            draw.ellipse()
+                .xy(ring.xy())
+                .wh(ring.wh())
+                .no_fill()
+                .stroke(palette.light)
+                .stroke_weight(weight);
+        }
+        tile => {
+            draw.ellipse()
+                .xy(circle.xy())
+                .wh(circle.wh())
+                .color(match tile {
+                    Tile::X => palette.dark,
+                    _ => palette.light,
+                });
+        }
     }
 }
@@ -0,0 +1,208 @@
+use serde::{Deserialize, Serialize};
+use std::{
+    fmt, fs,
+    path::{Path, PathBuf},
+};
+
+use crate::error::Error;
+
+/// A compare or tournament described in a TOML file, read by `run <match file>`, so that
+/// experiments can be repeated and shared without long command lines. Paths are relative to the
+/// file, like in AI lists.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct MatchFile {
+    pub mode: MatchMode,
+    // players in the same form as on the command line, without their limit; in compare mode the
+    // first one is compared with every other one
+    pub engines: Vec<String>,
+    // <max time> of every AI
+    pub time: String,
+    // compare mode: how many moves the openings are, and how many of them are played
+    #[serde(default)]
+    pub depth: usize,
+    pub games: Option<Amount>,
+    pub concurrency: Option<Amount>,
+    pub seed: Option<u64>,
+    pub adjudicate: Option<usize>,
+    pub position: Option<PathBuf>,
+    pub results: Option<PathBuf>,
+    pub report: Option<PathBuf>,
+    pub ratings: Option<PathBuf>,
+    pub capture: Option<PathBuf>,
+    // any other options, as they are written on the command line
+    #[serde(default)]
+    pub options: Vec<String>,
+    // the directory of the file
+    #[serde(skip)]
+    pub base: PathBuf,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MatchMode {
+    Compare,
+    Tournament,
+}
+
+/// A number, or a word like `all` or `auto` in its place.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Amount {
+    Number(usize),
+    Word(String),
+}
+
+impl fmt::Display for Amount {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Amount::Number(number) => write!(f, "{number}"),
+            Amount::Word(word) => write!(f, "{word}"),
+        }
+    }
+}
+
+impl MatchFile {
+    pub fn read(path: &Path) -> Result<Self, Error> {
+        let content = fs::read_to_string(path).map_err(|source| Error::Read {
+            what: format!("match file '{}'", path.display()),
+            source,
+        })?;
+
+        let mut match_file: Self = toml::from_str(&content).map_err(|err| {
+            Error::InvalidValue(format!("Match file '{}' is invalid: {err}", path.display()))
+        })?;
+
+        match_file.base = path.parent().unwrap_or(Path::new("")).to_owned();
+
+        let count = match_file.engines.len();
+
+        if count < 2 {
+            return Err(Error::InvalidValue(format!(
+                "Match file '{}' has {count} engines instead of at least two",
+                path.display()
+            )));
+        }
+
+        Ok(match_file)
+    }
+
+    /// The pairs of games of compare mode, all openings by default.
+    pub fn games_arg(&self) -> String {
+        self.games
+            .as_ref()
+            .map_or("all".to_owned(), Amount::to_string)
+    }
+
+    /// One game at a time by default, so that AIs don't slow each other down.
+    pub fn concurrency_arg(&self) -> String {
+        self.concurrency
+            .as_ref()
+            .map_or("1".to_owned(), Amount::to_string)
+    }
+
+    /// The command line options the file stands for.
+    pub fn options(&self) -> Vec<String> {
+        let mut options = Vec::new();
+
+        if let Some(empties) = self.adjudicate {
+            options.extend(["--adjudicate".to_owned(), empties.to_string()]);
+        }
+
+        let paths = [
+            ("--position", &self.position),
+            ("--results", &self.results),
+            ("--report", &self.report),
+            ("--ratings", &self.ratings),
+            ("--capture", &self.capture),
+        ];
+
+        for (option, path) in paths {
+            if let Some(path) = path {
+                options.push(option.to_owned());
+                options.push(self.base.join(path).display().to_string());
+            }
+        }
+
+        options.extend(self.options.iter().cloned());
+
+        options
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn match_file(mode: MatchMode) -> MatchFile {
+        MatchFile {
+            mode,
+            engines: vec!["a".to_owned(), "bots/b|cwd=bots".to_owned()],
+            time: "100ms".to_owned(),
+            depth: 2,
+            games: Some(Amount::Number(10)),
+            concurrency: Some(Amount::Word("auto".to_owned())),
+            seed: None,
+            adjudicate: Some(12),
+            position: None,
+            results: Some("results.jsonl".into()),
+            report: None,
+            ratings: None,
+            capture: None,
+            options: vec!["--warm-up".to_owned()],
+            base: "experiments".into(),
+        }
+    }
+
+    #[test]
+    fn toml_round_trip() {
+        let toml = r#"
+            mode = "compare"
+            engines = ["a", "bots/b|cwd=bots"]
+            time = "100ms"
+            depth = 2
+            games = 10
+            concurrency = "auto"
+            adjudicate = 12
+            results = "results.jsonl"
+            options = ["--warm-up"]
+        "#;
+
+        let mut expected = match_file(MatchMode::Compare);
+        expected.base = PathBuf::new();
+
+        let read: MatchFile = toml::from_str(toml).unwrap();
+        assert_eq!(read, expected);
+
+        let written = toml::to_string(&read).unwrap();
+        assert_eq!(toml::from_str::<MatchFile>(&written).unwrap(), expected);
+
+        assert!(toml::from_str::<MatchFile>(&format!("{toml}\nrounds = 2")).is_err());
+    }
+
+    #[test]
+    fn defaults() {
+        let mut match_file = match_file(MatchMode::Compare);
+        match_file.games = None;
+        match_file.concurrency = None;
+
+        assert_eq!(match_file.games_arg(), "all");
+        assert_eq!(match_file.concurrency_arg(), "1");
+    }
+
+    #[test]
+    fn options() {
+        let results = Path::new("experiments").join("results.jsonl");
+
+        assert_eq!(
+            match_file(MatchMode::Tournament).options(),
+            vec![
+                "--adjudicate".to_owned(),
+                "12".to_owned(),
+                "--results".to_owned(),
+                results.display().to_string(),
+                "--warm-up".to_owned(),
+            ]
+        );
+    }
+}
@@ -0,0 +1,227 @@
+use std::{
+    cmp::Ordering,
+    io::{self, BufRead, BufReader, Write},
+    net::{TcpListener, TcpStream},
+    sync::{
+        mpsc::{self, Receiver},
+        Arc, Mutex, PoisonError,
+    },
+    thread,
+    time::Duration,
+};
+
+use crate::{error::Error, *};
+
+const RECONNECT_INTERVAL: Duration = Duration::from_secs(1);
+
+/// The other player of a game played between two instances of the GUI over TCP.
+///
+/// Every move is sent as `move <seq> <move>`, where `<seq>` is the number of moves played before
+/// it. After (re)connecting both sides send every move of the game as `sync <move>...`, so moves
+/// played while disconnected aren't lost. `resync` asks the other side to send `sync` again.
+#[derive(Debug)]
+pub struct NetPlayer {
+    events: Receiver<NetEvent>,
+    stream: Arc<Mutex<Option<TcpStream>>>,
+    connected: bool,
+    // the moves of the game as known by the other side
+    remote_moves: Vec<Vec2>,
+}
+
+#[derive(Debug)]
+enum NetEvent {
+    Connected,
+    Disconnected,
+    Line(String),
+}
+
+impl NetPlayer {
+    pub fn host(port: u16) -> Result<Self, Error> {
+        let listener = TcpListener::bind(("0.0.0.0", port))
+            .map_err(|err| Error::Network(format!("Unable to listen on port {port}: {err}")))?;
+
+        Ok(Self::start(move || {
+            listener.accept().map(|(stream, _)| stream)
+        }))
+    }
+
+    pub fn join(address: String) -> Self {
+        Self::start(move || TcpStream::connect(&address))
+    }
+
+    // `connect` is called again every time the connection is lost
+    fn start(mut connect: impl FnMut() -> io::Result<TcpStream> + Send + 'static) -> Self {
+        let (sender, events) = mpsc::channel();
+        let stream = Arc::new(Mutex::new(None));
+
+        let thread_stream = Arc::clone(&stream);
+        thread::spawn(move || loop {
+            let Ok((stream, reader)) = connect().and_then(|stream| {
+                let reader = stream.try_clone()?;
+                Ok((stream, reader))
+            }) else {
+                thread::sleep(RECONNECT_INTERVAL);
+                continue;
+            };
+
+            *thread_stream.lock().unwrap_or_else(PoisonError::into_inner) = Some(stream);
+
+            // sending only fails if the player was dropped
+            if sender.send(NetEvent::Connected).is_err() {
+                return;
+            }
+
+            for line in BufReader::new(reader).lines() {
+                let Ok(line) = line else {
+                    break;
+                };
+
+                if sender.send(NetEvent::Line(line)).is_err() {
+                    return;
+                }
+            }
+
+            *thread_stream.lock().unwrap_or_else(PoisonError::into_inner) = None;
+
+            if sender.send(NetEvent::Disconnected).is_err() {
+                return;
+            }
+        });
+
+        Self {
+            events,
+            stream,
+            connected: false,
+            remote_moves: Vec::new(),
+        }
+    }
+
+    pub fn is_connected(&self) -> bool {
+        self.connected
+    }
+
+    /// Handles the messages of the other side, and sends it the moves it doesn't know about.
+    pub fn update(&mut self, moves: &[Vec2]) -> Result<(), Error> {
+        while let Ok(event) = self.events.try_recv() {
+            match event {
+                NetEvent::Connected => {
+                    self.connected = true;
+                    self.send(&sync_message(moves));
+                }
+                NetEvent::Disconnected => self.connected = false,
+                NetEvent::Line(line) => self.handle_line(&line, moves)?,
+            }
+        }
+
+        let common = moves.len().min(self.remote_moves.len());
+
+        if moves[..common] != self.remote_moves[..common] {
+            return Err(Error::Network(
+                "The games of the two sides are different".to_owned(),
+            ));
+        }
+
+        if self.connected {
+            for (seq, &mv) in moves.iter().enumerate().skip(self.remote_moves.len()) {
+                self.send(&format!("move {seq} {}", mv.move_string()));
+                self.remote_moves.push(mv);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// The move the other side played after `moves_played` moves, if it arrived already.
+    pub fn next_move(&self, moves_played: usize) -> Option<Vec2> {
+        self.remote_moves.get(moves_played).copied()
+    }
+
+    fn handle_line(&mut self, line: &str, moves: &[Vec2]) -> Result<(), Error> {
+        let invalid = || Error::Network(format!("Invalid message received: '{line}'"));
+
+        let mut parts = line.split_whitespace();
+
+        match parts.next() {
+            Some("move") => {
+                let seq: usize = parts
+                    .next()
+                    .and_then(|seq| seq.parse().ok())
+                    .ok_or_else(invalid)?;
+                let mv = parts.next().and_then(parse_move).ok_or_else(invalid)?;
+
+                match seq.cmp(&self.remote_moves.len()) {
+                    // already known from a sync
+                    Ordering::Less => {}
+                    Ordering::Equal => self.remote_moves.push(mv),
+                    Ordering::Greater => self.send("resync"),
+                }
+            }
+            Some("sync") => {
+                self.remote_moves = parts
+                    .map(parse_move)
+                    .collect::<Option<_>>()
+                    .ok_or_else(invalid)?;
+            }
+            Some("resync") => self.send(&sync_message(moves)),
+            _ => return Err(invalid()),
+        }
+
+        Ok(())
+    }
+
+    fn send(&mut self, message: &str) {
+        let mut stream = self.stream.lock().unwrap_or_else(PoisonError::into_inner);
+
+        // if writing fails, the connection is lost, which is noticed by the reading thread
+        if let Some(stream) = stream.as_mut() {
+            let _ = writeln!(stream, "{message}");
+        }
+    }
+}
+
+fn sync_message(moves: &[Vec2]) -> String {
+    let mut message = "sync".to_owned();
+
+    for mv in moves {
+        message.push(' ');
+        message.push_str(&mv.move_string());
+    }
+
+    message
+}
+
+pub fn parse_move(move_string: &str) -> Option<Vec2> {
+    let mut chars = move_string.chars();
+    let (x_char, y_char) = (chars.next()?, chars.next()?);
+
+    if chars.next().is_some() || !('a'..='h').contains(&x_char) || !('1'..='8').contains(&y_char) {
+        return None;
+    }
+
+    Some(Vec2::new(
+        (x_char as u32 - 'a' as u32) as isize,
+        (y_char as u32 - '1' as u32) as isize,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn moves() {
+        assert_eq!(parse_move("d3"), Some(Vec2::new(3, 2)));
+        assert_eq!(parse_move("h8"), Some(Vec2::new(7, 7)));
+        assert_eq!(parse_move("i1"), None);
+        assert_eq!(parse_move("a9"), None);
+        assert_eq!(parse_move("a1b"), None);
+    }
+
+    #[test]
+    fn sync() {
+        let moves = [Vec2::new(3, 2), Vec2::new(2, 4)];
+
+        assert_eq!(sync_message(&moves), "sync d3 c5");
+        assert_eq!(sync_message(&[]), "sync");
+    }
+}
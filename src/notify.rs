@@ -0,0 +1,68 @@
+use notify_rust::Notification;
+use std::process::Command;
+
+use crate::console::*;
+
+/// What to do when a compare or tournament ends, so that long runs don't have to be watched.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FinishHooks {
+    // show a desktop notification
+    pub notify: bool,
+    // shell command, run with the outcome in environment variables
+    pub on_finish: Option<String>,
+}
+
+impl FinishHooks {
+    /// `finished` is false if the run was aborted, in which case `headline` is the reason.
+    pub fn run(&self, console: &Console, finished: bool, headline: &str) {
+        let status = if finished { "finished" } else { "aborted" };
+
+        if self.notify {
+            let result = Notification::new()
+                .summary(&format!("Othello GUI: run {status}"))
+                .body(headline)
+                .show();
+
+            if let Err(err) = result {
+                console.warn(
+                    Category::Errors,
+                    &format!("Unable to show notification: {err}"),
+                );
+            }
+        }
+
+        if let Some(command) = &self.on_finish {
+            let result = shell_command(command)
+                .env("OTHELLO_GUI_STATUS", status)
+                .env("OTHELLO_GUI_RESULT", headline)
+                .status();
+
+            match result {
+                Ok(status) if status.success() => {}
+                Ok(status) => console.warn(
+                    Category::Errors,
+                    &format!("--on-finish command exited with {status}"),
+                ),
+                Err(err) => console.warn(
+                    Category::Errors,
+                    &format!("Unable to run --on-finish command: {err}"),
+                ),
+            }
+        }
+    }
+}
+
+fn shell_command(command: &str) -> Command {
+    let mut shell = if cfg!(windows) {
+        let mut shell = Command::new("cmd");
+        shell.arg("/C");
+        shell
+    } else {
+        let mut shell = Command::new("sh");
+        shell.arg("-c");
+        shell
+    };
+
+    shell.arg(command);
+    shell
+}
@@ -0,0 +1,360 @@
+use serde::{Deserialize, Serialize};
+use std::{
+    fmt::{self, Debug},
+    io,
+    path::PathBuf,
+    process::ExitStatus,
+};
+
+use crate::{
+    error::Error,
+    i18n::{tr, Text},
+    *,
+};
+
+/// Receives the events of games. Features following games (logging, transcripts,
+/// broadcasting) subscribe to these instead of reading the internals of `Game`.
+///
+/// Events are sent after `Game` is updated, so e.g. in `on_move` the move is already played.
+pub trait GameObserver: Debug {
+    fn on_game_start(&mut self, _game: &Game) {}
+
+    fn on_move(&mut self, _game: &Game, _mv: Vec2, _notes: &str) {}
+
+    fn on_undo(&mut self, _game: &Game) {}
+
+    fn on_game_end(&mut self, _game: &Game) {}
+
+    /// Sent when the AI to move fails, before the game ends because of it.
+    fn on_engine_error(&mut self, _game: &Game, _error: &EngineError) {}
+
+    /// Sent when a local AI finished running, with what it was sent and wrote, before its move
+    /// is played or the game is forfeited.
+    fn on_engine_io(&mut self, _game: &Game, _io: &EngineIo) {}
+
+    /// Sent when the AI to move misbehaves without losing the game, before its move is played.
+    fn on_engine_warning(&mut self, _game: &Game, _warning: &str) {}
+
+    /// Sent with `--verify-moves` when the move just played was played differently by the second
+    /// implementation of the rules, which is a bug of the GUI. The game goes on as the core
+    /// library played it.
+    fn on_verification_failure(&mut self, _game: &Game, _error: &str) {}
+
+    /// Sent in compare and tournament mode when something happens to all games, not one of them.
+    fn on_arena_event(&mut self, _event: &ArenaEvent) {}
+}
+
+/// Something happening to a compare or tournament as a whole, rather than to one of its games.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ArenaEvent {
+    /// The number of games played at once was changed by `<max concurrency>` auto.
+    ConcurrencyChanged {
+        from: usize,
+        to: usize,
+    },
+    /// No games are started while the load average is above `--max-load`.
+    StartsPaused {
+        load: f32,
+    },
+    StartsResumed,
+    /// `--max-duration` is up, the games not started yet are dropped.
+    Truncated {
+        dropped: usize,
+    },
+    /// The window was closed or Q was pressed before all games ended, the AIs were stopped.
+    Interrupted {
+        unfinished: usize,
+    },
+}
+
+#[derive(Debug)]
+pub enum EngineError {
+    InvalidOutput(Error),
+    RuntimeError { status: ExitStatus, stderr: String },
+    TimeOut { partial_output: String },
+    InvalidMove(Vec2),
+    Connection(String),
+    NodeLimitExceeded { nodes: u64, limit: u64 },
+    StartFailed { path: PathBuf, source: io::Error },
+}
+
+impl EngineError {
+    pub fn failure(&self) -> Failure {
+        match self {
+            EngineError::InvalidOutput(_) | EngineError::Connection(_) => Failure::Protocol,
+            EngineError::RuntimeError { .. } | EngineError::StartFailed { .. } => Failure::Crash,
+            EngineError::TimeOut { .. } | EngineError::NodeLimitExceeded { .. } => Failure::TimeOut,
+            EngineError::InvalidMove(_) => Failure::InvalidMove,
+        }
+    }
+}
+
+/// The kind of an `EngineError`, kept on the game it lost to count failures over a run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Failure {
+    // exceeding the node limit counts too
+    TimeOut,
+    Crash,
+    InvalidMove,
+    // unreadable output or a lost connection
+    Protocol,
+}
+
+impl fmt::Display for Failure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Failure::TimeOut => write!(f, "timeouts"),
+            Failure::Crash => write!(f, "crashes"),
+            Failure::InvalidMove => write!(f, "invalid moves"),
+            Failure::Protocol => write!(f, "protocol errors"),
+        }
+    }
+}
+
+// a one line summary, the console shows more details
+impl fmt::Display for EngineError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EngineError::InvalidOutput(err) => write!(f, "invalid output: {err}"),
+            EngineError::RuntimeError { status, .. } => {
+                write!(f, "program exit code was non-zero: {status}")
+            }
+            EngineError::TimeOut { .. } => write!(f, "exceeded time limit"),
+            EngineError::InvalidMove(mv) => write!(f, "invalid move: {}", mv.move_string()),
+            EngineError::Connection(err) => write!(f, "unable to reach AI: {err}"),
+            EngineError::NodeLimitExceeded { nodes, limit } => {
+                write!(f, "searched {nodes} nodes, exceeding the limit of {limit}")
+            }
+            EngineError::StartFailed { path, source } => {
+                write!(f, "unable to start '{}': {source}", path.display())
+            }
+        }
+    }
+}
+
+// games are logged to the console as an observer too
+impl GameObserver for Console {
+    fn on_game_start(&mut self, game: &Game) {
+        self.info(
+            Category::GameFlow,
+            &format!("{} {}", game.formatted_id(), tr(Text::GameStarted)),
+        );
+    }
+
+    fn on_move(&mut self, game: &Game, mv: Vec2, notes: &str) {
+        let mover = game.history[game.history.len() - 2].0.next_player;
+
+        self.info(
+            Category::GameFlow,
+            &format!(
+                "{} {}: {} ({})",
+                game.formatted_id(),
+                mover,
+                mv.move_string(),
+                notes
+            ),
+        );
+
+        if self.print_boards {
+            self.info(Category::GameFlow, &board_diagram(&game.pos, Some(mv)));
+        }
+    }
+
+    fn on_undo(&mut self, game: &Game) {
+        self.info(
+            Category::GameFlow,
+            &format!("{} {}", game.formatted_id(), tr(Text::UndidMove)),
+        );
+    }
+
+    fn on_game_end(&mut self, game: &Game) {
+        self.info(
+            Category::Results,
+            &format!(
+                "{} {}, {}: {} (X {}, O {})",
+                game.formatted_id(),
+                if game.adjudicated {
+                    tr(Text::GameAdjudicated)
+                } else {
+                    tr(Text::GameEnded)
+                },
+                tr(Text::Winner),
+                game.winner.expect("game ended without a winner"),
+                game.disk_counts()[Tile::X as usize],
+                game.disk_counts()[Tile::O as usize]
+            ),
+        );
+    }
+
+    fn on_engine_error(&mut self, game: &Game, error: &EngineError) {
+        let id = game.formatted_id();
+        let tile = game.pos.next_player;
+
+        match error {
+            EngineError::InvalidOutput(err) => {
+                self.warn(
+                    Category::Errors,
+                    &format!("{id} Error reading AI {tile} move: {err}"),
+                );
+            }
+            EngineError::RuntimeError { status, stderr } => {
+                self.warn(
+                    Category::Errors,
+                    // without a code if it was killed by a signal
+                    &format!("{id} AI {tile} program exit code was non-zero: {status}"),
+                );
+                self.warn(Category::EngineIo, "stderr of AI program:");
+                self.warn(Category::EngineIo, stderr);
+            }
+            EngineError::TimeOut { partial_output } => {
+                self.warn(
+                    Category::Errors,
+                    &format!("{id} AI {tile} program exceeded time limit"),
+                );
+                if !partial_output.is_empty() {
+                    self.warn(
+                        Category::EngineIo,
+                        "output of AI program before timing out:",
+                    );
+                    self.warn(Category::EngineIo, partial_output);
+                }
+            }
+            EngineError::InvalidMove(mv) => {
+                self.warn(
+                    Category::Errors,
+                    &format!(
+                        "{id} Invalid move played by AI {tile}: {}",
+                        mv.move_string()
+                    ),
+                );
+            }
+            EngineError::Connection(err) => {
+                self.warn(
+                    Category::Errors,
+                    &format!("{id} Unable to reach AI {tile}: {err}"),
+                );
+            }
+            EngineError::NodeLimitExceeded { nodes, limit } => {
+                self.warn(
+                    Category::Errors,
+                    &format!(
+                        "{id} AI {tile} searched {nodes} nodes, exceeding its limit of {limit}"
+                    ),
+                );
+            }
+            EngineError::StartFailed { path, source } => {
+                self.warn(
+                    Category::Errors,
+                    &format!(
+                        "{id} Unable to start AI {tile} program '{}': {source}",
+                        path.display()
+                    ),
+                );
+            }
+        }
+
+        game.print_input_for_debug(self);
+
+        if self.print_boards {
+            let last_move = game.history.last().expect("history empty").1;
+            self.warn(Category::Errors, &board_diagram(&game.pos, last_move));
+        }
+    }
+
+    fn on_engine_warning(&mut self, game: &Game, warning: &str) {
+        self.warn(
+            Category::Errors,
+            &format!(
+                "{} AI {}: {warning}",
+                game.formatted_id(),
+                game.pos.next_player
+            ),
+        );
+    }
+
+    fn on_verification_failure(&mut self, game: &Game, error: &str) {
+        self.warn(
+            Category::Errors,
+            &format!(
+                "{} Bug of the GUI, please report it: {error}",
+                game.formatted_id()
+            ),
+        );
+        self.warn(
+            Category::Errors,
+            &board_diagram(&game.history[game.history.len() - 2].0, None),
+        );
+    }
+}
+
+/// Sends events to the console and to every other observer of the games.
+#[derive(Debug)]
+pub struct Broadcast<'a> {
+    pub console: &'a mut Console,
+    pub observers: &'a mut [Box<dyn GameObserver>],
+}
+
+impl GameObserver for Broadcast<'_> {
+    fn on_game_start(&mut self, game: &Game) {
+        self.console.on_game_start(game);
+        for observer in self.observers.iter_mut() {
+            observer.on_game_start(game);
+        }
+    }
+
+    fn on_move(&mut self, game: &Game, mv: Vec2, notes: &str) {
+        self.console.on_move(game, mv, notes);
+        for observer in self.observers.iter_mut() {
+            observer.on_move(game, mv, notes);
+        }
+    }
+
+    fn on_undo(&mut self, game: &Game) {
+        self.console.on_undo(game);
+        for observer in self.observers.iter_mut() {
+            observer.on_undo(game);
+        }
+    }
+
+    fn on_game_end(&mut self, game: &Game) {
+        self.console.on_game_end(game);
+        for observer in self.observers.iter_mut() {
+            observer.on_game_end(game);
+        }
+    }
+
+    fn on_engine_error(&mut self, game: &Game, error: &EngineError) {
+        self.console.on_engine_error(game, error);
+        for observer in self.observers.iter_mut() {
+            observer.on_engine_error(game, error);
+        }
+    }
+
+    fn on_engine_io(&mut self, game: &Game, io: &EngineIo) {
+        for observer in self.observers.iter_mut() {
+            observer.on_engine_io(game, io);
+        }
+    }
+
+    fn on_engine_warning(&mut self, game: &Game, warning: &str) {
+        self.console.on_engine_warning(game, warning);
+        for observer in self.observers.iter_mut() {
+            observer.on_engine_warning(game, warning);
+        }
+    }
+
+    fn on_verification_failure(&mut self, game: &Game, error: &str) {
+        self.console.on_verification_failure(game, error);
+        for observer in self.observers.iter_mut() {
+            observer.on_verification_failure(game, error);
+        }
+    }
+
+    fn on_arena_event(&mut self, event: &ArenaEvent) {
+        self.console.on_arena_event(event);
+        for observer in self.observers.iter_mut() {
+            observer.on_arena_event(event);
+        }
+    }
+}
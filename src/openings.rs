@@ -0,0 +1,178 @@
+use std::collections::HashSet;
+
+use crate::*;
+
+// a random number for every tile of every square, and one for O to move, the same in every run
+const ZOBRIST: [[u64; 2]; 64] = zobrist_table();
+const ZOBRIST_O_TO_MOVE: u64 = splitmix64(128).0;
+
+/// A position reached from the initial position, with the moves leading to it.
+#[derive(Debug, Clone)]
+pub struct Opening {
+    pub pos: Pos,
+    pub moves: Vec<Vec2>,
+}
+
+/// The positions after `depth` moves from the initial position, generated one at a time as they
+/// are needed, depth first in the order of the valid moves.
+///
+/// A position reached by different moves, a transposition, is only generated the first time, and
+/// the positions after it aren't searched again.
+#[derive(Debug)]
+pub struct Openings {
+    depth: usize,
+    merge_symmetric: bool,
+    stack: Vec<Opening>,
+    // the hashes of the positions already reached
+    seen: HashSet<u64>,
+}
+
+impl Openings {
+    /// The openings starting with `line`, which is played from the initial position.
+    pub fn after(line: Vec<Vec2>, depth: usize) -> Self {
+        Self::from_start(Pos::new(), line, depth)
+    }
+
+    /// The openings starting with `line`, which is played from `start`, e.g. another start setup.
+    pub fn from_start(mut pos: Pos, line: Vec<Vec2>, depth: usize) -> Self {
+        for &mv in &line {
+            pos.play(mv);
+        }
+
+        Self {
+            depth,
+            merge_symmetric: false,
+            stack: vec![Opening { pos, moves: line }],
+            seen: HashSet::new(),
+        }
+    }
+
+    /// Also leaves out the positions which are rotations or mirror images of ones generated
+    /// before.
+    pub fn merge_symmetric(mut self) -> Self {
+        self.merge_symmetric = true;
+        self
+    }
+
+    fn key(&self, pos: &Pos) -> u64 {
+        if self.merge_symmetric {
+            zobrist(&symmetry::canonical(pos).0)
+        } else {
+            zobrist(pos)
+        }
+    }
+}
+
+impl Iterator for Openings {
+    type Item = Opening;
+
+    fn next(&mut self) -> Option<Opening> {
+        while let Some(opening) = self.stack.pop() {
+            if opening.moves.len() >= self.depth || opening.pos.is_game_over() {
+                return Some(opening);
+            }
+
+            // reversed, so that the first move is searched first
+            for mv in opening.pos.valid_moves().into_iter().rev() {
+                let pos = opening.pos.play_clone(mv);
+                let key = self.key(&pos);
+
+                if self.seen.insert(key) {
+                    let mut moves = opening.moves.clone();
+                    moves.push(mv);
+
+                    self.stack.push(Opening { pos, moves });
+                }
+            }
+        }
+
+        None
+    }
+}
+
+/// A hash of the board and the player to move.
+pub fn zobrist(pos: &Pos) -> u64 {
+    let mut hash = match pos.next_player {
+        Tile::O => ZOBRIST_O_TO_MOVE,
+        _ => 0,
+    };
+
+    for coor in Vec2::board_iter() {
+        let square = (coor.y * 8 + coor.x) as usize;
+
+        match pos.board.get(coor) {
+            Tile::X => hash ^= ZOBRIST[square][0],
+            Tile::O => hash ^= ZOBRIST[square][1],
+            Tile::Empty => {}
+        }
+    }
+
+    hash
+}
+
+const fn zobrist_table() -> [[u64; 2]; 64] {
+    let mut table = [[0; 2]; 64];
+    let mut state = 0;
+    let mut i = 0;
+
+    while i < 128 {
+        let (number, next_state) = splitmix64(state);
+        table[i / 2][i % 2] = number;
+        state = next_state;
+        i += 1;
+    }
+
+    table
+}
+
+// a random number from `state`, and the next state
+const fn splitmix64(state: u64) -> (u64, u64) {
+    let state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    let mut z = state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+
+    (z ^ (z >> 31), state)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn transpositions_once() {
+        let positions = Pos::new().tree_end(3);
+        let openings: Vec<Opening> = Openings::after(Vec::new(), 3).collect();
+
+        let hashes: HashSet<u64> = positions.iter().map(zobrist).collect();
+
+        assert_eq!(openings.len(), hashes.len());
+        assert!(openings.len() < positions.len());
+
+        for opening in &openings {
+            let mut pos = Pos::new();
+
+            for &mv in &opening.moves {
+                pos.play(mv);
+            }
+
+            assert!(same_pos(&pos, &opening.pos));
+            assert!(hashes.contains(&zobrist(&opening.pos)));
+        }
+    }
+
+    #[test]
+    fn symmetric_openings_once() {
+        assert_eq!(Openings::after(Vec::new(), 0).count(), 1);
+        assert_eq!(Openings::after(Vec::new(), 1).merge_symmetric().count(), 1);
+
+        let first = Pos::new().valid_moves()[0];
+        let openings: Vec<Opening> = Openings::after(vec![first], 3).merge_symmetric().collect();
+
+        assert!(openings.iter().all(|opening| opening.moves[0] == first));
+        assert_eq!(
+            openings.len(),
+            symmetry::unique(Pos::new().play_clone(first).tree_end(2)).len()
+        );
+    }
+}
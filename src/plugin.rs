@@ -0,0 +1,217 @@
+use libloading::Library;
+use std::{
+    ffi::{c_char, CStr, CString},
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Instant,
+};
+use tokio::{task, time};
+
+use crate::{ai::*, error::Error, *};
+
+/// The function a plugin exports as `othello_player_v1`. It is given the same input AIs get on
+/// stdin, as a null-terminated string, and writes what AIs write on stdout into `output`, at most
+/// `capacity` bytes, returning the number of bytes written, or a negative number if it failed.
+///
+/// It can be called from multiple threads at once, for different games.
+pub type PlayerV1 =
+    unsafe extern "C" fn(input: *const c_char, output: *mut c_char, capacity: usize) -> i64;
+
+const SYMBOL: &[u8] = b"othello_player_v1";
+
+// the output of AIs is only a few lines
+const OUTPUT_CAPACITY: usize = 64 * 1024;
+
+/// A player loaded from a shared library (`dylib:<path>`), run in-process without starting a
+/// program for every move.
+///
+/// Plugins can't be stopped, so one exceeding its time limit loses the game, but keeps running on
+/// a thread of its own until it returns.
+#[derive(Debug)]
+pub struct PluginPlayer {
+    pub path: PathBuf,
+    pub limit: Limit,
+    pub run_handle: Option<AIRunHandle>,
+    pub deterministic: bool,
+    // kept loaded while the function is used, shared by the clones of the player
+    library: Arc<Library>,
+    player: PlayerV1,
+}
+
+impl PluginPlayer {
+    pub fn load(path: &Path, limit: Limit) -> Result<Self, Error> {
+        let invalid = |err: libloading::Error| {
+            Error::InvalidEngine(format!("Unable to load plugin '{}': {err}", path.display()))
+        };
+
+        // SAFETY: loading a library runs its initialization code, which has to be trusted like
+        // any AI is
+        let library = unsafe { Library::new(path) }.map_err(invalid)?;
+
+        // SAFETY: the signature is that of the version of the interface named by the symbol
+        let player = *unsafe { library.get::<PlayerV1>(SYMBOL) }.map_err(invalid)?;
+
+        Ok(Self {
+            path: path.to_owned(),
+            limit,
+            run_handle: None,
+            deterministic: false,
+            library: Arc::new(library),
+            player,
+        })
+    }
+
+    pub fn input(&self, pos: Pos) -> String {
+        engine_input(pos, self.limit, self.deterministic)
+    }
+
+    pub fn run(&mut self, pos: Pos) {
+        let deadline = Instant::now() + self.limit.time_limit();
+
+        self.run_handle = Some(AIRunHandle::spawn(run_plugin(
+            Arc::clone(&self.library),
+            self.player,
+            self.input(pos),
+            deadline,
+        )));
+    }
+
+    pub fn try_clone(&self) -> Result<Self, Error> {
+        match self.run_handle {
+            None => Ok(Self {
+                path: self.path.clone(),
+                limit: self.limit,
+                run_handle: None,
+                deterministic: self.deterministic,
+                library: Arc::clone(&self.library),
+                player: self.player,
+            }),
+            Some(_) => Err(Error::CloneRunningEngine(format!(
+                "dylib:{}",
+                self.path.display()
+            ))),
+        }
+    }
+}
+
+async fn run_plugin(
+    library: Arc<Library>,
+    player: PlayerV1,
+    input: String,
+    deadline: Instant,
+) -> AIRunResult {
+    let Ok(input) = CString::new(input) else {
+        unreachable!("the input of AIs doesn't contain null bytes");
+    };
+
+    let call = task::spawn_blocking(move || {
+        // SAFETY: the library is kept loaded by the `Arc` moved here
+        let output = unsafe { call_player(player, &input) };
+
+        drop(library);
+
+        output
+    });
+
+    match time::timeout_at(deadline.into(), call).await {
+        Err(_) => AIRunResult::TimeOut {
+            partial_output: String::new(),
+        },
+        Ok(Err(err)) => {
+            AIRunResult::InvalidOuput(Error::InvalidOutput(format!("Plugin failed: {err}")))
+        }
+        Ok(Ok(output)) => plugin_result(output),
+    }
+}
+
+// what the player wrote, or `None` if it reported an error
+//
+// SAFETY: the library `player` is from has to be loaded
+unsafe fn call_player(player: PlayerV1, input: &CStr) -> Option<Vec<u8>> {
+    let mut output = vec![0u8; OUTPUT_CAPACITY];
+
+    // SAFETY: the input is null-terminated and the output is `capacity` bytes long, as the
+    // interface requires
+    let written = unsafe { player(input.as_ptr(), output.as_mut_ptr().cast(), output.len()) };
+
+    let written = usize::try_from(written).ok()?;
+    output.truncate(written.min(OUTPUT_CAPACITY));
+
+    Some(output)
+}
+
+fn plugin_result(output: Option<Vec<u8>>) -> AIRunResult {
+    let Some(output) = output else {
+        return AIRunResult::InvalidOuput(Error::InvalidOutput(
+            "Plugin reported an error".to_owned(),
+        ));
+    };
+
+    match parse_output(&String::from_utf8_lossy(&output)) {
+        Ok(output) => AIRunResult::Success(output),
+        Err(err) => AIRunResult::InvalidOuput(err),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ptr;
+
+    use super::*;
+
+    // writes its input back, as much as fits
+    unsafe extern "C" fn echo(input: *const c_char, output: *mut c_char, capacity: usize) -> i64 {
+        let input = unsafe { CStr::from_ptr(input) }.to_bytes();
+        let written = input.len().min(capacity);
+
+        unsafe { ptr::copy_nonoverlapping(input.as_ptr(), output.cast(), written) };
+
+        written as i64
+    }
+
+    unsafe extern "C" fn failing(_: *const c_char, _: *mut c_char, _: usize) -> i64 {
+        -1
+    }
+
+    // claims to have written more than it could
+    unsafe extern "C" fn overlong(_: *const c_char, output: *mut c_char, _: usize) -> i64 {
+        unsafe { ptr::copy_nonoverlapping(b"d3\n".as_ptr(), output.cast(), 3) };
+
+        i64::MAX
+    }
+
+    fn call(player: PlayerV1, input: &str) -> Option<Vec<u8>> {
+        let input = CString::new(input).unwrap();
+
+        // SAFETY: the players are functions of this module
+        unsafe { call_player(player, &input) }
+    }
+
+    #[test]
+    fn output_of_the_player() {
+        assert_eq!(call(echo, "c4\n").as_deref(), Some(&b"c4\n"[..]));
+
+        let AIRunResult::Success(output) = plugin_result(call(echo, "c4\nthinking\n")) else {
+            panic!("the output of the plugin was rejected");
+        };
+        assert_eq!(output.mv, Vec2::new(2, 3));
+        assert_eq!(output.notes.as_deref(), Some("thinking"));
+
+        let overlong = call(overlong, "").unwrap();
+        assert_eq!(overlong.len(), OUTPUT_CAPACITY);
+        assert_eq!(&overlong[..3], b"d3\n");
+    }
+
+    #[test]
+    fn errors_of_the_player() {
+        assert_eq!(call(failing, "c4\n"), None);
+        assert!(matches!(
+            plugin_result(None),
+            AIRunResult::InvalidOuput(Error::InvalidOutput(_))
+        ));
+        assert!(matches!(
+            plugin_result(call(echo, "z9\n")),
+            AIRunResult::InvalidOuput(Error::InvalidOutput(_))
+        ));
+    }
+}
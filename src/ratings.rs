@@ -0,0 +1,346 @@
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    fmt::Write as _,
+    fs::{self, OpenOptions},
+    io::Write,
+    path::Path,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use crate::error::Error;
+
+/// The Elo ratings of the AIs at the end of a tournament, as a line of the ratings file written
+/// with `--ratings`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RatingSnapshot {
+    // seconds since the Unix epoch
+    pub timestamp: u64,
+    pub ratings: Vec<Rating>,
+}
+
+/// The rating of a version of an engine.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Rating {
+    // the `name` AI option, or the path of the AI if it isn't named
+    pub engine: String,
+    pub version: Option<String>,
+    pub rating: f64,
+    // the <player> argument of the AI, with which it can be played again, missing in files
+    // written by older versions
+    #[serde(default)]
+    pub arg: Option<String>,
+}
+
+impl RatingSnapshot {
+    pub fn now(ratings: Vec<Rating>) -> Self {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |since| since.as_secs());
+
+        Self { timestamp, ratings }
+    }
+}
+
+pub fn append_snapshot(path: &Path, snapshot: &RatingSnapshot) -> Result<(), Error> {
+    let write_error = |source| Error::Write {
+        what: format!("ratings file '{}'", path.display()),
+        source,
+    };
+
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .map_err(write_error)?;
+
+    let line = serde_json::to_string(snapshot).expect("Error serializing ratings");
+
+    writeln!(file, "{line}").map_err(write_error)
+}
+
+/// Reads the snapshots of a ratings file, oldest first.
+pub fn read_snapshots(path: &Path) -> Result<Vec<RatingSnapshot>, Error> {
+    let content = fs::read_to_string(path).map_err(|source| Error::Read {
+        what: format!("ratings file '{}'", path.display()),
+        source,
+    })?;
+
+    let mut snapshots = content
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(i, line)| {
+            serde_json::from_str(line).map_err(|err| {
+                Error::InvalidValue(format!(
+                    "Line {} of ratings file '{}' is invalid: {err}",
+                    i + 1,
+                    path.display()
+                ))
+            })
+        })
+        .collect::<Result<Vec<RatingSnapshot>, _>>()?;
+
+    snapshots.sort_by_key(|snapshot| snapshot.timestamp);
+
+    Ok(snapshots)
+}
+
+/// The version of an engine with the highest latest rating, of those which can be played again.
+pub fn top_rated(snapshots: &[RatingSnapshot]) -> Option<&Rating> {
+    let mut latest: BTreeMap<EngineVersion, &Rating> = BTreeMap::new();
+
+    // the snapshots are the oldest first, so later ratings replace earlier ones
+    for snapshot in snapshots {
+        for rating in &snapshot.ratings {
+            latest.insert((&rating.engine, rating.version.as_deref()), rating);
+        }
+    }
+
+    latest
+        .into_values()
+        .filter(|rating| rating.arg.is_some())
+        .max_by(|rating1, rating2| rating1.rating.total_cmp(&rating2.rating))
+}
+
+/// The rating of every version of every engine in the tournaments it played, with the change
+/// since its previous one, and in total. The versions of an engine are listed together.
+pub fn history_table(snapshots: &[RatingSnapshot]) -> String {
+    let mut table = String::new();
+
+    for (engine, history) in histories(snapshots) {
+        writeln!(table, "{}", label(engine)).unwrap();
+
+        let mut previous: Option<f64> = None;
+
+        for &(timestamp, rating) in &history {
+            let change = previous.map_or(String::new(), |previous| {
+                format!("{:+.0}", rating - previous)
+            });
+
+            writeln!(table, "  {}  {rating: >5.0} {change: >5}", date(timestamp)).unwrap();
+            previous = Some(rating);
+        }
+
+        if let [(_, first), .., (_, last)] = history[..] {
+            writeln!(
+                table,
+                "  trend: {:+.0} over {} tournaments",
+                last - first,
+                history.len()
+            )
+            .unwrap();
+        }
+
+        table.push('\n');
+    }
+
+    table
+}
+
+/// A line chart of the ratings, with a line for every AI over the tournaments.
+pub fn svg_chart(snapshots: &[RatingSnapshot]) -> String {
+    const WIDTH: f64 = 800.0;
+    const HEIGHT: f64 = 480.0;
+    const MARGIN: f64 = 60.0;
+    // room for the names
+    const LEGEND_WIDTH: f64 = 200.0;
+    const COLORS: [&str; 7] = [
+        "#e69f00", "#56b4e9", "#009e73", "#f0e442", "#0072b2", "#d55e00", "#cc79a7",
+    ];
+
+    let histories = histories(snapshots);
+
+    let ratings = histories.values().flatten().map(|&(_, rating)| rating);
+    let min = ratings.clone().fold(f64::INFINITY, f64::min);
+    let max = ratings.fold(f64::NEG_INFINITY, f64::max);
+    // a single rating is drawn in the middle
+    let (min, max) = if max - min < 1.0 {
+        (min - 50.0, max + 50.0)
+    } else {
+        (min, max)
+    };
+
+    let plot_width = WIDTH - 2.0 * MARGIN - LEGEND_WIDTH;
+    let plot_height = HEIGHT - 2.0 * MARGIN;
+    let last = snapshots.len().saturating_sub(1).max(1) as f64;
+
+    let x = |idx: usize| MARGIN + plot_width * idx as f64 / last;
+    let y = |rating: f64| MARGIN + plot_height * (max - rating) / (max - min);
+
+    let mut svg = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{WIDTH}\" height=\"{HEIGHT}\" font-family=\"sans-serif\" font-size=\"12\">\n\
+         <rect width=\"100%\" height=\"100%\" fill=\"white\"/>\n\
+         <polyline points=\"{MARGIN},{MARGIN} {MARGIN},{} {},{}\" fill=\"none\" stroke=\"black\"/>\n\
+         <text x=\"{}\" y=\"{}\" text-anchor=\"end\">{max:.0}</text>\n\
+         <text x=\"{}\" y=\"{}\" text-anchor=\"end\">{min:.0}</text>\n",
+        MARGIN + plot_height,
+        MARGIN + plot_width,
+        MARGIN + plot_height,
+        MARGIN - 6.0,
+        MARGIN + 4.0,
+        MARGIN - 6.0,
+        MARGIN + plot_height + 4.0,
+    );
+
+    // the tournaments are placed evenly, labeled with their date
+    for (idx, snapshot) in snapshots.iter().enumerate() {
+        writeln!(
+            svg,
+            "<text x=\"{}\" y=\"{}\" text-anchor=\"middle\">{}</text>",
+            x(idx),
+            MARGIN + plot_height + 20.0,
+            &date(snapshot.timestamp)[..10]
+        )
+        .unwrap();
+    }
+
+    for (i, (engine, history)) in histories.iter().enumerate() {
+        let color = COLORS[i % COLORS.len()];
+
+        let points: Vec<String> = history
+            .iter()
+            .map(|&(timestamp, rating)| {
+                let idx = snapshots
+                    .iter()
+                    .position(|snapshot| snapshot.timestamp == timestamp)
+                    .unwrap_or_default();
+
+                format!("{:.1},{:.1}", x(idx), y(rating))
+            })
+            .collect();
+
+        writeln!(
+            svg,
+            "<polyline points=\"{}\" fill=\"none\" stroke=\"{color}\" stroke-width=\"2\"/>",
+            points.join(" ")
+        )
+        .unwrap();
+
+        writeln!(
+            svg,
+            "<text x=\"{}\" y=\"{}\" fill=\"{color}\">{}</text>",
+            WIDTH - LEGEND_WIDTH - MARGIN / 2.0,
+            MARGIN + 16.0 * i as f64,
+            escape_xml(&label(*engine))
+        )
+        .unwrap();
+    }
+
+    svg += "</svg>\n";
+    svg
+}
+
+// an engine and its version
+type EngineVersion<'a> = (&'a str, Option<&'a str>);
+
+// the ratings of every version of every engine with the time of the tournament
+fn histories(snapshots: &[RatingSnapshot]) -> BTreeMap<EngineVersion<'_>, Vec<(u64, f64)>> {
+    let mut histories: BTreeMap<EngineVersion, Vec<(u64, f64)>> = BTreeMap::new();
+
+    for snapshot in snapshots {
+        for rating in &snapshot.ratings {
+            histories
+                .entry((&rating.engine, rating.version.as_deref()))
+                .or_default()
+                .push((snapshot.timestamp, rating.rating));
+        }
+    }
+
+    histories
+}
+
+fn label((engine, version): EngineVersion) -> String {
+    match version {
+        Some(version) => format!("{engine} {version}"),
+        None => engine.to_owned(),
+    }
+}
+
+fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+// like 2023-05-14 18:30, in UTC
+fn date(timestamp: u64) -> String {
+    let days = (timestamp / 86400) as i64;
+    let minutes = timestamp % 86400 / 60;
+
+    // the civil date of days since 1970-01-01, by Howard Hinnant's algorithm
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let day_of_era = z.rem_euclid(146097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+
+    format!(
+        "{year:04}-{month:02}-{day:02} {:02}:{:02}",
+        minutes / 60,
+        minutes % 60
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snapshot(timestamp: u64, ratings: &[(&str, Option<&str>, f64)]) -> RatingSnapshot {
+        RatingSnapshot {
+            timestamp,
+            ratings: ratings
+                .iter()
+                .map(|&(engine, version, rating)| Rating {
+                    engine: engine.to_owned(),
+                    version: version.map(str::to_owned),
+                    rating,
+                    arg: Some(engine.to_owned()),
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn dates() {
+        assert_eq!(date(0), "1970-01-01 00:00");
+        assert_eq!(date(951_782_400 + 3600 + 60), "2000-02-29 01:01");
+        assert_eq!(date(1_684_089_000), "2023-05-14 18:30");
+    }
+
+    #[test]
+    fn history() {
+        let snapshots = [
+            snapshot(0, &[("a", None, 1500.0), ("b", Some("v2"), 1500.0)]),
+            snapshot(86400, &[("a", None, 1530.0), ("b", Some("v1"), 1480.0)]),
+        ];
+
+        let table = history_table(&snapshots);
+
+        assert_eq!(
+            table,
+            "a\n  1970-01-01 00:00   1500      \n  1970-01-02 00:00   1530   +30\n  trend: +30 over 2 tournaments\n\n\
+             b v1\n  1970-01-02 00:00   1480      \n\n\
+             b v2\n  1970-01-01 00:00   1500      \n\n"
+        );
+    }
+
+    #[test]
+    fn top_rated_by_latest_rating() {
+        let mut snapshots = vec![
+            snapshot(0, &[("a", None, 1600.0), ("b", None, 1400.0)]),
+            snapshot(86400, &[("a", None, 1450.0), ("b", None, 1550.0)]),
+        ];
+
+        assert_eq!(top_rated(&snapshots).unwrap().engine, "b");
+
+        snapshots[1].ratings[1].arg = None;
+
+        assert_eq!(top_rated(&snapshots).unwrap().engine, "a");
+    }
+}
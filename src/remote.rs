@@ -0,0 +1,81 @@
+use std::time::*;
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::TcpStream,
+    time,
+};
+
+use crate::{ai::*, error::Error, *};
+
+/// An engine running on another machine, reached over TCP.
+///
+/// For every move a new connection is opened, on which the engine is sent the same input AIs
+/// get on stdin. The engine writes its output the same way as AIs do, then closes the
+/// connection.
+#[derive(Debug)]
+pub struct RemoteEngine {
+    // <host>:<port>
+    pub address: String,
+    pub limit: Limit,
+    pub run_handle: Option<AIRunHandle>,
+    pub deterministic: bool,
+}
+
+impl RemoteEngine {
+    pub fn new(address: String, limit: Limit) -> Self {
+        Self {
+            address,
+            limit,
+            run_handle: None,
+            deterministic: false,
+        }
+    }
+
+    pub fn input(&self, pos: Pos) -> String {
+        engine_input(pos, self.limit, self.deterministic)
+    }
+
+    pub fn run(&mut self, pos: Pos) {
+        let deadline = Instant::now() + self.limit.time_limit();
+
+        self.run_handle = Some(AIRunHandle::spawn(run_remote(
+            self.address.clone(),
+            self.input(pos),
+            deadline,
+        )));
+    }
+
+    pub fn try_clone(&self) -> Result<Self, Error> {
+        match self.run_handle {
+            None => Ok(Self {
+                deterministic: self.deterministic,
+                ..Self::new(self.address.clone(), self.limit)
+            }),
+            Some(_) => Err(Error::CloneRunningEngine(format!("tcp:{}", self.address))),
+        }
+    }
+}
+
+async fn run_remote(address: String, input: String, deadline: Instant) -> AIRunResult {
+    let mut output = Vec::new();
+
+    let result = time::timeout_at(deadline.into(), async {
+        let mut stream = TcpStream::connect(&address).await?;
+
+        stream.write_all(input.as_bytes()).await?;
+        stream.flush().await?;
+        stream.read_to_end(&mut output).await
+    })
+    .await;
+
+    match result {
+        Err(_) => AIRunResult::TimeOut {
+            partial_output: String::from_utf8_lossy(&output).into_owned(),
+        },
+        Ok(Err(err)) => AIRunResult::ConnectionError(err.to_string()),
+        Ok(Ok(_)) => match parse_output(&String::from_utf8_lossy(&output)) {
+            Ok(output) => AIRunResult::Success(output),
+            Err(err) => AIRunResult::InvalidOuput(err),
+        },
+    }
+}
@@ -0,0 +1,230 @@
+use std::{fs::File, io::BufWriter, path::Path, time::Duration};
+
+use image::{
+    codecs::gif::{GifEncoder, Repeat},
+    Delay, Frame, Rgba, RgbaImage,
+};
+use nannou::prelude::Rgba8;
+use othello_gui::{error::Error, heatmap::Heatmap, results::Transcript, *};
+
+use crate::{theme::Palette, RING_FRACTION, TILE_STROKE_WEIGHT};
+
+/// How the frames of a game are drawn.
+#[derive(Debug, Clone, Copy)]
+pub struct RenderOptions {
+    // the width and height of the frames in pixels
+    pub size: u32,
+    // add frames of the disks turning over between the moves
+    pub animate: bool,
+    // how long every move is shown in a GIF
+    pub delay: Duration,
+    pub palette: Palette,
+}
+
+// the frames of turning over the disks flipped by a move
+const FLIP_FRAMES: usize = 6;
+// the final position is shown this many times longer than the others
+const LAST_FRAME_HOLD: u32 = 3;
+
+/// A position of the game, and the one before it, of which the changes are highlighted.
+#[derive(Debug, Clone, Copy)]
+struct Step {
+    pos: Pos,
+    prev: Option<(Pos, Vec2)>,
+}
+
+/// Replays the transcript, returning every frame with how long it is shown.
+pub fn frames(
+    transcript: &Transcript,
+    options: &RenderOptions,
+) -> Result<Vec<(RgbaImage, Duration)>, Error> {
+    let mut steps = vec![Step {
+        pos: transcript.start,
+        prev: None,
+    }];
+
+    for &mv in &transcript.moves {
+        let mut pos = steps.last().expect("steps empty").pos;
+
+        if !pos.is_valid_move(mv) {
+            return Err(Error::InvalidValue(format!(
+                "Game #{} has an invalid move: {}",
+                transcript.game_id,
+                mv.move_string()
+            )));
+        }
+
+        let prev = pos;
+        pos.play(mv);
+
+        steps.push(Step {
+            pos,
+            prev: Some((prev, mv)),
+        });
+    }
+
+    let mut frames = Vec::new();
+
+    for (i, step) in steps.iter().enumerate() {
+        if options.animate && step.prev.is_some() {
+            let flip_delay = options.delay / 2 / FLIP_FRAMES as u32;
+
+            for frame in 0..FLIP_FRAMES {
+                let progress = frame as f32 / FLIP_FRAMES as f32;
+                frames.push((draw_board(step, Some(progress), options), flip_delay));
+            }
+        }
+
+        let delay = if i == steps.len() - 1 {
+            options.delay * LAST_FRAME_HOLD
+        } else {
+            options.delay
+        };
+
+        frames.push((draw_board(step, None, options), delay));
+    }
+
+    Ok(frames)
+}
+
+/// Writes the frames as an animated GIF, looping forever.
+pub fn write_gif(frames: Vec<(RgbaImage, Duration)>, path: &Path) -> Result<(), Error> {
+    let write_error = |err: image::ImageError| Error::Write {
+        what: format!("GIF '{}'", path.display()),
+        source: std::io::Error::other(err),
+    };
+
+    let file = File::create(path).map_err(|source| Error::Write {
+        what: format!("GIF '{}'", path.display()),
+        source,
+    })?;
+
+    let mut encoder = GifEncoder::new(BufWriter::new(file));
+    encoder.set_repeat(Repeat::Infinite).map_err(write_error)?;
+
+    encoder
+        .encode_frames(frames.into_iter().map(|(image, delay)| {
+            Frame::from_parts(
+                image,
+                0,
+                0,
+                Delay::from_numer_denom_ms(delay.as_millis() as u32, 1),
+            )
+        }))
+        .map_err(write_error)
+}
+
+/// Writes the frames as numbered PNGs into `dir`, which is created if needed.
+pub fn write_pngs(frames: Vec<(RgbaImage, Duration)>, dir: &Path) -> Result<(), Error> {
+    std::fs::create_dir_all(dir).map_err(|source| Error::Write {
+        what: format!("directory '{}'", dir.display()),
+        source,
+    })?;
+
+    for (i, (image, _)) in frames.iter().enumerate() {
+        let path = dir.join(format!("{i:04}.png"));
+
+        image.save(&path).map_err(|err| Error::Write {
+            what: format!("PNG '{}'", path.display()),
+            source: std::io::Error::other(err),
+        })?;
+    }
+
+    Ok(())
+}
+
+// the same as the board in the window, without the labels; `flip` is the progress of turning
+// over the flipped disks, if they are being turned over
+fn draw_board(step: &Step, flip: Option<f32>, options: &RenderOptions) -> RgbaImage {
+    let palette = &options.palette;
+    let tile_size = options.size as f32 / 8.0;
+    // the disks are inside the stroke of the tile, with the same space as its width around them
+    let radius = tile_size / 2.0 - TILE_STROKE_WEIGHT * 1.5;
+    let hole = radius * (1.0 - 2.0 * RING_FRACTION);
+
+    RgbaImage::from_fn(options.size, options.size, |px, py| {
+        let (x, y) = (
+            (px as f32 / tile_size) as isize,
+            (py as f32 / tile_size) as isize,
+        );
+        let coor = Vec2::new(x.min(7), y.min(7));
+
+        // relative to the center of the tile
+        let dx = px as f32 + 0.5 - (coor.x as f32 + 0.5) * tile_size;
+        let dy = py as f32 + 0.5 - (coor.y as f32 + 0.5) * tile_size;
+
+        if f32::max(dx.abs(), dy.abs()) > tile_size / 2.0 - TILE_STROKE_WEIGHT {
+            return pixel(palette.tile_stroke);
+        }
+
+        let tile = step.pos.board.get(coor);
+        let before = step.prev.map(|(prev, _)| prev.board.get(coor));
+
+        let fill = match step.prev {
+            Some((_, mv)) if mv == coor => palette.move_highlight,
+            Some(_) if before != Some(tile) => palette.change_highlight,
+            _ => palette.background,
+        };
+
+        // flipped disks shrink to a line, and widen again with their new color
+        let (shown, width) = match (flip, before) {
+            (Some(progress), Some(before)) if before != Tile::Empty && before != tile => (
+                if progress < 0.5 { before } else { tile },
+                (1.0 - 2.0 * progress).abs(),
+            ),
+            _ => (tile, 1.0),
+        };
+
+        let distance = f32::hypot(dx / width.max(f32::EPSILON), dy);
+
+        match shown {
+            Tile::Empty => pixel(fill),
+            _ if distance > radius => pixel(fill),
+            Tile::O if palette.rings && distance < hole => pixel(fill),
+            Tile::X => pixel(palette.dark),
+            _ => pixel(palette.light),
+        }
+    })
+}
+
+/// The board with every square colored by how often it was played on, from the background for
+/// never to the highlight of changes for the most played square.
+pub fn heatmap_image(heatmap: &Heatmap, size: u32, palette: &Palette) -> RgbaImage {
+    let tile_size = size as f32 / 8.0;
+    let max = heatmap.max().max(1) as f32;
+
+    RgbaImage::from_fn(size, size, |px, py| {
+        let (x, y) = (
+            ((px as f32 / tile_size) as usize).min(7),
+            ((py as f32 / tile_size) as usize).min(7),
+        );
+
+        let dx = px as f32 + 0.5 - (x as f32 + 0.5) * tile_size;
+        let dy = py as f32 + 0.5 - (y as f32 + 0.5) * tile_size;
+
+        if f32::max(dx.abs(), dy.abs()) > tile_size / 2.0 - TILE_STROKE_WEIGHT {
+            return pixel(palette.tile_stroke);
+        }
+
+        let heat = heatmap.counts[y][x] as f32 / max;
+        let mix = |from: u8, to: u8| (from as f32 + (to as f32 - from as f32) * heat).round() as u8;
+
+        let (from, to) = (palette.background, palette.change_highlight);
+
+        Rgba([
+            mix(from.color.red, to.color.red),
+            mix(from.color.green, to.color.green),
+            mix(from.color.blue, to.color.blue),
+            255,
+        ])
+    })
+}
+
+fn pixel(color: Rgba8) -> Rgba<u8> {
+    Rgba([
+        color.color.red,
+        color.color.green,
+        color.color.blue,
+        color.alpha,
+    ])
+}
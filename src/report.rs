@@ -0,0 +1,781 @@
+use serde::Serialize;
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs,
+    path::Path,
+    str::FromStr,
+    time::Duration,
+};
+
+use crate::{analysis::AnalyzedMove, error::Error, results::Transcript, system::SystemInfo, *};
+
+/// The summary of a compare or tournament, written as JSON with `--report`.
+#[derive(Debug, Clone, Serialize)]
+pub struct Report {
+    // "compare", "tournament" or "rerun"
+    pub mode: String,
+    pub games: usize,
+    pub standings: Vec<Standing>,
+    // in the order of the standings
+    pub crosstable: Vec<Vec<Option<HeadToHead>>>,
+    pub score_history: Vec<ScorePoint>,
+    // in tournaments, the standings after every round
+    pub rounds: Vec<RoundStandings>,
+    pub system: SystemInfo,
+}
+
+/// The standings once every pairing of AIs played `round` games.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RoundStandings {
+    pub round: usize,
+    pub standings: Vec<Standing>,
+}
+
+/// How `--report` is written: JSON for tools, Markdown to paste into a forum post or an issue,
+/// or an HTML page.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ReportFormat {
+    #[default]
+    Json,
+    Markdown,
+    Html,
+}
+
+impl FromStr for ReportFormat {
+    type Err = String;
+
+    fn from_str(str: &str) -> Result<Self, Self::Err> {
+        match str.to_lowercase().as_str() {
+            "json" => Ok(ReportFormat::Json),
+            "markdown" | "md" => Ok(ReportFormat::Markdown),
+            "html" => Ok(ReportFormat::Html),
+            other => Err(format!("Unknown report format '{other}'")),
+        }
+    }
+}
+
+/// The results of an AI in the finished games. Versions of the same engine, given with the
+/// `name` and `version` AI options, have the same `engine`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Standing {
+    pub id: PlayerId,
+    pub name: String,
+    pub engine: String,
+    pub version: Option<String>,
+    pub score: f32,
+    pub games: usize,
+    pub wins: usize,
+    pub draws: usize,
+    pub losses: usize,
+    // games lost by the failure of the AI
+    pub failures: usize,
+}
+
+/// The score of an AI against another one, and the number of games they played.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct HeadToHead {
+    pub score: f32,
+    pub games: usize,
+}
+
+/// The total score of every AI after a game finished.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ScorePoint {
+    // the number of games finished
+    pub games: usize,
+    pub elapsed_ms: u128,
+    pub scores: BTreeMap<PlayerId, f32>,
+}
+
+/// The scores of the AIs as the games finish, for plotting them over time.
+#[derive(Debug, Clone, Default)]
+pub struct ScoreHistory {
+    pub points: Vec<ScorePoint>,
+    // different programs can have the same name
+    names: BTreeMap<PlayerId, String>,
+}
+
+impl ScoreHistory {
+    /// Adds the result of a finished game, `elapsed` after the start of the run.
+    pub fn record(&mut self, game: &Game, elapsed: Duration) {
+        let mut scores = self
+            .points
+            .last()
+            .map(|point| point.scores.clone())
+            .unwrap_or_default();
+
+        for (player, tile) in game.players.iter().zip(Tile::opponent_iter()) {
+            let id = player.id();
+
+            self.names
+                .entry(id.clone())
+                .or_insert_with(|| player.name());
+            *scores.entry(id).or_default() += game.score_for(tile, Scoring::WinDrawLoss);
+        }
+
+        self.points.push(ScorePoint {
+            games: self.points.len() + 1,
+            elapsed_ms: elapsed.as_millis(),
+            scores,
+        });
+    }
+
+    /// The AIs which played a finished game with their names, in the order of their ids.
+    pub fn players(&self) -> Vec<(&PlayerId, &str)> {
+        self.names
+            .iter()
+            .map(|(id, name)| (id, name.as_str()))
+            .collect()
+    }
+
+    pub fn name(&self, id: &PlayerId) -> &str {
+        self.names.get(id).map_or("", String::as_str)
+    }
+}
+
+/// The standings of the finished games, the highest score first.
+pub fn standings<'a>(games: impl IntoIterator<Item = &'a Game>) -> Vec<Standing> {
+    let mut standings: Vec<Standing> = Vec::new();
+
+    for game in games.into_iter().filter(|game| game.is_game_over()) {
+        for (player, tile) in game.players.iter().zip(Tile::opponent_iter()) {
+            let id = player.id();
+
+            let idx = match standings.iter().position(|standing| standing.id == id) {
+                Some(idx) => idx,
+                None => {
+                    let (engine, version) = player.engine_and_version();
+
+                    standings.push(Standing {
+                        id,
+                        name: player.name(),
+                        engine,
+                        version,
+                        score: 0.0,
+                        games: 0,
+                        wins: 0,
+                        draws: 0,
+                        losses: 0,
+                        failures: 0,
+                    });
+                    standings.len() - 1
+                }
+            };
+
+            let standing = &mut standings[idx];
+            standing.score += game.score_for(tile, Scoring::WinDrawLoss);
+            standing.games += 1;
+
+            match game.winner {
+                Some(winner) if winner == tile => standing.wins += 1,
+                Some(Tile::Empty) => standing.draws += 1,
+                _ => standing.losses += 1,
+            }
+
+            if game.failure.is_some() && game.winner == Some(tile.opponent()) {
+                standing.failures += 1;
+            }
+        }
+    }
+
+    standings.sort_by(|s1, s2| s2.score.total_cmp(&s1.score));
+
+    standings
+}
+
+/// The round of every game, in the order of `games`: the number of games of its pairing of AIs up
+/// to it in the order of ids, so that the first game of every pairing is in round 1.
+pub fn rounds(games: &[Game]) -> Vec<usize> {
+    let mut order: Vec<usize> = (0..games.len()).collect();
+    order.sort_by_key(|&idx| games[idx].id);
+
+    let mut played: HashMap<[PlayerId; 2], usize> = HashMap::new();
+    let mut rounds = vec![0; games.len()];
+
+    for idx in order {
+        let mut pairing = games[idx].players.each_ref().map(Player::id);
+        pairing.sort();
+
+        let count = played.entry(pairing).or_insert(0);
+        *count += 1;
+        rounds[idx] = *count;
+    }
+
+    rounds
+}
+
+/// The standings after every round all of whose games ended, from the first on.
+pub fn round_standings(games: &[Game]) -> Vec<RoundStandings> {
+    let rounds = rounds(games);
+    let in_round = |round: usize| {
+        games
+            .iter()
+            .zip(&rounds)
+            .filter(move |(_, &game_round)| game_round <= round)
+            .map(|(game, _)| game)
+    };
+
+    (1..=rounds.iter().copied().max().unwrap_or(0))
+        .take_while(|&round| in_round(round).all(Game::is_game_over))
+        .map(|round| RoundStandings {
+            round,
+            standings: standings(in_round(round)),
+        })
+        .collect()
+}
+
+/// What every AI of `standings` scored against every other in the finished games, `None` for
+/// itself and for AIs it didn't play.
+pub fn crosstable(games: &[Game], standings: &[Standing]) -> Vec<Vec<Option<HeadToHead>>> {
+    let mut table = vec![vec![None; standings.len()]; standings.len()];
+
+    let idx = |player: &Player| {
+        let id = player.id();
+        standings.iter().position(|standing| standing.id == id)
+    };
+
+    for game in games.iter().filter(|game| game.is_game_over()) {
+        let (Some(x), Some(o)) = (idx(&game.players[0]), idx(&game.players[1])) else {
+            continue;
+        };
+
+        if x == o {
+            continue;
+        }
+
+        for (player, opponent, tile) in [(x, o, Tile::X), (o, x, Tile::O)] {
+            let head_to_head = table[player][opponent].get_or_insert(HeadToHead {
+                score: 0.0,
+                games: 0,
+            });
+
+            head_to_head.score += game.score_for(tile, Scoring::WinDrawLoss);
+            head_to_head.games += 1;
+        }
+    }
+
+    table
+}
+
+pub fn write_report(report: &Report, path: &Path, format: ReportFormat) -> Result<(), Error> {
+    let content = match format {
+        ReportFormat::Json => {
+            serde_json::to_string_pretty(report).expect("Error serializing report") + "\n"
+        }
+        ReportFormat::Markdown => markdown(report),
+        ReportFormat::Html => html(report),
+    };
+
+    fs::write(path, content).map_err(|source| Error::Write {
+        what: format!("report '{}'", path.display()),
+        source,
+    })
+}
+
+/// The standings and the crosstable as Markdown tables, under a heading.
+pub fn markdown(report: &Report) -> String {
+    let mut text = format!("# {}\n\n{} games\n\n", title(report), report.games);
+
+    text += "| # | AI | Score | Games | % | W | D | L | Failures |\n";
+    text += "|--:|----|------:|------:|--:|--:|--:|--:|---------:|\n";
+
+    for (place, standing) in report.standings.iter().enumerate() {
+        text += &format!(
+            "| {} | {} | {:.1} | {} | {:.1} | {} | {} | {} | {} |\n",
+            place + 1,
+            markdown_escape(&standing.name),
+            standing.score,
+            standing.games,
+            percentage(standing),
+            standing.wins,
+            standing.draws,
+            standing.losses,
+            standing.failures
+        );
+    }
+
+    // a single compare only has one pair of AIs, whose result is already in the standings
+    if report.standings.len() > 2 {
+        text += "\n## Crosstable\n\n| # | AI |";
+        text += &(1..=report.standings.len())
+            .map(|place| format!(" {place} |"))
+            .collect::<String>();
+        text += "\n|--:|----|";
+        text += &"--:|".repeat(report.standings.len());
+        text += "\n";
+
+        for (place, (standing, row)) in report.standings.iter().zip(&report.crosstable).enumerate()
+        {
+            text += &format!("| {} | {} |", place + 1, markdown_escape(&standing.name));
+
+            for head_to_head in row {
+                text += &format!(" {} |", head_to_head_text(*head_to_head));
+            }
+
+            text += "\n";
+        }
+    }
+
+    for round in &report.rounds {
+        text += &format!(
+            "\n## After round {}\n\n| # | AI | Score | Games |\n|--:|----|------:|------:|\n",
+            round.round
+        );
+
+        for (place, standing) in round.standings.iter().enumerate() {
+            text += &format!(
+                "| {} | {} | {:.1} | {} |\n",
+                place + 1,
+                markdown_escape(&standing.name),
+                standing.score,
+                standing.games
+            );
+        }
+    }
+
+    text
+}
+
+/// A standalone page with the standings and the crosstable.
+pub fn html(report: &Report) -> String {
+    let title = html_escape(&title(report));
+
+    let mut standings = String::from(
+        "<tr><th>#</th><th>AI</th><th>Score</th><th>Games</th><th>%</th><th>W</th><th>D</th><th>L</th><th>Failures</th></tr>\n",
+    );
+
+    for (place, standing) in report.standings.iter().enumerate() {
+        standings += &format!(
+            "<tr><td>{}</td><td>{}</td><td>{:.1}</td><td>{}</td><td>{:.1}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>\n",
+            place + 1,
+            html_escape(&standing.name),
+            standing.score,
+            standing.games,
+            percentage(standing),
+            standing.wins,
+            standing.draws,
+            standing.losses,
+            standing.failures
+        );
+    }
+
+    let mut crosstable = String::new();
+
+    if report.standings.len() > 2 {
+        crosstable += "<h2>Crosstable</h2>\n<table>\n<tr><th>#</th><th>AI</th>";
+        crosstable += &(1..=report.standings.len())
+            .map(|place| format!("<th>{place}</th>"))
+            .collect::<String>();
+        crosstable += "</tr>\n";
+
+        for (place, (standing, row)) in report.standings.iter().zip(&report.crosstable).enumerate()
+        {
+            crosstable += &format!(
+                "<tr><td>{}</td><td>{}</td>",
+                place + 1,
+                html_escape(&standing.name)
+            );
+
+            for head_to_head in row {
+                crosstable += &format!("<td>{}</td>", head_to_head_text(*head_to_head));
+            }
+
+            crosstable += "</tr>\n";
+        }
+
+        crosstable += "</table>\n";
+    }
+
+    let mut rounds = String::new();
+
+    for round in &report.rounds {
+        rounds += &format!(
+            "<h2>After round {}</h2>\n<table>\n<tr><th>#</th><th>AI</th><th>Score</th><th>Games</th></tr>\n",
+            round.round
+        );
+
+        for (place, standing) in round.standings.iter().enumerate() {
+            rounds += &format!(
+                "<tr><td>{}</td><td>{}</td><td>{:.1}</td><td>{}</td></tr>\n",
+                place + 1,
+                html_escape(&standing.name),
+                standing.score,
+                standing.games
+            );
+        }
+
+        rounds += "</table>\n";
+    }
+
+    format!(
+        r#"<!DOCTYPE html>
+<html lang="en">
+<head>
+<meta charset="utf-8">
+<title>{title}</title>
+<style>
+    body {{ font-family: sans-serif; margin: 24px; }}
+    table {{ border-collapse: collapse; margin-bottom: 24px; }}
+    td, th {{ border: 1px solid #ccc; padding: 4px 8px; text-align: right; }}
+    td:nth-child(2), th:nth-child(2) {{ text-align: left; }}
+</style>
+</head>
+<body>
+<h1>{title}</h1>
+<p>{games} games</p>
+<table>
+{standings}</table>
+{crosstable}{rounds}</body>
+</html>
+"#,
+        games = report.games,
+    )
+}
+
+/// A standalone page showing a game move by move, with the board drawn by a script, so that it
+/// can be reviewed in any browser. The positions are played here and embedded in the page.
+pub fn game_viewer(transcript: &Transcript, analysis: &[AnalyzedMove]) -> Result<String, Error> {
+    let positions: Vec<String> = transcript
+        .game()?
+        .history
+        .iter()
+        .enumerate()
+        .map(|(ply, (pos, mv))| {
+            let analyzed = analysis.iter().find(|analyzed| analyzed.ply == ply);
+            viewer_position(pos, *mv, analyzed)
+        })
+        .collect();
+
+    let [x_name, o_name] = transcript.players.each_ref().map(|name| html_escape(name));
+    let title = format!("Game #{}: {x_name} vs {o_name}", transcript.game_id);
+
+    Ok(format!(
+        r#"<!DOCTYPE html>
+<html lang="en">
+<head>
+<meta charset="utf-8">
+<title>{title}</title>
+<style>
+    body {{ font-family: sans-serif; margin: 24px; }}
+    #viewer {{ display: flex; gap: 24px; align-items: flex-start; }}
+    #board {{ display: grid; grid-template-columns: repeat(8, 48px); grid-auto-rows: 48px; gap: 2px; padding: 2px; background: #222; }}
+    .square {{ position: relative; background: #2e7d32; }}
+    .last {{ outline: 3px solid #f44336; outline-offset: -3px; }}
+    .disk {{ position: absolute; inset: 5px; border-radius: 50%; }}
+    .X {{ background: #111; }}
+    .O {{ background: #eee; }}
+    #moves {{ max-width: 320px; max-height: 400px; overflow-y: auto; line-height: 1.6; }}
+    #moves span {{ cursor: pointer; padding: 0 4px; }}
+    #moves .current {{ background: #ffeb3b; }}
+    #moves .blunder {{ color: #c62828; font-weight: bold; }}
+</style>
+</head>
+<body>
+<h1>{title}</h1>
+<p>X (black): {x_name}<br>O (white): {o_name}</p>
+<p id="status"></p>
+<div id="viewer">
+<div id="board"></div>
+<div>
+<p><button id="first">|&lt;</button> <button id="prev">&lt;</button> <button id="next">&gt;</button> <button id="last">&gt;|</button></p>
+<div id="moves"></div>
+</div>
+</div>
+<script>
+// every position with the square of the move leading to it, -1 for none, and the player to move,
+// - once the game is over
+const positions = [
+{positions}];
+
+const squares = [];
+const links = [];
+let current = 0;
+
+for (let i = 0; i < 64; i++) {{
+    const square = document.createElement("div");
+    square.className = "square";
+    document.getElementById("board").appendChild(square);
+    squares.push(square);
+}}
+
+positions.slice(1).forEach((position, i) => {{
+    const link = document.createElement("span");
+    link.textContent = (i + 1) + ". " + position.move;
+    link.onclick = () => show(i + 1);
+
+    if (position.blunder) {{
+        link.textContent += "??";
+        link.title = position.blunder;
+        link.classList.add("blunder");
+    }}
+
+    document.getElementById("moves").append(link, " ");
+    links.push(link);
+}});
+
+function show(index) {{
+    current = Math.max(0, Math.min(positions.length - 1, index));
+    const position = positions[current];
+
+    squares.forEach((square, i) => {{
+        square.replaceChildren();
+        square.classList.toggle("last", i === position.square);
+
+        if (position.board[i] !== ".") {{
+            const disk = document.createElement("div");
+            disk.className = "disk " + position.board[i];
+            square.appendChild(disk);
+        }}
+    }});
+
+    links.forEach((link, i) => link.classList.toggle("current", i + 1 === current));
+
+    const count = tile => position.board.split(tile).length - 1;
+    const [x, o] = [count("X"), count("O")];
+    let state = position.next + " to move";
+
+    if (position.next === "-") {{
+        state = x === o ? "draw" : (x > o ? "X" : "O") + " wins";
+    }}
+
+    document.getElementById("status").textContent =
+        "Move " + current + " of " + (positions.length - 1) + " - X " + x + " : O " + o + " - " + state;
+}}
+
+document.getElementById("first").onclick = () => show(0);
+document.getElementById("prev").onclick = () => show(current - 1);
+document.getElementById("next").onclick = () => show(current + 1);
+document.getElementById("last").onclick = () => show(positions.length - 1);
+
+document.addEventListener("keydown", event => {{
+    const target = {{ ArrowLeft: current - 1, ArrowRight: current + 1, Home: 0, End: positions.length - 1 }}[event.key];
+
+    if (target !== undefined) {{
+        show(target);
+        event.preventDefault();
+    }}
+}});
+
+show(0);
+</script>
+</body>
+</html>
+"#,
+        positions = positions.concat(),
+    ))
+}
+
+// a line of the positions of the game viewer, with the move leading to it described if it was a
+// blunder
+fn viewer_position(pos: &Pos, mv: Option<Vec2>, analyzed: Option<&AnalyzedMove>) -> String {
+    let board: String = (0..8)
+        .flat_map(|y| (0..8).map(move |x| tile_char(pos.board.get(Vec2::new(x, y)))))
+        .collect();
+    let next = if pos.is_game_over() {
+        '-'
+    } else {
+        tile_char(pos.next_player)
+    };
+
+    let blunder = match analyzed {
+        Some(analyzed) if analyzed.is_blunder() => format!(
+            "Blunder: loses {:.1} discs, {} was best",
+            analyzed.loss,
+            analyzed.best_move.move_string()
+        ),
+        _ => String::new(),
+    };
+
+    format!(
+        "    {{ board: \"{board}\", square: {}, move: \"{}\", next: \"{next}\", blunder: \"{blunder}\" }},\n",
+        mv.map_or(-1, |mv| mv.y * 8 + mv.x),
+        mv.map_or(String::new(), |mv| mv.move_string())
+    )
+}
+
+fn title(report: &Report) -> String {
+    let mode = match report.mode.as_str() {
+        "compare" => "Compare",
+        "tournament" => "Tournament",
+        "rerun" => "Rerun",
+        other => other,
+    };
+
+    format!("{mode} results")
+}
+
+fn percentage(standing: &Standing) -> f32 {
+    if standing.games == 0 {
+        return 0.0;
+    }
+
+    standing.score / standing.games as f32 * 100.0
+}
+
+// e.g. `1.5/2`, or `-` if the AIs didn't play
+fn head_to_head_text(head_to_head: Option<HeadToHead>) -> String {
+    match head_to_head {
+        Some(HeadToHead { score, games }) => format!("{score:.1}/{games}"),
+        None => "-".to_owned(),
+    }
+}
+
+// names are paths, which may contain the separator of the cells
+fn markdown_escape(text: &str) -> String {
+    text.replace('|', "\\|")
+}
+
+fn html_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analysis::Phase;
+
+    fn finished(winner: Tile) -> Game {
+        let mut game = Game::new(0, [Player::Human, Player::Human]);
+        game.winner = Some(winner);
+        game
+    }
+
+    #[test]
+    fn cumulative_scores() {
+        let mut history = ScoreHistory::default();
+        history.record(&finished(Tile::X), Duration::from_secs(1));
+        history.record(&finished(Tile::Empty), Duration::from_secs(2));
+
+        let id = Player::Human.id();
+        let name = Player::Human.name();
+
+        assert_eq!(history.points.len(), 2);
+        assert_eq!(history.points[1].games, 2);
+        assert_eq!(history.points[1].elapsed_ms, 2000);
+        // humans are only known by their names, so their scores add up
+        assert_eq!(history.points[1].scores[&id], 2.0);
+        assert_eq!(history.players(), vec![(&id, name.as_str())]);
+    }
+
+    #[test]
+    fn standings_after_rounds() {
+        let ai = |path: &str| {
+            Player::AI(AI::new(
+                EngineConfig::new(path.into()),
+                Limit::Time(Duration::from_secs(1)),
+            ))
+        };
+
+        // a plays b twice, and c once
+        let mut games: Vec<Game> = [("a", "b"), ("b", "a"), ("a", "c")]
+            .into_iter()
+            .enumerate()
+            .map(|(id, (x, o))| Game::new(id, [ai(x), ai(o)]))
+            .collect();
+
+        assert_eq!(rounds(&games), vec![1, 2, 1]);
+        assert!(round_standings(&games).is_empty());
+
+        games[0].winner = Some(Tile::X);
+        games[2].winner = Some(Tile::O);
+
+        let after = round_standings(&games);
+
+        assert_eq!(after.len(), 1);
+        assert_eq!(after[0].round, 1);
+        assert_eq!(after[0].standings.iter().map(|s| s.games).sum::<usize>(), 4);
+        assert_eq!(after[0].standings[2].score, 0.0);
+
+        games[1].winner = Some(Tile::Empty);
+
+        assert_eq!(round_standings(&games).len(), 2);
+    }
+
+    #[test]
+    fn markdown_tables() {
+        let standing = |name: &str, score, wins| Standing {
+            id: Player::Human.id(),
+            name: name.to_owned(),
+            engine: name.to_owned(),
+            version: None,
+            score,
+            games: 4,
+            wins,
+            draws: 0,
+            losses: 4 - wins,
+            failures: 0,
+        };
+        let head_to_head = |score| Some(HeadToHead { score, games: 2 });
+
+        let report = Report {
+            mode: "tournament".to_owned(),
+            games: 6,
+            standings: vec![
+                standing("a|b", 3.0, 3),
+                standing("c", 2.0, 2),
+                standing("d", 1.0, 1),
+            ],
+            crosstable: vec![
+                vec![None, head_to_head(1.0), head_to_head(2.0)],
+                vec![head_to_head(1.0), None, head_to_head(1.0)],
+                vec![head_to_head(0.0), head_to_head(1.0), None],
+            ],
+            score_history: Vec::new(),
+            rounds: Vec::new(),
+            system: SystemInfo::capture(),
+        };
+
+        let markdown = markdown(&report);
+
+        assert!(markdown.starts_with("# Tournament results\n\n6 games\n"));
+        assert!(markdown.contains("| 1 | a\\|b | 3.0 | 4 | 75.0 | 3 | 0 | 1 | 0 |\n"));
+        assert!(markdown.contains("| 3 | d | 0.0/2 | 1.0/2 | - |\n"));
+    }
+
+    #[test]
+    fn game_viewer_positions() {
+        let mut moves = Vec::new();
+        let mut pos = Pos::new();
+
+        for _ in 0..3 {
+            let mv = pos.valid_moves()[0];
+            moves.push(mv);
+            pos.play(mv);
+        }
+
+        let mut transcript = Transcript {
+            game_id: 7,
+            players: ["a<b".to_owned(), "c".to_owned()],
+            start: Pos::new(),
+            moves: moves.clone(),
+        };
+
+        let page = game_viewer(&transcript, &[]).unwrap();
+
+        assert!(page.contains("<title>Game #7: a&lt;b vs c</title>"));
+        assert_eq!(page.matches("{ board: ").count(), 4);
+        assert!(page.contains(&format!("move: \"{}\"", moves[2].move_string())));
+        assert_eq!(page.matches("blunder: \"\"").count(), 4);
+
+        let blunder = AnalyzedMove {
+            ply: 2,
+            mover: Tile::O,
+            mv: moves[1],
+            phase: Phase::Opening,
+            loss: 8.0,
+            best_move: moves[0],
+        };
+        let page = game_viewer(&transcript, &[blunder]).unwrap();
+        assert!(page.contains(&format!(
+            "blunder: \"Blunder: loses 8.0 discs, {} was best\"",
+            moves[0].move_string()
+        )));
+
+        // the second move again, which isn't valid after the first two
+        transcript.moves.push(moves[1]);
+        assert!(game_viewer(&transcript, &[]).is_err());
+    }
+}
@@ -0,0 +1,428 @@
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    fs::{self, File, OpenOptions},
+    io::{self, Write},
+    path::{Path, PathBuf},
+};
+
+use crate::{error::Error, observer::*, system::SystemInfo, *};
+
+/// Appends a JSON line to a file for every finished game, with everything needed to interpret
+/// the result weeks later: which builds of the engines played, and from which opening.
+#[derive(Debug)]
+pub struct ResultsWriter {
+    target: Target,
+    // hashing is slow for large binaries, so every file is hashed only once
+    hashes: HashMap<PathBuf, Option<String>>,
+    system: SystemInfo,
+}
+
+#[derive(Debug)]
+enum Target {
+    Append(File),
+    // when rerunning failed games, their lines are replaced by the new results
+    Replace {
+        path: PathBuf,
+        lines: Vec<String>,
+        // game ids with the index of the line of their old result
+        pending: Vec<(usize, usize)>,
+    },
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct GameRecord {
+    game_id: usize,
+    players: Vec<PlayerRecord>,
+    opening: Opening,
+    seed: Option<u64>,
+    moves: Vec<String>,
+    // "X", "O" or "draw"
+    winner: String,
+    // null if the game was played to the end
+    failure: Option<Failure>,
+    // ended early with the result of perfect play
+    #[serde(default)]
+    adjudicated: bool,
+    // the X and O disks on the final board
+    #[serde(default)]
+    disks: [usize; 2],
+    // the machine the game was played on, null in files written by older versions
+    #[serde(default)]
+    system: Option<SystemInfo>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct PlayerRecord {
+    name: String,
+    // the <player> and <max time> arguments, with which the game can be played again
+    arg: Option<String>,
+    limit: Option<String>,
+    version: Option<String>,
+    hash: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Opening {
+    board: Vec<String>,
+    next_player: char,
+    // the moves leading to the opening from the initial position, if known
+    moves: Option<Vec<String>>,
+}
+
+impl ResultsWriter {
+    pub fn create(path: &Path) -> io::Result<Self> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+
+        Ok(Self {
+            target: Target::Append(file),
+            hashes: HashMap::new(),
+            system: SystemInfo::capture(),
+        })
+    }
+
+    /// Reads the games of a results file lost by a failing AI, to be played again. Their results
+    /// are replaced in the file as the new games end.
+    pub fn rerun(path: &Path) -> Result<(Self, Vec<FailedGame>), Error> {
+        let content = fs::read_to_string(path).map_err(|source| Error::Read {
+            what: format!("results file '{}'", path.display()),
+            source,
+        })?;
+
+        let lines: Vec<String> = content.lines().map(str::to_owned).collect();
+        let mut pending = Vec::new();
+        let mut failed = Vec::new();
+
+        for (i, line) in lines.iter().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            let record: GameRecord = serde_json::from_str(line).map_err(|err| {
+                Error::InvalidValue(format!(
+                    "Line {} of results file '{}' is invalid: {err}",
+                    i + 1,
+                    path.display()
+                ))
+            })?;
+
+            if record.failure.is_none() {
+                continue;
+            }
+
+            pending.push((record.game_id, i));
+            failed.push(record.failed_game()?);
+        }
+
+        let writer = Self {
+            target: Target::Replace {
+                path: path.to_owned(),
+                lines,
+                pending,
+            },
+            hashes: HashMap::new(),
+            system: SystemInfo::capture(),
+        };
+
+        Ok((writer, failed))
+    }
+
+    fn hash(&mut self, player: &Player) -> Option<String> {
+        let path = player.engine_path()?;
+
+        self.hashes
+            .entry(path.to_owned())
+            .or_insert_with(|| run::file_hash(path).ok())
+            .clone()
+    }
+
+    fn record(&mut self, game: &Game) -> GameRecord {
+        let (start, _) = game.history[0];
+
+        GameRecord {
+            game_id: game.id,
+            players: game
+                .players
+                .iter()
+                .zip(&game.versions)
+                .map(|(player, version)| PlayerRecord {
+                    name: player.name(),
+                    arg: player.arg(),
+                    limit: player.limit().map(Limit::arg),
+                    version: version.clone(),
+                    hash: self.hash(player),
+                })
+                .collect(),
+            opening: Opening {
+                board: board_rows(&start.board),
+                next_player: tile_char(start.next_player),
+                // the moves of games from other start setups don't lead there from the initial
+                // position, so only the board is known
+                moves: game
+                    .opening
+                    .as_ref()
+                    .filter(|line| leads_to(line, &start))
+                    .map(|line| line.iter().map(|mv| mv.move_string()).collect()),
+            },
+            seed: game.seed,
+            moves: game.moves().iter().map(|mv| mv.move_string()).collect(),
+            winner: winner_string(game.winner.expect("Expected a finished game")),
+            failure: game.failure,
+            adjudicated: game.adjudicated,
+            disks: game.disk_counts(),
+            system: Some(self.system.clone()),
+        }
+    }
+}
+
+impl GameRecord {
+    fn failed_game(&self) -> Result<FailedGame, Error> {
+        let players = self
+            .players
+            .iter()
+            .map(|player| match (&player.arg, &player.limit) {
+                (Some(arg), Some(limit)) => Ok(vec![arg.clone(), limit.clone()]),
+                (Some(arg), None) => Ok(vec![arg.clone()]),
+                _ => Err(Error::InvalidValue(format!(
+                    "Game #{} can't be played again, as '{}' can't be recreated",
+                    self.game_id, player.name
+                ))),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(FailedGame {
+            game_id: self.game_id,
+            players: players.try_into().map_err(|_| {
+                Error::InvalidValue(format!("Game #{} doesn't have two players", self.game_id))
+            })?,
+            opening: self.opening.pos().ok_or_else(|| {
+                Error::InvalidValue(format!("Game #{} has an invalid opening", self.game_id))
+            })?,
+            seed: self.seed,
+            opening_line: self.opening.line(),
+        })
+    }
+
+    fn transcript(&self) -> Result<Transcript, Error> {
+        let game_id = self.game_id;
+        let invalid =
+            |what: &str| Error::InvalidValue(format!("Game #{game_id} has an invalid {what}"));
+
+        let players: Vec<String> = self
+            .players
+            .iter()
+            .map(|player| player.name.clone())
+            .collect();
+
+        Ok(Transcript {
+            game_id,
+            players: players
+                .try_into()
+                .map_err(|_| invalid("number of players"))?,
+            start: self.opening.pos().ok_or_else(|| invalid("opening"))?,
+            moves: parse_moves(&self.moves).ok_or_else(|| invalid("move"))?,
+        })
+    }
+
+    // the moves from the initial position, if they are known
+    fn full_line(&self) -> Option<Vec<Vec2>> {
+        let mut line = self.opening.line()?;
+        line.extend(parse_moves(&self.moves)?);
+        Some(line)
+    }
+}
+
+impl Opening {
+    fn pos(&self) -> Option<Pos> {
+        if self.board.len() != 8 {
+            return None;
+        }
+
+        parse_pos(&self.board.concat(), self.next_player)
+    }
+
+    fn line(&self) -> Option<Vec<Vec2>> {
+        match &self.moves {
+            Some(moves) => parse_moves(moves),
+            // games not started from an opening, e.g. in tournaments
+            None => {
+                let initial = Pos::new();
+
+                let is_initial = self.board == board_rows(&initial.board)
+                    && self.next_player == tile_char(initial.next_player);
+
+                is_initial.then(Vec::new)
+            }
+        }
+    }
+}
+
+// whether `line` played from the initial position reaches `pos`
+fn leads_to(line: &[Vec2], pos: &Pos) -> bool {
+    let mut reached = Pos::new();
+
+    for &mv in line {
+        if !reached.is_valid_move(mv) {
+            return false;
+        }
+
+        reached.play(mv);
+    }
+
+    same_pos(&reached, pos)
+}
+
+fn parse_moves(moves: &[String]) -> Option<Vec<Vec2>> {
+    moves.iter().map(|mv| net::parse_move(mv)).collect()
+}
+
+/// A game of a results file, as the moves from the initial position and the winner.
+#[derive(Debug)]
+pub struct PlayedGame {
+    pub moves: Vec<Vec2>,
+    pub winner: Tile,
+}
+
+/// Reads the games of a results file, except those whose moves from the initial position aren't
+/// known, of which the count is returned.
+pub fn read_played_games(path: &Path) -> Result<(Vec<PlayedGame>, usize), Error> {
+    let mut games = Vec::new();
+    let mut skipped = 0;
+
+    for record in read_records(path)? {
+        let winner = match record.winner.as_str() {
+            "draw" => Some(Tile::Empty),
+            winner => winner.chars().next().and_then(parse_tile),
+        };
+
+        match (record.full_line(), winner) {
+            (Some(moves), Some(winner)) => games.push(PlayedGame { moves, winner }),
+            _ => skipped += 1,
+        }
+    }
+
+    Ok((games, skipped))
+}
+
+/// A game of a results file, as its starting position and the moves played from it.
+#[derive(Debug)]
+pub struct Transcript {
+    pub game_id: usize,
+    // the names of the players, indexed by their tile
+    pub players: [String; 2],
+    pub start: Pos,
+    pub moves: Vec<Vec2>,
+}
+
+impl Transcript {
+    /// The game as it was played, with people as players, e.g. to replay or analyze it.
+    pub fn game(&self) -> Result<Game, Error> {
+        let mut game = Game::from_pos(self.game_id, [Player::Human, Player::Human], self.start);
+
+        for &mv in &self.moves {
+            if !game.pos.is_valid_move(mv) {
+                return Err(Error::InvalidValue(format!(
+                    "Game #{} has an invalid move: {}",
+                    self.game_id,
+                    mv.move_string()
+                )));
+            }
+
+            game.pos.play(mv);
+            game.history.push((game.pos, Some(mv)));
+        }
+
+        Ok(game)
+    }
+}
+
+/// Reads the game with `game_id` from a results file.
+pub fn read_transcript(path: &Path, game_id: usize) -> Result<Transcript, Error> {
+    let record = read_records(path)?
+        .into_iter()
+        .find(|record| record.game_id == game_id)
+        .ok_or_else(|| {
+            Error::InvalidValue(format!(
+                "There is no game #{game_id} in results file '{}'",
+                path.display()
+            ))
+        })?;
+
+    record.transcript()
+}
+
+/// Reads all games of a results file, except invalid ones, of which the count is returned.
+pub fn read_transcripts(path: &Path) -> Result<(Vec<Transcript>, usize), Error> {
+    let mut transcripts = Vec::new();
+    let mut skipped = 0;
+
+    for record in read_records(path)? {
+        match record.transcript() {
+            Ok(transcript) => transcripts.push(transcript),
+            Err(_) => skipped += 1,
+        }
+    }
+
+    Ok((transcripts, skipped))
+}
+
+fn read_records(path: &Path) -> Result<Vec<GameRecord>, Error> {
+    let content = fs::read_to_string(path).map_err(|source| Error::Read {
+        what: format!("results file '{}'", path.display()),
+        source,
+    })?;
+
+    content
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(i, line)| {
+            serde_json::from_str(line).map_err(|err| {
+                Error::InvalidValue(format!(
+                    "Line {} of results file '{}' is invalid: {err}",
+                    i + 1,
+                    path.display()
+                ))
+            })
+        })
+        .collect()
+}
+
+/// A game of a results file to be played again, with the arguments its players were given.
+#[derive(Debug)]
+pub struct FailedGame {
+    pub game_id: usize,
+    pub players: [Vec<String>; 2],
+    pub opening: Pos,
+    pub opening_line: Option<Vec<Vec2>>,
+    pub seed: Option<u64>,
+}
+
+impl GameObserver for ResultsWriter {
+    fn on_game_end(&mut self, game: &Game) {
+        let record = self.record(game);
+        let line = serde_json::to_string(&record).expect("Error serializing game result");
+
+        let result = match &mut self.target {
+            Target::Append(file) => writeln!(file, "{line}"),
+            Target::Replace {
+                path,
+                lines,
+                pending,
+            } => {
+                match pending.iter().position(|&(id, _)| id == game.id) {
+                    Some(idx) => lines[pending.remove(idx).1] = line,
+                    None => lines.push(line),
+                }
+
+                // rewritten every time, so that stopping the rerun doesn't lose results
+                fs::write(path, lines.join("\n") + "\n")
+            }
+        };
+
+        // losing a result shouldn't stop the games, so failures are only reported
+        if let Err(err) = result {
+            eprintln!("Unable to write result of game #{}: {err}", game.id);
+        }
+    }
+}
@@ -0,0 +1,162 @@
+use std::{fmt, str::FromStr};
+
+use crate::*;
+
+/// The rules a game is played by, which decide how it starts and which moves are valid.
+///
+/// The core library only knows the rules of Othello, the other rules are built on top of them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Rules {
+    #[default]
+    Othello,
+    // classic Reversi: the board starts empty, and the players take turns placing the first four
+    // disks on the center squares, without turning any over, then the game goes on as Othello
+    Reversi,
+}
+
+impl Rules {
+    pub fn start(self) -> Pos {
+        let mut pos = Pos::new();
+
+        if self == Rules::Reversi {
+            for coor in center() {
+                pos.board.set(coor, Tile::Empty);
+            }
+        }
+
+        pos
+    }
+
+    /// Whether the first disks are still being placed in the center.
+    pub fn is_placing(self, pos: &Pos) -> bool {
+        self == Rules::Reversi && center().any(|coor| pos.board.get(coor) == Tile::Empty)
+    }
+
+    pub fn valid_moves(self, pos: &Pos) -> Vec<Vec2> {
+        if self.is_placing(pos) {
+            center()
+                .filter(|&coor| pos.board.get(coor) == Tile::Empty)
+                .collect()
+        } else {
+            pos.valid_moves()
+        }
+    }
+
+    pub fn is_valid_move(self, pos: &Pos, mv: Vec2) -> bool {
+        if self.is_placing(pos) {
+            center().any(|coor| coor == mv) && pos.board.get(mv) == Tile::Empty
+        } else {
+            pos.is_valid_move(mv)
+        }
+    }
+
+    pub fn play(self, pos: &mut Pos, mv: Vec2) {
+        if !self.is_placing(pos) {
+            pos.play(mv);
+            return;
+        }
+
+        pos.board.set(mv, pos.next_player);
+        pos.next_player = pos.next_player.opponent();
+        *pos = self.with_next_player(*pos);
+    }
+
+    pub fn is_game_over(self, pos: &Pos) -> bool {
+        !self.is_placing(pos) && pos.is_game_over()
+    }
+
+    /// The position with the player to move as these rules decide it, e.g. for positions read
+    /// from diagrams, which are read by the rules of Othello.
+    pub fn with_next_player(self, mut pos: Pos) -> Pos {
+        if self.is_placing(&pos) {
+            // X places first
+            let placed = center()
+                .filter(|&coor| pos.board.get(coor) != Tile::Empty)
+                .count();
+            pos.next_player = if placed % 2 == 0 { Tile::X } else { Tile::O };
+        } else if self == Rules::Reversi
+            && pos.next_player != Tile::Empty
+            && pos.valid_moves().is_empty()
+        {
+            // after placing, the player without a move passes, as in Othello
+            pos.next_player = pos.next_player.opponent();
+
+            if pos.valid_moves().is_empty() {
+                pos.next_player = Tile::Empty;
+            }
+        }
+
+        pos
+    }
+}
+
+impl FromStr for Rules {
+    type Err = String;
+
+    fn from_str(str: &str) -> Result<Self, Self::Err> {
+        match str.to_lowercase().as_str() {
+            "othello" => Ok(Rules::Othello),
+            "reversi" => Ok(Rules::Reversi),
+            other => Err(format!("Unknown rules '{other}'")),
+        }
+    }
+}
+
+impl fmt::Display for Rules {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Rules::Othello => write!(f, "othello"),
+            Rules::Reversi => write!(f, "reversi"),
+        }
+    }
+}
+
+// the four squares in the middle of the board
+fn center() -> impl Iterator<Item = Vec2> {
+    [(3, 3), (4, 3), (3, 4), (4, 4)]
+        .into_iter()
+        .map(|(x, y)| Vec2::new(x, y))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reversi_placement() {
+        let rules = Rules::Reversi;
+        let mut pos = rules.start();
+
+        assert!(rules.is_placing(&pos));
+        assert!(!rules.is_game_over(&pos));
+        assert_eq!(rules.valid_moves(&pos).len(), 4);
+        assert!(!rules.is_valid_move(&pos, Vec2::new(0, 0)));
+
+        // the disks of a player are placed side by side, unlike in the initial position of Othello
+        for mv in [(3, 3), (3, 4), (4, 3), (4, 4)] {
+            let player = pos.next_player;
+            let mv = Vec2::new(mv.0, mv.1);
+
+            assert!(rules.is_valid_move(&pos, mv));
+            rules.play(&mut pos, mv);
+            assert_eq!(pos.board.get(mv), player);
+        }
+
+        assert!(!rules.is_placing(&pos));
+        assert_eq!(pos.next_player, Tile::X);
+        assert_eq!(rules.valid_moves(&pos), pos.valid_moves());
+        assert!(!pos.valid_moves().is_empty());
+
+        assert!(same_pos(&Rules::Othello.start(), &Pos::new()));
+    }
+
+    #[test]
+    fn placing_player_from_board() {
+        let rules = Rules::Reversi;
+        let mut pos = rules.start();
+        rules.play(&mut pos, Vec2::new(3, 3));
+
+        pos.next_player = Tile::Empty;
+        assert_eq!(rules.with_next_player(pos).next_player, Tile::O);
+    }
+}
@@ -0,0 +1,424 @@
+use crate::{engine_config::*, error::Error};
+use sha2::{Digest, Sha256};
+use std::{
+    env, fs, io,
+    path::{Component, Path, PathBuf},
+    process::Command,
+};
+
+/// Creates the command launching the AI.
+///
+/// If no interpreter is configured, it is chosen based on the extension for common script
+/// languages. Otherwise executables are spawned directly. Batch files are run through `cmd` on
+/// Windows, and on other platforms files without the executable bit are run with the
+/// interpreter in their shebang line, or with `sh` if they don't have one.
+pub fn command(config: &EngineConfig) -> Command {
+    let path = match (absolute_path(&config.path), &config.working_dir) {
+        (Some(path), _) => path,
+        // relative paths are resolved differently on different platforms if the working
+        // directory is changed
+        (None, Some(_)) => env::current_dir()
+            .expect("Error getting current directory")
+            .join(&config.path),
+        (None, None) => explicit_path(&config.path),
+    };
+
+    let mut command = match launcher(&path, config.interpreter.as_deref()) {
+        Some(launcher) => {
+            let mut command = Command::new(&launcher[0]);
+            command.args(&launcher[1..]).arg(&path);
+            command
+        }
+        None => Command::new(&path),
+    };
+
+    command.args(config.option_args());
+
+    if let Some(dir) = &config.working_dir {
+        command.current_dir(dir);
+    }
+
+    command.envs(config.env.iter().cloned());
+
+    command
+}
+
+/// Checks whether the AI can be launched, so problems are reported before any games are played.
+/// AIs with a build command may only be created by the build, so whether they exist is checked
+/// after building them.
+pub fn validate(config: &EngineConfig) -> Result<(), Error> {
+    let path = &config.path;
+
+    let unbuilt = config.build.is_some() && !path.exists();
+
+    if !path.exists() && !unbuilt {
+        return Err(Error::InvalidEngine(format!(
+            "Path '{}' is not valid{}",
+            path.display(),
+            missing_path_hint(path)
+        )));
+    }
+
+    if !path.is_file() && !unbuilt {
+        return Err(Error::InvalidEngine(format!(
+            "Path '{}' points to something not a file",
+            path.display()
+        )));
+    }
+
+    if let Some(dir) = &config.working_dir {
+        if !dir.is_dir() {
+            return Err(Error::InvalidEngine(format!(
+                "Working directory '{}' of '{}' is not a directory",
+                dir.display(),
+                path.display()
+            )));
+        }
+    }
+
+    if let Some(launcher) = launcher(path, config.interpreter.as_deref()) {
+        if find_program(&launcher[0]).is_none() {
+            return Err(Error::InvalidEngine(format!(
+                "Unable to find '{}', which is needed to run '{}'",
+                launcher[0],
+                path.display()
+            )));
+        }
+
+        if is_cmd(&launcher[0]) {
+            check_cmd_paths(config)?;
+        }
+    }
+
+    Ok(())
+}
+
+// `cmd` runs batch files, but it has its own rules for quoting, and it can't run in a network
+// share, so paths which other programs are fine with fail inside it with confusing messages
+fn check_cmd_paths(config: &EngineConfig) -> Result<(), Error> {
+    let path = &config.path;
+
+    if let Some(special) = path
+        .to_string_lossy()
+        .chars()
+        .find(|char| CMD_SPECIAL_CHARS.contains(char))
+    {
+        return Err(Error::InvalidEngine(format!(
+            "Path '{}' contains '{special}', which cmd, the program running batch files, treats \
+            specially. Rename it or the directories containing it, or give an interpreter in the \
+            AI's configuration",
+            path.display()
+        )));
+    }
+
+    let working_dir = match &config.working_dir {
+        Some(dir) => absolute_path(dir),
+        None => env::current_dir().ok(),
+    };
+
+    if let Some(dir) = working_dir.filter(|dir| is_network_share(dir)) {
+        return Err(Error::InvalidEngine(format!(
+            "'{}' would run in '{}', a network share, which cmd, the program running batch files, \
+            doesn't support as its working directory. Map the share to a drive letter, e.g. with \
+            `net use`, and use that path instead",
+            path.display(),
+            dir.display()
+        )));
+    }
+
+    Ok(())
+}
+
+// characters `cmd` expands or can't have escaped even in quotes
+const CMD_SPECIAL_CHARS: &[char] = &['%', '!', '^', '&', '"'];
+
+// Windows paths are split by hand, so that they are recognized on every platform
+fn is_cmd(program: &str) -> bool {
+    let name = program.rsplit(['/', '\\']).next().unwrap_or(program);
+
+    matches!(name.to_lowercase().as_str(), "cmd" | "cmd.exe")
+}
+
+// paths like `\\server\share\ai.bat`
+fn is_network_share(path: &Path) -> bool {
+    let path = path.to_string_lossy();
+
+    path.starts_with(r"\\") && !path.starts_with(r"\\?\") || path.starts_with(r"\\?\UNC\")
+}
+
+/// Why a path of an AI which doesn't exist may have been given wrong, to be appended to the error,
+/// or an empty string if there is no guess.
+fn missing_path_hint(path: &Path) -> String {
+    let text = path.to_string_lossy();
+
+    if text.contains('"') {
+        return ". It contains a quote, which is likely left over from quoting it on the command \
+            line: on Windows, a quote right after a backslash isn't taken as closing the quotes, so \
+            leave out the trailing backslash of quoted directories"
+            .to_owned();
+    }
+
+    if text.trim() != text {
+        return ". It starts or ends with whitespace".to_owned();
+    }
+
+    // an unquoted path with spaces is split into several arguments, the first of which is taken
+    // as the path
+    let Some(file_name) = path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+    else {
+        return String::new();
+    };
+    let dir = match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+
+    let split = fs::read_dir(dir).ok().and_then(|entries| {
+        entries
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.file_name().to_string_lossy().into_owned())
+            .find(|name| name.starts_with(&format!("{file_name} ")))
+    });
+
+    match split {
+        Some(name) => format!(
+            ". There is '{}' next to it, so the path was likely split at a space; put it in quotes",
+            path.with_file_name(name).display()
+        ),
+        None => String::new(),
+    }
+}
+
+/// The path made absolute like by `absolute_path`, so that a program is identified the same
+/// however its path was given. If its directory doesn't exist, `.` and `..` are resolved without
+/// accessing the file system.
+pub fn absolute(path: &Path) -> PathBuf {
+    if let Some(absolute) = absolute_path(path) {
+        return absolute;
+    }
+
+    let current_dir = env::current_dir().expect("Error getting current directory");
+    let mut absolute = PathBuf::new();
+
+    for component in current_dir.join(path).components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                absolute.pop();
+            }
+            component => absolute.push(component),
+        }
+    }
+
+    absolute
+}
+
+/// The path made absolute with the directories it is in resolved, e.g. mapped network drives, so
+/// that it doesn't depend on the working directory. The file itself is kept as it is given, as
+/// some programs behave differently depending on the name of the link they are run through.
+/// `None` if its directory doesn't exist.
+fn absolute_path(path: &Path) -> Option<PathBuf> {
+    let file_name = path.file_name()?;
+    let dir = match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+
+    Some(without_verbatim_prefix(fs::canonicalize(dir).ok()?).join(file_name))
+}
+
+// Windows canonicalizes to paths starting with `\\?\`, which many programs, including `cmd`,
+// don't understand
+fn without_verbatim_prefix(path: PathBuf) -> PathBuf {
+    let text = path.to_string_lossy();
+
+    if let Some(share) = text.strip_prefix(r"\\?\UNC\") {
+        PathBuf::from(format!(r"\\{share}"))
+    } else if let Some(local) = text.strip_prefix(r"\\?\") {
+        PathBuf::from(local)
+    } else {
+        path
+    }
+}
+
+/// The SHA-256 hash of the AI's file in hex, which identifies the exact build that played.
+pub fn file_hash(path: &Path) -> io::Result<String> {
+    let hash = Sha256::digest(fs::read(path)?);
+
+    Ok(hash.iter().map(|byte| format!("{byte:02x}")).collect())
+}
+
+// `Command` searches PATH for bare file names, but AIs are always given relative to the current
+// directory
+fn explicit_path(path: &Path) -> PathBuf {
+    if path.is_relative() && path.components().count() == 1 {
+        Path::new(".").join(path)
+    } else {
+        path.to_owned()
+    }
+}
+
+// program (with arguments) the path of the AI has to be passed to, if it can't be run directly
+fn launcher(path: &Path, interpreter: Option<&[String]>) -> Option<Vec<String>> {
+    match interpreter {
+        Some(interpreter) => Some(interpreter.to_vec()),
+        None => interpreter_for_extension(path).or_else(|| platform_launcher(path)),
+    }
+}
+
+fn interpreter_for_extension(path: &Path) -> Option<Vec<String>> {
+    let extension = path.extension()?.to_str()?.to_lowercase();
+
+    let interpreter: &[&str] = match extension.as_str() {
+        "py" if cfg!(windows) => &["python"],
+        "py" => &["python3"],
+        "jar" => &["java", "-jar"],
+        "js" => &["node"],
+        _ => return None,
+    };
+
+    Some(interpreter.iter().map(|arg| arg.to_string()).collect())
+}
+
+fn find_program(program: &str) -> Option<PathBuf> {
+    let program = Path::new(program);
+
+    if program.components().count() > 1 {
+        return program.is_file().then(|| program.to_owned());
+    }
+
+    let extensions: &[&str] = if cfg!(windows) {
+        &["", ".exe", ".com", ".bat", ".cmd"]
+    } else {
+        &[""]
+    };
+
+    env::split_paths(&env::var_os("PATH")?)
+        .flat_map(|dir| {
+            extensions.iter().map(move |extension| {
+                let mut file_name = program.as_os_str().to_owned();
+                file_name.push(extension);
+                dir.join(file_name)
+            })
+        })
+        .find(|candidate| candidate.is_file())
+}
+
+#[cfg(windows)]
+fn platform_launcher(path: &Path) -> Option<Vec<String>> {
+    let extension = path.extension()?.to_str()?.to_lowercase();
+
+    matches!(extension.as_str(), "bat" | "cmd").then(|| vec!["cmd".to_owned(), "/C".to_owned()])
+}
+
+#[cfg(unix)]
+fn platform_launcher(path: &Path) -> Option<Vec<String>> {
+    use std::{
+        fs::File,
+        io::{BufRead, BufReader},
+        os::unix::fs::PermissionsExt,
+    };
+
+    let is_executable = path
+        .metadata()
+        .map(|metadata| metadata.permissions().mode() & 0o111 != 0)
+        .unwrap_or(true);
+
+    if is_executable {
+        return None;
+    }
+
+    let first_line = File::open(path)
+        .ok()
+        .and_then(|file| BufReader::new(file).lines().next()?.ok());
+
+    Some(
+        first_line
+            .as_deref()
+            .and_then(parse_shebang)
+            .unwrap_or_else(|| vec!["sh".to_owned()]),
+    )
+}
+
+#[cfg(not(any(windows, unix)))]
+fn platform_launcher(_path: &Path) -> Option<Vec<String>> {
+    None
+}
+
+#[cfg(any(unix, test))]
+fn parse_shebang(line: &str) -> Option<Vec<String>> {
+    let interpreter: Vec<String> = line
+        .strip_prefix("#!")?
+        .split_whitespace()
+        .map(|part| part.to_owned())
+        .collect();
+
+    (!interpreter.is_empty()).then_some(interpreter)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shebang() {
+        assert_eq!(
+            parse_shebang("#!/usr/bin/env python3"),
+            Some(vec!["/usr/bin/env".to_owned(), "python3".to_owned()])
+        );
+        assert_eq!(
+            parse_shebang("#! /bin/sh"),
+            Some(vec!["/bin/sh".to_owned()])
+        );
+        assert_eq!(parse_shebang("#!"), None);
+        assert_eq!(parse_shebang("import sys"), None);
+    }
+
+    #[test]
+    fn interpreter_by_extension() {
+        assert_eq!(
+            launcher(Path::new("bot.jar"), None),
+            Some(vec!["java".to_owned(), "-jar".to_owned()])
+        );
+        assert_eq!(
+            launcher(Path::new("bot.py"), Some(&["pypy3".to_owned()])),
+            Some(vec!["pypy3".to_owned()])
+        );
+    }
+
+    #[test]
+    fn paths_for_cmd() {
+        assert_eq!(
+            without_verbatim_prefix(PathBuf::from(r"\\?\C:\ais\ai.bat")),
+            PathBuf::from(r"C:\ais\ai.bat")
+        );
+        assert_eq!(
+            without_verbatim_prefix(PathBuf::from(r"\\?\UNC\server\ais\ai.bat")),
+            PathBuf::from(r"\\server\ais\ai.bat")
+        );
+
+        assert!(is_network_share(Path::new(r"\\server\ais")));
+        assert!(is_network_share(Path::new(r"\\?\UNC\server\ais")));
+        assert!(!is_network_share(Path::new(r"\\?\C:\ais")));
+        assert!(!is_network_share(Path::new(r"C:\ais")));
+
+        assert!(is_cmd("cmd"));
+        assert!(is_cmd(r"C:\Windows\System32\CMD.EXE"));
+        assert!(!is_cmd("python"));
+    }
+
+    #[test]
+    fn hints_for_missing_paths() {
+        assert!(missing_path_hint(Path::new(r#"C:\my ais\""#)).contains("quote"));
+        assert!(missing_path_hint(Path::new("ai ")).contains("whitespace"));
+        assert_eq!(missing_path_hint(Path::new("no such ai")), "");
+    }
+
+    #[test]
+    fn bare_file_names_are_relative_to_current_dir() {
+        assert_eq!(explicit_path(Path::new("ai")), Path::new(".").join("ai"));
+        assert_eq!(explicit_path(Path::new("ais/ai")), Path::new("ais/ai"));
+    }
+}
@@ -0,0 +1,427 @@
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, HashMap},
+    io::{self, Read, Write},
+    net::{TcpListener, TcpStream},
+    sync::{
+        mpsc::{self, Receiver, Sender, TryRecvError},
+        Arc, Mutex, PoisonError,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+use tungstenite::Message;
+
+use crate::{error::Error, observer::*, *};
+
+const PAGE: &str = include_str!("../assets/spectator.html");
+
+/// Serves a page on which the games can be watched from a browser. The page is kept up to date
+/// through a WebSocket, and the state is also available as JSON at `/state`.
+///
+/// Only one game is shown at a time: the followed game is kept until it ends, then the next game
+/// with any activity is followed, like in the window. All games are sent to WebSockets connecting
+/// to `/games`, which is how the GUI spectates.
+#[derive(Debug)]
+pub struct SpectatorServer {
+    shared: Arc<Mutex<Shared>>,
+    followed: Option<usize>,
+    followed_ended: bool,
+    // id of the player -> standing
+    standings: HashMap<PlayerId, Standing>,
+}
+
+#[derive(Debug, Default)]
+struct Shared {
+    snapshot: Option<Snapshot>,
+    move_started: Option<Instant>,
+    clients: Vec<Sender<String>>,
+    // the latest snapshot of every running game, sent to watchers as they connect
+    games: BTreeMap<usize, String>,
+    // the WebSockets watching all games
+    watchers: Vec<Sender<String>>,
+}
+
+impl Shared {
+    fn snapshot_json(&mut self) -> Option<String> {
+        let snapshot = self.snapshot.as_mut()?;
+
+        snapshot.move_elapsed_ms = self
+            .move_started
+            .map_or(0, |move_started| move_started.elapsed().as_millis());
+
+        Some(serde_json::to_string(snapshot).expect("Error serializing spectator state"))
+    }
+}
+
+/// The state of a game as it is sent to spectators.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Snapshot {
+    pub game_id: usize,
+    // rows from top to bottom, 'X', 'O' or '.' for each tile
+    pub board: Vec<String>,
+    // null if the game is over
+    pub next_player: Option<char>,
+    pub last_move: Option<String>,
+    pub players: [PlayerInfo; 2],
+    // "X", "O" or "draw", null if the game is not over
+    pub winner: Option<String>,
+    pub move_elapsed_ms: u128,
+    pub standings: Vec<Standing>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlayerInfo {
+    pub name: String,
+    // null for humans and network players
+    pub time_limit_ms: Option<u128>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Standing {
+    pub name: String,
+    pub score: f32,
+    pub games: usize,
+}
+
+impl Snapshot {
+    /// The position, `None` if the board is invalid. The next player is empty once the game is
+    /// over.
+    pub fn pos(&self) -> Option<Pos> {
+        parse_pos(&self.board.concat(), self.next_player.unwrap_or('.'))
+    }
+
+    pub fn last_move(&self) -> Option<Vec2> {
+        self.last_move.as_deref().and_then(net::parse_move)
+    }
+
+    pub fn winner(&self) -> Option<Tile> {
+        match self.winner.as_deref()? {
+            "draw" => Some(Tile::Empty),
+            winner => winner.chars().next().and_then(parse_tile),
+        }
+    }
+}
+
+impl SpectatorServer {
+    pub fn start(port: u16) -> io::Result<Self> {
+        let listener = TcpListener::bind(("0.0.0.0", port))?;
+        let shared = Arc::new(Mutex::new(Shared::default()));
+
+        let server_shared = Arc::clone(&shared);
+        thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let shared = Arc::clone(&server_shared);
+
+                // a misbehaving browser only loses its own connection
+                thread::spawn(move || {
+                    let _ = handle_connection(stream, &shared);
+                });
+            }
+        });
+
+        Ok(Self {
+            shared,
+            followed: None,
+            followed_ended: false,
+            standings: HashMap::new(),
+        })
+    }
+
+    fn follows(&mut self, game: &Game) -> bool {
+        if self.followed.is_none() || (self.followed_ended && self.followed != Some(game.id)) {
+            self.followed = Some(game.id);
+            self.followed_ended = false;
+        }
+
+        self.followed == Some(game.id)
+    }
+
+    fn publish(&mut self, game: &Game, move_started: bool) {
+        let mut standings: Vec<Standing> = self.standings.values().cloned().collect();
+        standings.sort_by(|a, b| b.score.total_cmp(&a.score).then(a.name.cmp(&b.name)));
+
+        let follows = self.follows(game);
+
+        let snapshot = Snapshot {
+            game_id: game.id,
+            board: board_rows(&game.pos.board),
+            next_player: game.next_player().map(|_| tile_char(game.pos.next_player)),
+            last_move: game
+                .history
+                .last()
+                .expect("history empty")
+                .1
+                .map(|mv| mv.move_string()),
+            players: [player_info(&game.players[0]), player_info(&game.players[1])],
+            winner: game.winner.map(winner_string),
+            move_elapsed_ms: 0,
+            standings,
+        };
+
+        let mut shared = self.shared.lock().unwrap_or_else(PoisonError::into_inner);
+
+        let json = serde_json::to_string(&snapshot).expect("Error serializing spectator state");
+
+        if game.winner.is_some() {
+            shared.games.remove(&game.id);
+        } else {
+            shared.games.insert(game.id, json.clone());
+        }
+
+        shared
+            .watchers
+            .retain(|watcher| watcher.send(json.clone()).is_ok());
+
+        if !follows {
+            return;
+        }
+
+        shared.snapshot = Some(snapshot);
+        if move_started {
+            shared.move_started = Some(Instant::now());
+        }
+
+        let json = shared.snapshot_json().unwrap();
+        shared
+            .clients
+            .retain(|client| client.send(json.clone()).is_ok());
+    }
+}
+
+impl GameObserver for SpectatorServer {
+    fn on_game_start(&mut self, game: &Game) {
+        self.publish(game, true);
+    }
+
+    fn on_move(&mut self, game: &Game, _mv: Vec2, _notes: &str) {
+        self.publish(game, true);
+    }
+
+    fn on_undo(&mut self, game: &Game) {
+        self.publish(game, true);
+    }
+
+    fn on_game_end(&mut self, game: &Game) {
+        for (player, tile) in game.players.iter().zip(Tile::opponent_iter()) {
+            let standing = self
+                .standings
+                .entry(player.id())
+                .or_insert_with(|| Standing {
+                    name: player.name(),
+                    score: 0.0,
+                    games: 0,
+                });
+
+            standing.score += game.score_for(tile, Scoring::WinDrawLoss);
+            standing.games += 1;
+        }
+
+        self.publish(game, false);
+
+        if self.followed == Some(game.id) {
+            self.followed_ended = true;
+        }
+    }
+}
+
+/// Receives the games of an arena from its spectator server, to watch them in the window with
+/// `spectate <address>`.
+#[derive(Debug)]
+pub struct SpectatorClient {
+    receiver: Receiver<Snapshot>,
+    connected: bool,
+}
+
+impl SpectatorClient {
+    pub fn connect(address: &str) -> Result<Self, Error> {
+        let url = format!("ws://{address}/games");
+
+        let (mut websocket, _) = tungstenite::connect(url.as_str()).map_err(|err| {
+            Error::Network(format!(
+                "Unable to connect to the arena at '{address}': {err}"
+            ))
+        })?;
+
+        let (sender, receiver) = mpsc::channel();
+
+        thread::spawn(move || {
+            while let Ok(message) = websocket.read() {
+                let Message::Text(json) = message else {
+                    continue;
+                };
+
+                // snapshots which aren't understood, e.g. of a newer version, are skipped
+                let Ok(snapshot) = serde_json::from_str(&json) else {
+                    continue;
+                };
+
+                if sender.send(snapshot).is_err() {
+                    break;
+                }
+            }
+        });
+
+        Ok(Self {
+            receiver,
+            connected: true,
+        })
+    }
+
+    /// The snapshots received since the last call, in the order they were sent.
+    pub fn receive(&mut self) -> Vec<Snapshot> {
+        let mut snapshots = Vec::new();
+
+        loop {
+            match self.receiver.try_recv() {
+                Ok(snapshot) => snapshots.push(snapshot),
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => {
+                    self.connected = false;
+                    break;
+                }
+            }
+        }
+
+        snapshots
+    }
+
+    /// Whether the arena could still send snapshots, as of the last `receive`.
+    pub fn is_connected(&self) -> bool {
+        self.connected
+    }
+}
+
+fn player_info(player: &Player) -> PlayerInfo {
+    PlayerInfo {
+        name: player.name(),
+        time_limit_ms: player.limit().map(|limit| limit.time_limit().as_millis()),
+    }
+}
+
+fn handle_connection(mut stream: TcpStream, shared: &Mutex<Shared>) -> io::Result<()> {
+    let head = peek_request_head(&stream)?;
+    let request = String::from_utf8_lossy(&head).to_lowercase();
+
+    if request.contains("upgrade: websocket") {
+        // the GUI watches all games, the page only the followed one
+        let all_games = request.starts_with("get /games");
+
+        return serve_websocket(stream, shared, all_games);
+    }
+
+    // the head was only peeked
+    stream.read_exact(&mut vec![0; head.len()])?;
+
+    let (content_type, body) = if request.starts_with("get /state") {
+        let json = shared
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .snapshot_json()
+            .unwrap_or_else(|| "null".to_owned());
+
+        ("application/json", json)
+    } else {
+        ("text/html; charset=utf-8", PAGE.to_owned())
+    };
+
+    write!(
+        stream,
+        "HTTP/1.1 200 OK\r\nContent-Type: {content_type}\r\nContent-Length: {}\r\nCache-Control: no-store\r\nConnection: close\r\n\r\n{body}",
+        body.len()
+    )?;
+
+    stream.flush()
+}
+
+// The request head is peeked instead of read, as the WebSocket handshake has to read it again.
+fn peek_request_head(stream: &TcpStream) -> io::Result<Vec<u8>> {
+    let mut buffer = [0; 4096];
+
+    for _ in 0..100 {
+        let len = stream.peek(&mut buffer)?;
+
+        if len == 0 {
+            return Err(io::ErrorKind::UnexpectedEof.into());
+        }
+
+        if let Some(end) = buffer[..len]
+            .windows(4)
+            .position(|bytes| bytes == b"\r\n\r\n")
+        {
+            return Ok(buffer[..end + 4].to_vec());
+        }
+
+        if len == buffer.len() {
+            break;
+        }
+
+        thread::sleep(Duration::from_millis(10));
+    }
+
+    Err(io::Error::new(
+        io::ErrorKind::InvalidData,
+        "incomplete or too long request",
+    ))
+}
+
+fn serve_websocket(stream: TcpStream, shared: &Mutex<Shared>, all_games: bool) -> io::Result<()> {
+    let mut websocket = tungstenite::accept(stream)
+        .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err.to_string()))?;
+
+    let (sender, receiver) = mpsc::channel();
+
+    {
+        let mut shared = shared.lock().unwrap_or_else(PoisonError::into_inner);
+
+        if all_games {
+            for json in shared.games.values() {
+                let _ = sender.send(json.clone());
+            }
+
+            shared.watchers.push(sender);
+        } else {
+            if let Some(json) = shared.snapshot_json() {
+                let _ = sender.send(json);
+            }
+
+            shared.clients.push(sender);
+        }
+    }
+
+    for json in receiver {
+        if websocket.send(Message::Text(json)).is_err() {
+            break;
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn snapshot_position() {
+        let mut game = Game::new(3, [Player::Human, Player::Human]);
+        let mv = game.pos.valid_moves()[0];
+        game.pos.play(mv);
+        game.history.push((game.pos, Some(mv)));
+
+        let mut server = SpectatorServer {
+            shared: Arc::new(Mutex::new(Shared::default())),
+            followed: None,
+            followed_ended: false,
+            standings: HashMap::new(),
+        };
+        server.publish(&game, true);
+
+        let json = server.shared.lock().unwrap().games[&3].clone();
+        let snapshot: Snapshot = serde_json::from_str(&json).unwrap();
+
+        assert!(snapshot.pos().is_some_and(|pos| same_pos(&pos, &game.pos)));
+        assert_eq!(snapshot.last_move(), Some(mv));
+        assert_eq!(snapshot.winner(), None);
+    }
+}
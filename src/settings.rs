@@ -0,0 +1,162 @@
+use serde::{Deserialize, Serialize};
+use std::{env, fs, io, path::PathBuf};
+
+use crate::{
+    error::Error,
+    i18n::{tr, Text},
+};
+
+/// Preferences of the GUI, changed in the settings menu and kept between runs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    // mark the valid moves when a human is to move
+    pub hints: bool,
+    pub theme: Theme,
+    pub undo: UndoGranularity,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Theme {
+    #[default]
+    Classic,
+    Dark,
+    // for color blindness, the disks differ in shape too
+    Accessible,
+}
+
+impl Theme {
+    pub const ALL: [Theme; 3] = [Theme::Classic, Theme::Dark, Theme::Accessible];
+
+    /// The theme after this one in the settings menu.
+    pub fn next(self) -> Self {
+        let idx = Self::ALL.iter().position(|&theme| theme == self).unwrap();
+        Self::ALL[(idx + 1) % Self::ALL.len()]
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Theme::Classic => tr(Text::ThemeClassic),
+            Theme::Dark => tr(Text::ThemeDark),
+            Theme::Accessible => tr(Text::ThemeAccessible),
+        }
+    }
+}
+
+/// How far back undo goes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UndoGranularity {
+    // to the last position a human was to move in
+    #[default]
+    Turn,
+    // a single move, after which an AI to move plays again
+    Move,
+}
+
+impl UndoGranularity {
+    pub fn next(self) -> Self {
+        match self {
+            UndoGranularity::Turn => UndoGranularity::Move,
+            UndoGranularity::Move => UndoGranularity::Turn,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            UndoGranularity::Turn => tr(Text::UndoTurn),
+            UndoGranularity::Move => tr(Text::UndoMove),
+        }
+    }
+}
+
+impl Settings {
+    /// `othello_gui/settings.json` in the configuration directory of the user, if it is known.
+    pub fn path() -> Option<PathBuf> {
+        let dir = env::var_os("XDG_CONFIG_HOME")
+            .map(PathBuf::from)
+            .or_else(|| env::var_os("APPDATA").map(PathBuf::from))
+            .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+
+        Some(dir.join("othello_gui").join("settings.json"))
+    }
+
+    /// The saved settings, or the defaults if there are none yet.
+    pub fn load() -> Result<Self, Error> {
+        let Some(path) = Self::path() else {
+            return Ok(Self::default());
+        };
+
+        let content = match fs::read_to_string(&path) {
+            Ok(content) => content,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(source) => {
+                return Err(Error::Read {
+                    what: format!("settings file '{}'", path.display()),
+                    source,
+                })
+            }
+        };
+
+        serde_json::from_str(&content).map_err(|err| {
+            Error::InvalidValue(format!(
+                "Settings file '{}' is invalid: {err}",
+                path.display()
+            ))
+        })
+    }
+
+    pub fn save(&self) -> Result<(), Error> {
+        let Some(path) = Self::path() else {
+            return Ok(());
+        };
+
+        let write_error = |source| Error::Write {
+            what: format!("settings file '{}'", path.display()),
+            source,
+        };
+
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).map_err(write_error)?;
+        }
+
+        let content = serde_json::to_string_pretty(self).expect("Error serializing settings");
+
+        fs::write(&path, content).map_err(write_error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn missing_settings_are_defaults() {
+        let settings: Settings = serde_json::from_str(r#"{"theme":"dark"}"#).unwrap();
+
+        assert_eq!(
+            settings,
+            Settings {
+                theme: Theme::Dark,
+                ..Settings::default()
+            }
+        );
+    }
+
+    #[test]
+    fn round_trip() {
+        let settings = Settings {
+            hints: true,
+            theme: Theme::Dark,
+            undo: UndoGranularity::Move,
+        };
+
+        let content = serde_json::to_string(&settings).unwrap();
+
+        assert_eq!(
+            serde_json::from_str::<Settings>(&content).unwrap(),
+            settings
+        );
+    }
+}
@@ -0,0 +1,76 @@
+use std::{fmt, str::FromStr};
+
+use crate::*;
+
+/// The arrangement of the four disks in the center which games start from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Start {
+    // the disks of a player on a diagonal
+    #[default]
+    Standard,
+    // the disks of a player in the same column, which makes for different openings
+    Parallel,
+}
+
+impl Start {
+    pub fn pos(self) -> Pos {
+        let mut pos = Pos::new();
+
+        if self == Start::Parallel {
+            // the lower row of the center copies the upper one, X still moves first
+            for x in 3..5 {
+                let upper = pos.board.get(Vec2::new(x, 3));
+                pos.board.set(Vec2::new(x, 4), upper);
+            }
+        }
+
+        pos
+    }
+}
+
+impl FromStr for Start {
+    type Err = String;
+
+    fn from_str(str: &str) -> Result<Self, Self::Err> {
+        match str.to_lowercase().as_str() {
+            "standard" => Ok(Start::Standard),
+            "parallel" => Ok(Start::Parallel),
+            other => Err(format!("Unknown start '{other}'")),
+        }
+    }
+}
+
+impl fmt::Display for Start {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Start::Standard => write!(f, "standard"),
+            Start::Parallel => write!(f, "parallel"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parallel_center() {
+        let pos = Start::Parallel.pos();
+
+        for x in 3..5 {
+            assert_eq!(
+                pos.board.get(Vec2::new(x, 3)),
+                pos.board.get(Vec2::new(x, 4))
+            );
+        }
+        assert_ne!(
+            pos.board.get(Vec2::new(3, 3)),
+            pos.board.get(Vec2::new(4, 3))
+        );
+        assert_eq!(pos.next_player, Tile::X);
+        assert!(!pos.valid_moves().is_empty());
+
+        assert!(same_pos(&Start::Standard.pos(), &Pos::new()));
+        assert_eq!("Parallel".parse(), Ok(Start::Parallel));
+    }
+}
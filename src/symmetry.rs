@@ -0,0 +1,146 @@
+use std::collections::HashSet;
+
+use crate::*;
+
+/// One of the 8 rotations and reflections of the board. They keep the rules the same, so
+/// positions which are the same under one of them are the same opening.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Symmetry(u8);
+
+impl Symmetry {
+    pub const IDENTITY: Symmetry = Symmetry(0);
+
+    pub fn all() -> impl Iterator<Item = Symmetry> {
+        (0..8).map(Symmetry)
+    }
+
+    /// The symmetries which leave the initial position as it is, so games from it can be
+    /// rotated or mirrored by them and still be from the initial position.
+    pub fn of_initial_pos() -> impl Iterator<Item = Symmetry> {
+        Symmetry::all().filter(|symmetry| same_pos(&symmetry.pos(&Pos::new()), &Pos::new()))
+    }
+
+    // the bits are, applied in this order: swap x and y, mirror x, mirror y
+    pub fn vec2(self, coor: Vec2) -> Vec2 {
+        let (x, y) = if self.0 & 1 == 0 {
+            (coor.x, coor.y)
+        } else {
+            (coor.y, coor.x)
+        };
+        let x = if self.0 & 2 == 0 { x } else { 7 - x };
+        let y = if self.0 & 4 == 0 { y } else { 7 - y };
+
+        Vec2::new(x, y)
+    }
+
+    pub fn board(self, board: &Board) -> Board {
+        let mut transformed = *board;
+
+        for coor in Vec2::board_iter() {
+            transformed.set(self.vec2(coor), board.get(coor));
+        }
+
+        transformed
+    }
+
+    pub fn pos(self, pos: &Pos) -> Pos {
+        let mut transformed = *pos;
+        transformed.board = self.board(&pos.board);
+        transformed
+    }
+
+    /// The symmetry undoing this one.
+    pub fn inverse(self) -> Symmetry {
+        let coor = Vec2::new(1, 2);
+        let moved = self.vec2(coor);
+
+        Symmetry::all()
+            .find(|other| other.vec2(moved) == coor)
+            .expect("every symmetry has an inverse")
+    }
+}
+
+/// The position with the smallest board of those the same as `pos` under a symmetry, which is the
+/// same for all of them, and the symmetry turning `pos` into it.
+pub fn canonical(pos: &Pos) -> (Pos, Symmetry) {
+    Symmetry::all()
+        .map(|symmetry| (symmetry.pos(pos), symmetry))
+        .min_by_key(|(transformed, _)| board_rows(&transformed.board))
+        .expect("there are symmetries")
+}
+
+/// The positions without those which are the same as an earlier one under a symmetry, or without
+/// one, in their order.
+pub fn unique(positions: Vec<Pos>) -> Vec<Pos> {
+    let mut seen = HashSet::new();
+
+    positions
+        .into_iter()
+        .filter(|pos| {
+            let (canonical, _) = canonical(pos);
+            seen.insert((board_rows(&canonical.board), canonical.next_player))
+        })
+        .collect()
+}
+
+/// The moves of a game from the initial position, rotated or mirrored so that games which are the
+/// same under a symmetry have the same moves, the smallest of them.
+pub fn canonical_line(moves: &[Vec2]) -> Vec<Vec2> {
+    Symmetry::of_initial_pos()
+        .map(|symmetry| {
+            moves
+                .iter()
+                .map(|&mv| symmetry.vec2(mv))
+                .collect::<Vec<_>>()
+        })
+        .min_by_key(|line| line.iter().map(|mv| (mv.y, mv.x)).collect::<Vec<_>>())
+        .expect("the identity leaves the initial position as it is")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn inverses() {
+        let pos = Pos::new().play_clone(Vec2::new(3, 2));
+
+        for symmetry in Symmetry::all() {
+            let back = symmetry.inverse().pos(&symmetry.pos(&pos));
+            assert!(same_pos(&back, &pos));
+        }
+
+        assert_eq!(Symmetry::of_initial_pos().count(), 4);
+    }
+
+    #[test]
+    fn openings_are_unique() {
+        let after_first = Pos::new().valid_moves().len();
+        let first_moves = Pos::new().tree_end(1);
+
+        // all first moves are the same under a symmetry
+        assert_eq!(first_moves.len(), after_first);
+        assert_eq!(unique(first_moves).len(), 1);
+
+        let openings = Pos::new().tree_end(3);
+        let unique_openings = unique(openings.clone());
+
+        assert!(unique_openings.len() < openings.len());
+
+        for pos in &openings {
+            let (canonical_pos, _) = canonical(pos);
+
+            assert!(unique_openings
+                .iter()
+                .any(|other| same_pos(&canonical(other).0, &canonical_pos)));
+        }
+    }
+
+    #[test]
+    fn mirrored_lines() {
+        let line = vec![Vec2::new(3, 2), Vec2::new(2, 4)];
+        let mirrored: Vec<Vec2> = line.iter().map(|&mv| Symmetry(1).vec2(mv)).collect();
+
+        assert_eq!(canonical_line(&line), canonical_line(&mirrored));
+    }
+}
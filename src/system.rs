@@ -0,0 +1,200 @@
+use serde::{Deserialize, Serialize};
+use std::process::Command;
+
+/// The machine games were played on, recorded with the results so that they can be compared
+/// across machines later.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SystemInfo {
+    pub cpu: Option<String>,
+    pub logical_cores: usize,
+    pub physical_cores: usize,
+    // like "linux x86_64"
+    pub os: String,
+    pub version: String,
+}
+
+impl SystemInfo {
+    pub fn capture() -> Self {
+        Self {
+            cpu: cpu_model(),
+            logical_cores: std::thread::available_parallelism().map_or(1, |count| count.get()),
+            physical_cores: physical_cores().len(),
+            os: format!("{} {}", std::env::consts::OS, std::env::consts::ARCH),
+            version: env!("CARGO_PKG_VERSION").to_owned(),
+        }
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn cpu_model() -> Option<String> {
+    std::fs::read_to_string("/proc/cpuinfo")
+        .ok()?
+        .lines()
+        .find(|line| line.starts_with("model name"))?
+        .split_once(':')
+        .map(|(_, model)| model.trim().to_owned())
+}
+
+#[cfg(target_os = "windows")]
+fn cpu_model() -> Option<String> {
+    std::env::var("PROCESSOR_IDENTIFIER").ok()
+}
+
+#[cfg(target_os = "macos")]
+fn cpu_model() -> Option<String> {
+    let output = Command::new("sysctl")
+        .args(["-n", "machdep.cpu.brand_string"])
+        .output()
+        .ok()?;
+
+    Some(String::from_utf8_lossy(&output.stdout).trim().to_owned())
+        .filter(|model| !model.is_empty())
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "windows", target_os = "macos")))]
+fn cpu_model() -> Option<String> {
+    None
+}
+
+/// One logical CPU of every physical core, so that AIs pinned to them don't share a core through
+/// hyper-threading. Falls back to every logical CPU if the topology is unknown.
+pub fn physical_cores() -> Vec<usize> {
+    let logical = std::thread::available_parallelism().map_or(1, |count| count.get());
+
+    let mut cores: Vec<usize> = Vec::new();
+    let mut seen = Vec::new();
+
+    for cpu in 0..logical {
+        match core_id(cpu) {
+            Some(id) if seen.contains(&id) => {}
+            Some(id) => {
+                seen.push(id);
+                cores.push(cpu);
+            }
+            None => cores.push(cpu),
+        }
+    }
+
+    cores
+}
+
+// (package, core) of the logical CPU
+#[cfg(target_os = "linux")]
+fn core_id(cpu: usize) -> Option<(String, String)> {
+    let read = |file: &str| {
+        std::fs::read_to_string(format!("/sys/devices/system/cpu/cpu{cpu}/topology/{file}"))
+            .ok()
+            .map(|content| content.trim().to_owned())
+    };
+
+    Some((read("physical_package_id")?, read("core_id")?))
+}
+
+#[cfg(not(target_os = "linux"))]
+fn core_id(_cpu: usize) -> Option<(String, String)> {
+    None
+}
+
+pub const PINNING_SUPPORTED: bool = cfg!(target_os = "linux");
+
+/// Makes the launched program only run on the logical CPU `core`.
+#[cfg(target_os = "linux")]
+pub fn pin_to_core(command: &mut Command, core: usize) {
+    use std::{io, os::unix::process::CommandExt};
+
+    // SAFETY: only async-signal-safe functions are called between fork and exec
+    unsafe {
+        command.pre_exec(move || {
+            let mut set: libc::cpu_set_t = std::mem::zeroed();
+            libc::CPU_SET(core, &mut set);
+
+            if libc::sched_setaffinity(0, std::mem::size_of::<libc::cpu_set_t>(), &set) != 0 {
+                return Err(io::Error::last_os_error());
+            }
+
+            Ok(())
+        });
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn pin_to_core(_command: &mut Command, _core: usize) {}
+
+/// Starts the program in its own process group, so that processes it leaves behind can be found
+/// with `kill_leftovers`.
+#[cfg(target_os = "linux")]
+pub fn own_process_group(command: &mut Command) {
+    use std::os::unix::process::CommandExt;
+
+    command.process_group(0);
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn own_process_group(_command: &mut Command) {}
+
+/// Kills the processes left in the process group of the exited program `pid`, returning whether
+/// there were any.
+#[cfg(target_os = "linux")]
+pub fn kill_leftovers(pid: u32) -> bool {
+    let group = -(pid as libc::pid_t);
+
+    // SAFETY: kill has no memory safety requirements, signal 0 only checks for processes
+    unsafe {
+        if libc::kill(group, 0) != 0 {
+            return false;
+        }
+
+        libc::kill(group, libc::SIGKILL);
+    }
+
+    true
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn kill_leftovers(_pid: u32) -> bool {
+    false
+}
+
+/// The load average of the last minute, where supported.
+#[cfg(target_os = "linux")]
+pub fn load_average() -> Option<f32> {
+    std::fs::read_to_string("/proc/loadavg")
+        .ok()?
+        .split_whitespace()
+        .next()?
+        .parse()
+        .ok()
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn load_average() -> Option<f32> {
+    None
+}
+
+/// The time all CPUs spent busy and in total since boot, in clock ticks, where supported.
+/// Comparing two of these gives the CPU usage in between, unlike the load average, which lags a
+/// minute behind.
+#[cfg(target_os = "linux")]
+pub fn cpu_times() -> Option<(u64, u64)> {
+    let stat = std::fs::read_to_string("/proc/stat").ok()?;
+
+    // cpu user nice system idle iowait irq softirq steal ..., guest time is included in user
+    let times: Vec<u64> = stat
+        .lines()
+        .next()?
+        .strip_prefix("cpu ")?
+        .split_whitespace()
+        .take(8)
+        .map(|time| time.parse().ok())
+        .collect::<Option<_>>()?;
+
+    let total: u64 = times.iter().sum();
+    let idle = times.get(3)? + times.get(4)?;
+
+    Some((total - idle, total))
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn cpu_times() -> Option<(u64, u64)> {
+    None
+}
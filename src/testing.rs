@@ -0,0 +1,160 @@
+use rand::{rngs::StdRng, seq::SliceRandom, SeedableRng};
+
+use crate::{symmetry::Symmetry, *};
+
+// Support for testing the rules: random games, and invariants every position and move of a game
+// has to keep, so that the implementation of the rules can be changed, e.g. made faster, and
+// checked against many games. Used by the tests and by the fuzz target in `fuzz`.
+
+/// A game from the initial position, with the move played in every position but the last.
+#[derive(Debug, Clone)]
+pub struct Playout {
+    pub positions: Vec<Pos>,
+    pub moves: Vec<Vec2>,
+}
+
+impl Playout {
+    /// Plays `choose(pos, valid moves)` until the game is over, or until it returns `None`.
+    pub fn new(mut choose: impl FnMut(&Pos, &[Vec2]) -> Option<Vec2>) -> Self {
+        let mut pos = Pos::new();
+        let mut playout = Self {
+            positions: vec![pos],
+            moves: Vec::new(),
+        };
+
+        while !pos.is_game_over() {
+            let Some(mv) = choose(&pos, &pos.valid_moves()) else {
+                break;
+            };
+
+            pos.play(mv);
+            playout.positions.push(pos);
+            playout.moves.push(mv);
+        }
+
+        playout
+    }
+}
+
+/// A whole game of random valid moves, the same for the same seed.
+pub fn random_playout(seed: u64) -> Playout {
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    Playout::new(|_, moves| moves.choose(&mut rng).copied())
+}
+
+/// A game whose moves are chosen by the bytes of `data`, one byte per move, ending when they run
+/// out, so that a fuzzer can explore games by changing the bytes.
+pub fn playout_from_bytes(data: &[u8]) -> Playout {
+    let mut bytes = data.iter();
+
+    Playout::new(|_, moves| {
+        let &byte = bytes.next()?;
+        Some(moves[usize::from(byte) % moves.len()])
+    })
+}
+
+/// Checks every position and move of the game with the checks below.
+pub fn check_playout(playout: &Playout) -> Result<(), String> {
+    for pos in &playout.positions {
+        check_game_over(pos)?;
+        check_move_symmetry(pos)?;
+    }
+
+    for (i, &mv) in playout.moves.iter().enumerate() {
+        let (before, after) = (&playout.positions[i], &playout.positions[i + 1]);
+
+        check_disk_conservation(before, mv, after)?;
+        verify::check_move(before, mv, after)?;
+    }
+
+    Ok(())
+}
+
+/// A move adds one disk of the player making it, and turns over at least one disk of the
+/// opponent, which become the player's.
+pub fn check_disk_conservation(before: &Pos, mv: Vec2, after: &Pos) -> Result<(), String> {
+    let player = before.next_player;
+    let gained = tile_count(after, player) as isize - tile_count(before, player) as isize;
+    let lost = tile_count(before, player.opponent()) as isize
+        - tile_count(after, player.opponent()) as isize;
+
+    if lost < 1 || gained != lost + 1 {
+        return Err(format!(
+            "{} by {player} gained it {gained} disks, and its opponent lost {lost}",
+            mv.move_string()
+        ));
+    }
+
+    Ok(())
+}
+
+/// The game is over exactly when neither player has a valid move, otherwise the player to move
+/// has one, as passes are played automatically.
+pub fn check_game_over(pos: &Pos) -> Result<(), String> {
+    let has_moves = |tile: Tile| {
+        let mut pos = *pos;
+        pos.next_player = tile;
+        !pos.valid_moves().is_empty()
+    };
+
+    let any_moves = has_moves(Tile::X) || has_moves(Tile::O);
+
+    match (pos.is_game_over(), any_moves) {
+        (true, true) => Err("The game is over, but there are valid moves".to_owned()),
+        (false, false) => {
+            Err("Neither player has a valid move, but the game isn't over".to_owned())
+        }
+        (false, true) if pos.valid_moves().is_empty() => Err(format!(
+            "{} is to move, but it has to pass",
+            pos.next_player
+        )),
+        _ => Ok(()),
+    }
+}
+
+/// Rotating or mirroring the board rotates or mirrors the valid moves with it.
+pub fn check_move_symmetry(pos: &Pos) -> Result<(), String> {
+    let mut moves = pos.valid_moves();
+    moves.sort_by_key(|mv| (mv.x, mv.y));
+
+    for symmetry in Symmetry::all() {
+        let mut expected: Vec<Vec2> = moves.iter().map(|&mv| symmetry.vec2(mv)).collect();
+        expected.sort_by_key(|mv| (mv.x, mv.y));
+
+        let mut actual = symmetry.pos(pos).valid_moves();
+        actual.sort_by_key(|mv| (mv.x, mv.y));
+
+        if actual != expected {
+            return Err(format!(
+                "The valid moves change under {symmetry:?} of the board"
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn random_games_keep_invariants() {
+        for seed in 0..50 {
+            let playout = random_playout(seed);
+
+            assert!(playout.positions.last().unwrap().is_game_over());
+            assert_eq!(check_playout(&playout), Ok(()), "seed {seed}");
+        }
+    }
+
+    #[test]
+    fn bytes_choose_moves() {
+        let playout = playout_from_bytes(&[0, 1, 2]);
+
+        assert_eq!(playout.moves.len(), 3);
+        assert_eq!(playout.moves, playout_from_bytes(&[0, 1, 2]).moves);
+        assert_eq!(check_playout(&playout), Ok(()));
+    }
+}
@@ -0,0 +1,82 @@
+use nannou::prelude::*;
+use othello_gui::settings::Theme;
+
+/// The colors the board is drawn with.
+#[derive(Debug, Clone, Copy)]
+pub struct Palette {
+    pub background: Rgba8,
+    pub change_highlight: Rgba8,
+    pub move_highlight: Rgba8,
+    pub tile_stroke: Rgba8,
+    // the disks of O, and text
+    pub light: Rgba8,
+    // the disks of X
+    pub dark: Rgba8,
+    // draw the disks of O as rings, so they differ from those of X in shape, not only in color
+    pub rings: bool,
+}
+
+pub const CLASSIC: Palette = Palette {
+    background: rgba8(30, 90, 60, 255),
+    change_highlight: rgba8(91, 203, 215, 255),
+    move_highlight: rgba8(53, 103, 202, 255),
+    tile_stroke: rgba8(250, 250, 230, 255),
+    light: rgba8(250, 250, 230, 255),
+    dark: rgba8(5, 10, 15, 255),
+    rings: false,
+};
+
+pub const DARK: Palette = Palette {
+    background: rgba8(24, 26, 30, 255),
+    change_highlight: rgba8(60, 110, 120, 255),
+    move_highlight: rgba8(45, 70, 130, 255),
+    tile_stroke: rgba8(90, 96, 104, 255),
+    light: rgba8(225, 225, 220, 255),
+    dark: rgba8(0, 0, 0, 255),
+    rings: false,
+};
+
+// the highlights are orange and sky blue of the Okabe-Ito palette, which stay apart with every
+// common kind of color blindness
+pub const ACCESSIBLE: Palette = Palette {
+    background: rgba8(40, 60, 75, 255),
+    change_highlight: rgba8(86, 180, 233, 255),
+    move_highlight: rgba8(230, 159, 0, 255),
+    tile_stroke: rgba8(240, 240, 240, 255),
+    light: rgba8(255, 255, 255, 255),
+    dark: rgba8(0, 0, 0, 255),
+    rings: true,
+};
+
+// the lines of graphs, the colors of the Okabe-Ito palette, which can be told apart with every
+// common kind of color blindness, on the backgrounds of every palette
+pub const SERIES_COLORS: [Rgba8; 7] = [
+    rgba8(230, 159, 0, 255),
+    rgba8(86, 180, 233, 255),
+    rgba8(0, 158, 115, 255),
+    rgba8(240, 228, 66, 255),
+    rgba8(0, 114, 178, 255),
+    rgba8(213, 94, 0, 255),
+    rgba8(204, 121, 167, 255),
+];
+
+pub fn palette(theme: Theme) -> Palette {
+    match theme {
+        Theme::Classic => CLASSIC,
+        Theme::Dark => DARK,
+        Theme::Accessible => ACCESSIBLE,
+    }
+}
+
+// reimplementation required, so it is a constant function
+pub const fn rgba8(red: u8, green: u8, blue: u8, alpha: u8) -> Rgba8 {
+    Rgba8 {
+        color: Rgb8 {
+            red,
+            green,
+            blue,
+            standard: std::marker::PhantomData,
+        },
+        alpha,
+    }
+}
@@ -0,0 +1,135 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::*;
+
+// set for the whole run with --verify-moves
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+const DIRECTIONS: [(isize, isize); 8] = [
+    (-1, -1),
+    (0, -1),
+    (1, -1),
+    (-1, 0),
+    (1, 0),
+    (-1, 1),
+    (0, 1),
+    (1, 1),
+];
+
+/// Makes `Game::play` check every move with `check_move`, for the rest of the run.
+pub fn enable() {
+    ENABLED.store(true, Ordering::Relaxed);
+}
+
+pub fn is_enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+/// Plays `mv` in `before` again with an implementation independent of the core library, which
+/// scans from the move in every direction, and compares the result with `after`, the position the
+/// core library played it to. A disagreement is a bug of the GUI, not of the AIs, so it is
+/// described for a bug report.
+pub fn check_move(before: &Pos, mv: Vec2, after: &Pos) -> Result<(), String> {
+    let player = before.next_player;
+    let flips = scan_flips(&before.board, mv, player);
+
+    if flips.is_empty() {
+        return Err(format!(
+            "{} was played, but it isn't a valid move for {player}",
+            mv.move_string()
+        ));
+    }
+
+    let mut board = before.board;
+    board.set(mv, player);
+
+    for coor in flips {
+        board.set(coor, player);
+    }
+
+    if let Some(coor) = Vec2::board_iter().find(|&coor| board.get(coor) != after.board.get(coor)) {
+        return Err(format!(
+            "after {}, {} should be {} but it is {}",
+            mv.move_string(),
+            coor.move_string(),
+            tile_char(board.get(coor)),
+            tile_char(after.board.get(coor))
+        ));
+    }
+
+    let has_move = |tile| Vec2::board_iter().any(|coor| !scan_flips(&board, coor, tile).is_empty());
+
+    // the opponent moves next, unless it has to pass
+    let next_player = [player.opponent(), player]
+        .into_iter()
+        .find(|&tile| has_move(tile));
+
+    match next_player {
+        None if !after.is_game_over() => Err(format!(
+            "after {}, neither player has a valid move, but the game isn't over",
+            mv.move_string()
+        )),
+        Some(tile) if after.is_game_over() || after.next_player != tile => Err(format!(
+            "after {}, {tile} should move next",
+            mv.move_string()
+        )),
+        _ => Ok(()),
+    }
+}
+
+// the disks of the opponent of `player` which playing `mv` would turn over
+fn scan_flips(board: &Board, mv: Vec2, player: Tile) -> Vec<Vec2> {
+    let on_board = |x: isize, y: isize| (0..8).contains(&x) && (0..8).contains(&y);
+
+    if board.get(mv) != Tile::Empty {
+        return Vec::new();
+    }
+
+    let mut flips = Vec::new();
+
+    for (dx, dy) in DIRECTIONS {
+        let mut line = Vec::new();
+        let (mut x, mut y) = (mv.x + dx, mv.y + dy);
+
+        while on_board(x, y) && board.get(Vec2::new(x, y)) == player.opponent() {
+            line.push(Vec2::new(x, y));
+            (x, y) = (x + dx, y + dy);
+        }
+
+        if on_board(x, y) && board.get(Vec2::new(x, y)) == player {
+            flips.extend(line);
+        }
+    }
+
+    flips
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn agrees_with_core() {
+        let mut pos = Pos::new();
+
+        while !pos.is_game_over() {
+            for mv in pos.valid_moves() {
+                assert_eq!(check_move(&pos, mv, &pos.play_clone(mv)), Ok(()));
+            }
+
+            let mv = pos.valid_moves()[0];
+            pos.play(mv);
+        }
+    }
+
+    #[test]
+    fn finds_wrong_flips() {
+        let pos = Pos::new();
+        let mv = pos.valid_moves()[0];
+
+        let mut after = pos.play_clone(mv);
+        after.board.set(mv, Tile::Empty);
+
+        assert!(check_move(&pos, mv, &after).is_err());
+    }
+}
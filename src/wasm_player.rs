@@ -0,0 +1,324 @@
+use std::{
+    fmt,
+    io::{self, Write},
+    path::{Path, PathBuf},
+    sync::OnceLock,
+    thread,
+    time::Duration,
+};
+use tokio::task;
+use wasi_common::{
+    pipe::{ReadPipe, WritePipe},
+    I32Exit,
+};
+use wasmtime::{Config, Engine, Linker, Module, Store, StoreLimits, StoreLimitsBuilder, Trap};
+use wasmtime_wasi::{sync::WasiCtxBuilder, WasiCtx};
+
+use crate::{ai::*, error::Error, *};
+
+// how often the epoch of the engine is increased, which is how precisely time limits are kept
+const TICK: Duration = Duration::from_millis(10);
+// the most memory an AI can use, so that games played at once can't run out of it
+const MAX_MEMORY: usize = 512 * 1024 * 1024;
+
+/// An AI compiled to WebAssembly for WASI (`wasm:<path>`), run in a sandbox without access to
+/// files, the network or the environment. It is given its input on stdin and writes its output
+/// on stdout, like AIs run as programs.
+///
+/// Instead of the process being killed, the AI is interrupted once its time limit is up, so
+/// whatever it wrote until then is kept.
+pub struct WasmPlayer {
+    pub path: PathBuf,
+    pub limit: Limit,
+    pub run_handle: Option<AIRunHandle>,
+    pub deterministic: bool,
+    // compiled once, shared by the clones of the player
+    module: Module,
+}
+
+// how a run of the module ended
+enum Exit {
+    Finished,
+    Interrupted,
+    Failed(String),
+    // it wrote more than `MAX_OUTPUT_SIZE` bytes
+    Overflowed,
+}
+
+// what a run of the module has access to
+struct State {
+    wasi: WasiCtx,
+    limits: StoreLimits,
+}
+
+// stdout of the module, which fails writes once `MAX_OUTPUT_SIZE` bytes were written, like the
+// pipes of programs
+#[derive(Debug, Default)]
+struct BoundedOutput {
+    output: Vec<u8>,
+    overflowed: bool,
+}
+
+impl Write for BoundedOutput {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let room = MAX_OUTPUT_SIZE - self.output.len();
+
+        if buf.len() > room {
+            self.output.extend_from_slice(&buf[..room]);
+            self.overflowed = true;
+
+            return Err(io::Error::other("output limit exceeded"));
+        }
+
+        self.output.extend_from_slice(buf);
+
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl WasmPlayer {
+    pub fn load(path: &Path, limit: Limit) -> Result<Self, Error> {
+        let module = Module::from_file(engine(), path).map_err(|err| {
+            Error::InvalidEngine(format!(
+                "Unable to load WebAssembly AI '{}': {err:#}",
+                path.display()
+            ))
+        })?;
+
+        Ok(Self {
+            path: path.to_owned(),
+            limit,
+            run_handle: None,
+            deterministic: false,
+            module,
+        })
+    }
+
+    pub fn input(&self, pos: Pos) -> String {
+        engine_input(pos, self.limit, self.deterministic)
+    }
+
+    pub fn run(&mut self, pos: Pos) {
+        self.run_handle = Some(AIRunHandle::spawn(run_wasm(
+            self.module.clone(),
+            self.input(pos),
+            deadline_ticks(self.limit.time_limit()),
+        )));
+    }
+
+    pub fn try_clone(&self) -> Result<Self, Error> {
+        match self.run_handle {
+            None => Ok(Self {
+                path: self.path.clone(),
+                limit: self.limit,
+                run_handle: None,
+                deterministic: self.deterministic,
+                module: self.module.clone(),
+            }),
+            Some(_) => Err(Error::CloneRunningEngine(format!(
+                "wasm:{}",
+                self.path.display()
+            ))),
+        }
+    }
+}
+
+impl fmt::Debug for WasmPlayer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WasmPlayer")
+            .field("path", &self.path)
+            .field("limit", &self.limit)
+            .field("run_handle", &self.run_handle)
+            .field("deterministic", &self.deterministic)
+            .finish_non_exhaustive()
+    }
+}
+
+// All modules are compiled by one engine, whose epoch is increased every tick, interrupting the
+// AIs whose deadline passed.
+fn engine() -> &'static Engine {
+    static ENGINE: OnceLock<Engine> = OnceLock::new();
+
+    ENGINE.get_or_init(|| {
+        let mut config = Config::new();
+        config.epoch_interruption(true);
+
+        let engine = Engine::new(&config).expect("Error creating WebAssembly engine");
+
+        let ticker = engine.clone();
+        thread::spawn(move || loop {
+            thread::sleep(TICK);
+            ticker.increment_epoch();
+        });
+
+        engine
+    })
+}
+
+// the number of ticks after which an AI with `time_limit` is interrupted
+fn deadline_ticks(time_limit: Duration) -> u64 {
+    // the deadline is counted from the next tick, which may come right away
+    (time_limit.as_millis() / TICK.as_millis() + 2) as u64
+}
+
+async fn run_wasm(module: Module, input: String, ticks: u64) -> AIRunResult {
+    let Ok((exit, output)) = task::spawn_blocking(move || execute(&module, input, ticks)).await
+    else {
+        return AIRunResult::InvalidOuput(Error::InvalidOutput(
+            "WebAssembly AI stopped unexpectedly".to_owned(),
+        ));
+    };
+
+    wasm_result(exit, &output)
+}
+
+fn wasm_result(exit: Exit, output: &[u8]) -> AIRunResult {
+    let output = String::from_utf8_lossy(output);
+
+    match exit {
+        Exit::Interrupted => AIRunResult::TimeOut {
+            partial_output: sanitize(&output),
+        },
+        Exit::Failed(err) => AIRunResult::InvalidOuput(Error::InvalidOutput(format!(
+            "WebAssembly AI failed: {err}"
+        ))),
+        Exit::Overflowed => AIRunResult::InvalidOuput(output_overflow("stdout")),
+        Exit::Finished => match parse_output(&output) {
+            Ok(output) => AIRunResult::Success(output),
+            Err(err) => AIRunResult::InvalidOuput(err),
+        },
+    }
+}
+
+// runs the module until it returns or is interrupted after `ticks`, with what it wrote
+fn execute(module: &Module, input: String, ticks: u64) -> (Exit, Vec<u8>) {
+    let stdout = WritePipe::new(BoundedOutput::default());
+
+    let wasi = WasiCtxBuilder::new()
+        .stdin(Box::new(ReadPipe::from(input)))
+        .stdout(Box::new(stdout.clone()))
+        .build();
+
+    let limits = StoreLimitsBuilder::new().memory_size(MAX_MEMORY).build();
+
+    let mut store = Store::new(module.engine(), State { wasi, limits });
+    store.limiter(|state| &mut state.limits);
+    store.set_epoch_deadline(ticks);
+
+    let result = run_module(&mut store, module);
+
+    // the store holds the other reference to the pipe
+    drop(store);
+
+    let stdout = stdout.try_into_inner().unwrap_or_default();
+
+    // the module may have ended in any way after its write failed
+    let exit = if stdout.overflowed {
+        Exit::Overflowed
+    } else {
+        exit_of(result)
+    };
+
+    (exit, stdout.output)
+}
+
+fn exit_of(result: wasmtime::Result<()>) -> Exit {
+    match result {
+        Ok(()) => Exit::Finished,
+        Err(err) => match (err.downcast_ref::<I32Exit>(), err.downcast_ref::<Trap>()) {
+            (Some(I32Exit(0)), _) => Exit::Finished,
+            (Some(I32Exit(code)), _) => Exit::Failed(format!("exited with code {code}")),
+            (_, Some(Trap::Interrupt)) => Exit::Interrupted,
+            _ => Exit::Failed(format!("{err:#}")),
+        },
+    }
+}
+
+fn run_module(store: &mut Store<State>, module: &Module) -> wasmtime::Result<()> {
+    let mut linker = Linker::new(module.engine());
+    wasmtime_wasi::add_to_linker(&mut linker, |state: &mut State| &mut state.wasi)?;
+
+    linker.module(&mut *store, "", module)?;
+
+    linker
+        .get_default(&mut *store, "")?
+        .typed::<(), ()>(&*store)?
+        .call(&mut *store, ())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ticks_of_time_limits() {
+        assert_eq!(deadline_ticks(Duration::ZERO), 2);
+        assert_eq!(deadline_ticks(Duration::from_millis(5)), 2);
+        assert_eq!(deadline_ticks(Duration::from_secs(1)), 102);
+    }
+
+    #[test]
+    fn exits_of_modules() {
+        assert!(matches!(exit_of(Ok(())), Exit::Finished));
+        assert!(matches!(exit_of(Err(I32Exit(0).into())), Exit::Finished));
+        assert!(matches!(
+            exit_of(Err(Trap::Interrupt.into())),
+            Exit::Interrupted
+        ));
+
+        let Exit::Failed(err) = exit_of(Err(I32Exit(3).into())) else {
+            panic!("a module exiting with an error code didn't fail");
+        };
+        assert_eq!(err, "exited with code 3");
+
+        assert!(matches!(
+            exit_of(Err(Trap::UnreachableCodeReached.into())),
+            Exit::Failed(_)
+        ));
+    }
+
+    #[test]
+    fn bounded_output() {
+        let mut stdout = BoundedOutput::default();
+
+        assert!(stdout.write_all(&[b' '; MAX_OUTPUT_SIZE - 1]).is_ok());
+        assert!(!stdout.overflowed);
+
+        assert!(stdout.write_all(b"e6").is_err());
+        assert!(stdout.overflowed);
+        assert_eq!(stdout.output.len(), MAX_OUTPUT_SIZE);
+    }
+
+    #[test]
+    fn results_of_runs() {
+        let AIRunResult::Success(output) = wasm_result(Exit::Finished, b"e6\n") else {
+            panic!("the output of the module was rejected");
+        };
+        assert_eq!(output.mv, Vec2::new(4, 5));
+
+        assert!(matches!(
+            wasm_result(Exit::Finished, b"e9\n"),
+            AIRunResult::InvalidOuput(Error::InvalidOutput(_))
+        ));
+        assert!(matches!(
+            wasm_result(Exit::Failed("trapped".to_owned()), b"e6\n"),
+            AIRunResult::InvalidOuput(Error::InvalidOutput(_))
+        ));
+
+        let overflowed = wasm_result(Exit::Overflowed, b"e6\n");
+        let AIRunResult::InvalidOuput(Error::InvalidOutput(err)) = overflowed else {
+            panic!("a module writing too much wasn't rejected");
+        };
+        assert!(err.contains("1024 KB to stdout"));
+
+        // whatever was written before the interruption is kept
+        let AIRunResult::TimeOut { partial_output } = wasm_result(Exit::Interrupted, b"e") else {
+            panic!("an interrupted module didn't time out");
+        };
+        assert_eq!(partial_output, "e");
+    }
+}